@@ -55,7 +55,8 @@ use namada::ledger::gas::TxGasMeter;
 use namada::ledger::ibc::storage::{channel_key, connection_key};
 use namada::ledger::native_vp::ibc::get_dummy_header;
 use namada::ledger::queries::{
-    Client, EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
+    Client, EncodedResponseQuery, RequestCtx, RequestQuery, Router,
+    DEFAULT_MAX_REQUEST_PATH_LEN, RPC,
 };
 use namada::state::StorageRead;
 use namada::tendermint_rpc::{self};
@@ -392,6 +393,7 @@ impl BenchShell {
             &mut TxGasMeter::new_from_sub_limit(u64::MAX.into()),
             &TxIndex(0),
             tx,
+            None,
             &mut self.inner.vp_wasm_cache,
             &mut self.inner.tx_wasm_cache,
         )
@@ -733,6 +735,8 @@ impl Client for BenchShell {
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
+            max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+            request_timing_hook: None,
         };
 
         if request.path == "/shell/dry_run_tx" {
@@ -287,6 +287,11 @@ impl Finalized {
             implicit_vp,
             epochs_per_year,
             max_signatures_per_transaction,
+            max_value_bytes,
+            max_writes,
+            max_events,
+            max_verifiers,
+            max_vp_previews,
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
             max_block_gas,
@@ -333,6 +338,11 @@ impl Finalized {
             pos_inflation_amount: Amount::native_whole(pos_inflation_amount),
             max_proposal_bytes,
             max_signatures_per_transaction,
+            max_value_bytes,
+            max_writes,
+            max_events,
+            max_verifiers,
+            max_vp_previews,
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
             max_block_gas,
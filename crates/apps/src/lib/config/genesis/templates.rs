@@ -280,6 +280,16 @@ pub struct ChainParams<T: TemplateValidation> {
     pub epochs_per_year: u64,
     /// Maximum number of signature per transaction
     pub max_signatures_per_transaction: u8,
+    /// Max size, in bytes, of a value written by a transaction
+    pub max_value_bytes: u64,
+    /// Max number of keys written by a transaction
+    pub max_writes: u64,
+    /// Max number of events emitted by a transaction
+    pub max_events: u64,
+    /// Max number of verifiers inserted by a transaction
+    pub max_verifiers: u64,
+    /// Max number of VP previews requested by a transaction
+    pub max_vp_previews: u64,
     /// Max gas for block
     pub max_block_gas: u64,
     /// Fee unshielding gas limit
@@ -306,6 +316,11 @@ impl ChainParams<Unvalidated> {
             implicit_vp,
             epochs_per_year,
             max_signatures_per_transaction,
+            max_value_bytes,
+            max_writes,
+            max_events,
+            max_verifiers,
+            max_vp_previews,
             max_block_gas,
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
@@ -351,6 +366,11 @@ impl ChainParams<Unvalidated> {
             implicit_vp,
             epochs_per_year,
             max_signatures_per_transaction,
+            max_value_bytes,
+            max_writes,
+            max_events,
+            max_verifiers,
+            max_vp_previews,
             max_block_gas,
             fee_unshielding_gas_limit,
             fee_unshielding_descriptions_limit,
@@ -3,6 +3,7 @@ use std::env;
 
 use color_eyre::eyre::Result;
 use eyre::WrapErr;
+use namada::vm::host_env::log_level::{set_default_log_level, LevelFilter};
 use tracing_appender::non_blocking::WorkerGuard;
 use tracing_log::LogTracer;
 use tracing_subscriber::filter::{Directive, EnvFilter};
@@ -18,6 +19,9 @@ const FMT_ENV_KEY: &str = "NAMADA_LOG_FMT";
 const DIR_ENV_KEY: &str = "NAMADA_LOG_DIR";
 // Env var to set rolling log frequency
 const ROLLING_ENV_KEY: &str = "NAMADA_LOG_ROLLING";
+// Env var to set the level of the wasm host calls traces (e.g. "debug" to
+// enable them or "info" to silence them)
+const HOST_CALLS_ENV_KEY: &str = "NAMADA_LOG_HOST_CALLS";
 
 const LOG_FILE_NAME_PREFIX: &str = "namada.log";
 
@@ -45,6 +49,7 @@ pub fn init_from_env_or(
     let filter = filter_from_env_or(default);
     let guard = set_subscriber(filter)?;
     init_log_tracer()?;
+    init_host_calls_log_level()?;
     Ok(guard)
 }
 
@@ -58,6 +63,18 @@ pub fn init_log_tracer() -> Result<()> {
     LogTracer::init().wrap_err("Failed to initialize log adapter")
 }
 
+/// Set the process-wide level of the wasm host calls traces from env var, if
+/// any. Runners configured with their own level are not affected.
+pub fn init_host_calls_log_level() -> Result<()> {
+    if let Ok(level) = env::var(HOST_CALLS_ENV_KEY) {
+        let level: LevelFilter = level.parse().wrap_err_with(|| {
+            format!("Invalid log level set for {HOST_CALLS_ENV_KEY}")
+        })?;
+        set_default_log_level(level);
+    }
+    Ok(())
+}
+
 pub fn set_subscriber(filter: EnvFilter) -> Result<Option<WorkerGuard>> {
     let with_color = if let Ok(val) = env::var(COLOR_ENV_KEY) {
         val.to_ascii_lowercase() != "false"
@@ -19,6 +19,7 @@ use namada::state::{
 };
 use namada::token::conversion::update_allowed_conversions;
 use namada::tx::data::protocol::ProtocolTxType;
+use namada::types::hash::Hash;
 use namada::types::key::tm_raw_hash_to_string;
 use namada::types::storage::{BlockHash, BlockResults, Epoch, Header};
 use namada::vote_ext::ethereum_events::MultiSignedEthEvent;
@@ -198,6 +199,8 @@ where
         // Tracks the accepted transactions
         self.wl_storage.storage.block.results = BlockResults::default();
         let mut changed_keys = BTreeSet::new();
+        // The keys changed by each applied tx, indexed by its hash
+        let mut tx_changed_keys = vec![];
         for (tx_index, processed_tx) in req.txs.iter().enumerate() {
             let tx = if let Ok(tx) = Tx::try_from(processed_tx.tx.as_ref()) {
                 tx
@@ -408,6 +411,11 @@ where
                 },
             };
 
+            // The fee payer of a decrypted tx is that of its wrapper
+            let fee_payer = embedding_wrapper
+                .as_ref()
+                .and_then(|wrapper| wrapper.header().wrapper())
+                .map(|wrapper| wrapper.fee_payer());
             let tx_result = protocol::check_tx_allowed(&tx, &self.wl_storage)
                 .and_then(|()| {
                     protocol::dispatch_tx(
@@ -418,6 +426,7 @@ where
                                 .try_into()
                                 .expect("transaction index out of bounds"),
                         ),
+                        fee_payer.as_ref(),
                         &mut tx_gas_meter,
                         &mut self.wl_storage,
                         &mut self.vp_wasm_cache,
@@ -463,6 +472,12 @@ where
                             }
                             changed_keys
                                 .extend(result.changed_keys.iter().cloned());
+                            if let Ok(hash) =
+                                Hash::try_from(tx_event["hash"].as_str())
+                            {
+                                tx_changed_keys
+                                    .push((hash, result.changed_keys.clone()));
+                            }
                             stats.increment_successful_txs();
                             if let Some(wrapper) = embedding_wrapper {
                                 self.commit_inner_tx_hash(wrapper);
@@ -488,6 +503,7 @@ where
                                     // Add the IBC event besides the tx_event
                                     let mut event = Event::from(ibc_event);
                                     event["height"] = height.to_string();
+                                    event["tx_index"] = tx_index.to_string();
                                     event
                                 })
                                 // eth bridge events
@@ -584,6 +600,7 @@ where
                     }
                 }
             }
+            tx_event["tx_index"] = tx_index.to_string();
             response.events.push(tx_event);
         }
 
@@ -625,6 +642,7 @@ where
         )?;
 
         self.event_log_mut().log_events(response.events.clone());
+        self.event_log_mut().log_tx_changed_keys(tx_changed_keys);
         tracing::debug!("End finalize_block {height} of epoch {current_epoch}");
 
         Ok(response)
@@ -46,6 +46,9 @@ use namada::ledger::protocol::{
     apply_wasm_tx, get_fee_unshielding_transaction,
     get_transfer_hash_from_storage, ShellParams,
 };
+use namada::ledger::queries::{
+    RequestTimingHook, DEFAULT_MAX_REQUEST_PATH_LEN,
+};
 use namada::ledger::{parameters, pos, protocol};
 use namada::parameters::validate_tx_bytes;
 use namada::proof_of_stake::slashing::{process_slashes, slash};
@@ -357,6 +360,11 @@ where
     /// limit the how many block heights in the past can the storage be
     /// queried for reading values.
     storage_read_past_height_limit: Option<u64>,
+    /// Taken from config `max_request_path_len`. The query paths longer
+    /// than this number of bytes are rejected.
+    max_request_path_len: usize,
+    /// When set, the hook is invoked with the timing of every query.
+    pub request_timing_hook: Option<RequestTimingHook>,
     /// Proposal execution tracking
     pub proposal_data: HashSet<u64>,
     /// Log of events emitted by `FinalizeBlock` ABCI calls.
@@ -415,6 +423,10 @@ where
         let mode = config.shell.tendermint_mode;
         let storage_read_past_height_limit =
             config.shell.storage_read_past_height_limit;
+        let max_request_path_len = config
+            .shell
+            .max_request_path_len
+            .unwrap_or(DEFAULT_MAX_REQUEST_PATH_LEN);
         if !Path::new(&base_dir).is_dir() {
             std::fs::create_dir(&base_dir)
                 .expect("Creating directory for Namada should not fail");
@@ -534,6 +546,8 @@ where
                 tx_wasm_compilation_cache as usize,
             ),
             storage_read_past_height_limit,
+            max_request_path_len,
+            request_timing_hook: None,
             proposal_data: HashSet::new(),
             // TODO: config event log params
             event_log: EventLog::default(),
@@ -565,6 +579,7 @@ where
     pub fn last_state(&mut self) -> response::Info {
         let mut response = response::Info {
             last_block_height: tendermint::block::Height::from(0_u32),
+            app_version: namada::ledger::queries::PROTOCOL_VERSION,
             ..Default::default()
         };
         let result = self.wl_storage.storage.get_state();
@@ -1522,6 +1537,7 @@ where
     let result = apply_wasm_tx(
         unshield,
         &TxIndex::default(),
+        Some(&wrapper.fee_payer()),
         ShellParams::new(
             &mut TxGasMeter::new(fee_unshielding_gas_limit),
             temp_wl_storage,
@@ -2137,6 +2153,11 @@ mod test_utils {
             implicit_vp_code_hash: Default::default(),
             epochs_per_year: 365,
             max_signatures_per_transaction: 10,
+            max_value_bytes: 1024 * 1024,
+            max_writes: 10_000,
+            max_events: 1_000,
+            max_verifiers: 128,
+            max_vp_previews: 16,
             staked_ratio: Default::default(),
             pos_inflation_amount: Default::default(),
             fee_unshielding_gas_limit: 0,
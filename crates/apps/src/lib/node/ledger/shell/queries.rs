@@ -1,7 +1,7 @@
 //! Shell methods for querying state
 
-use namada::ledger::dry_run_tx;
 use namada::ledger::queries::{RequestCtx, ResponseQuery};
+use namada::ledger::{dry_run_tx, gas_estimate, simulate_tx};
 use namada::token;
 use namada::types::address::Address;
 
@@ -24,11 +24,17 @@ where
             vp_wasm_cache: self.vp_wasm_cache.read_only(),
             tx_wasm_cache: self.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: self.storage_read_past_height_limit,
+            max_request_path_len: self.max_request_path_len,
+            request_timing_hook: self.request_timing_hook.clone(),
         };
 
         // Invoke the root RPC handler - returns borsh-encoded data on success
         let result = if query.path == "/shell/dry_run_tx" {
             dry_run_tx(ctx, &query)
+        } else if query.path == "/shell/tx/simulate" {
+            simulate_tx(ctx, &query)
+        } else if query.path == "/shell/tx/gas_estimate" {
+            gas_estimate(ctx, &query)
         } else {
             namada::ledger::queries::handle_path(ctx, &query)
         };
@@ -11,11 +11,12 @@ use data_encoding::HEXUPPER;
 use itertools::Either;
 use lazy_static::lazy_static;
 use namada::eth_bridge::oracle::config::Config as OracleConfig;
-use namada::ledger::dry_run_tx;
 use namada::ledger::events::log::dumb_queries;
 use namada::ledger::queries::{
-    EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
+    EncodedResponseQuery, RequestCtx, RequestQuery, Router,
+    DEFAULT_MAX_REQUEST_PATH_LEN, RPC,
 };
+use namada::ledger::{dry_run_tx, gas_estimate, simulate_tx};
 use namada::proof_of_stake::pos_queries::PosQueries;
 use namada::proof_of_stake::storage::{
     read_consensus_validator_set_addresses_with_stake,
@@ -695,9 +696,15 @@ impl<'a> Client for &'a MockNode {
             vp_wasm_cache: borrowed.vp_wasm_cache.read_only(),
             tx_wasm_cache: borrowed.tx_wasm_cache.read_only(),
             storage_read_past_height_limit: None,
+            max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+            request_timing_hook: None,
         };
         if request.path == "/shell/dry_run_tx" {
             dry_run_tx(ctx, &request)
+        } else if request.path == "/shell/tx/simulate" {
+            simulate_tx(ctx, &request)
+        } else if request.path == "/shell/tx/gas_estimate" {
+            gas_estimate(ctx, &request)
         } else {
             rpc.handle(ctx, &request)
         }
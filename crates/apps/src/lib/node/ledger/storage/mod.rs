@@ -167,6 +167,11 @@ mod tests {
             implicit_vp_code_hash: Default::default(),
             epochs_per_year: 365,
             max_signatures_per_transaction: 10,
+            max_value_bytes: 1024 * 1024,
+            max_writes: 10_000,
+            max_events: 1_000,
+            max_verifiers: 128,
+            max_vp_previews: 16,
             staked_ratio: Default::default(),
             pos_inflation_amount: Default::default(),
             fee_unshielding_gas_limit: 0,
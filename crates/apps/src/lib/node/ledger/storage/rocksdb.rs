@@ -1570,7 +1570,15 @@ impl<'iter> DBIter<'iter> for RocksDB {
         &'iter self,
         prefix: Option<&Key>,
     ) -> PersistentPrefixIterator<'iter> {
-        iter_subspace_prefix(self, prefix)
+        iter_subspace_prefix(self, prefix, None)
+    }
+
+    fn iter_prefix_after(
+        &'iter self,
+        prefix: Option<&Key>,
+        last_key: &str,
+    ) -> PersistentPrefixIterator<'iter> {
+        iter_subspace_prefix(self, prefix, Some(last_key))
     }
 
     fn iter_results(&'iter self) -> PersistentPrefixIterator<'iter> {
@@ -1611,19 +1619,26 @@ impl<'iter> DBIter<'iter> for RocksDB {
             .expect("{REPLAY_PROTECTION_CF} column family should exist");
 
         let stripped_prefix = Some(replay_protection::last_prefix());
-        iter_prefix(self, replay_protection_cf, stripped_prefix.as_ref(), None)
+        iter_prefix(
+            self,
+            replay_protection_cf,
+            stripped_prefix.as_ref(),
+            None,
+            None,
+        )
     }
 }
 
 fn iter_subspace_prefix<'iter>(
     db: &'iter RocksDB,
     prefix: Option<&Key>,
+    last_key: Option<&str>,
 ) -> PersistentPrefixIterator<'iter> {
     let subspace_cf = db
         .get_column_family(SUBSPACE_CF)
         .expect("{SUBSPACE_CF} column family should exist");
     let stripped_prefix = None;
-    iter_prefix(db, subspace_cf, stripped_prefix, prefix)
+    iter_prefix(db, subspace_cf, stripped_prefix, prefix, last_key)
 }
 
 fn iter_diffs_prefix<'a>(
@@ -1646,18 +1661,20 @@ fn iter_diffs_prefix<'a>(
             .unwrap(),
     );
     // get keys without the `stripped_prefix`
-    iter_prefix(db, diffs_cf, stripped_prefix.as_ref(), prefix)
+    iter_prefix(db, diffs_cf, stripped_prefix.as_ref(), prefix, None)
 }
 
 /// Create an iterator over key-vals in the given CF matching the given
 /// prefix(es). If any, the `stripped_prefix` is matched first and will be
 /// removed from the matched keys. If any, the second `prefix` is matched
-/// against the stripped keys and remains in the matched keys.
+/// against the stripped keys and remains in the matched keys. If any, the
+/// iterator is positioned after the `last_key` of the stripped keys.
 fn iter_prefix<'a>(
     db: &'a RocksDB,
     cf: &'a ColumnFamily,
     stripped_prefix: Option<&Key>,
     prefix: Option<&Key>,
+    last_key: Option<&str>,
 ) -> PersistentPrefixIterator<'a> {
     let stripped_prefix = match stripped_prefix {
         Some(p) if !p.is_empty() => format!("{p}/"),
@@ -1669,11 +1686,20 @@ fn iter_prefix<'a>(
         }
         _ => stripped_prefix.clone(),
     };
-    let read_opts = make_iter_read_opts(Some(prefix.clone()));
+    // The smallest key after the `last_key` is the `last_key` followed by a
+    // nul char. The seek cannot start before the prefix.
+    let start = match last_key {
+        Some(last_key) => std::cmp::max(
+            format!("{stripped_prefix}{last_key}\0"),
+            prefix.clone(),
+        ),
+        None => prefix.clone(),
+    };
+    let read_opts = make_iter_read_opts(Some(prefix));
     let iter = db.0.iterator_cf_opt(
         cf,
         read_opts,
-        IteratorMode::From(prefix.as_bytes(), Direction::Forward),
+        IteratorMode::From(start.as_bytes(), Direction::Forward),
     );
     PersistentPrefixIterator(PrefixIterator::new(iter, stripped_prefix))
 }
@@ -1995,19 +2021,39 @@ mod test {
             .iter_prefix(Some(&prefix_0))
             .map(|(key, _val, _)| Key::parse(key).unwrap())
             .collect();
-        itertools::assert_equal(keys_0, itered_keys);
+        itertools::assert_equal(keys_0.clone(), itered_keys);
 
         let itered_keys: Vec<Key> = db
             .iter_prefix(Some(&prefix_1))
             .map(|(key, _val, _)| Key::parse(key).unwrap())
             .collect();
-        itertools::assert_equal(keys_1, itered_keys);
+        itertools::assert_equal(keys_1.clone(), itered_keys);
 
         let itered_keys: Vec<Key> = db
             .iter_prefix(None)
             .map(|(key, _val, _)| Key::parse(key).unwrap())
             .collect();
-        itertools::assert_equal(all_keys, itered_keys);
+        itertools::assert_equal(all_keys.clone(), itered_keys);
+
+        // Iterating after a key seeks past it
+        let itered_keys: Vec<Key> = db
+            .iter_prefix_after(Some(&prefix_0), &keys_0[0].to_string())
+            .map(|(key, _val, _)| Key::parse(key).unwrap())
+            .collect();
+        itertools::assert_equal(keys_0[1..].to_vec(), itered_keys);
+
+        // A key before the prefix starts the iteration at the prefix
+        let itered_keys: Vec<Key> = db
+            .iter_prefix_after(Some(&prefix_1), &all_keys[0].to_string())
+            .map(|(key, _val, _)| Key::parse(key).unwrap())
+            .collect();
+        itertools::assert_equal(keys_1.clone(), itered_keys);
+
+        // Nothing is left after the last key
+        assert!(db
+            .iter_prefix_after(Some(&prefix_1), &keys_1[2].to_string())
+            .next()
+            .is_none());
     }
 
     #[test]
@@ -13,6 +13,8 @@ use thiserror::Error;
 use super::address::HASH_LEN;
 use crate::ibc::apps::transfer::types::msgs::transfer::MsgTransfer;
 use crate::ibc::apps::transfer::types::{Memo, PrefixedDenom, TracePath};
+use crate::ibc::core::channel::types::packet::Packet;
+use crate::ibc::core::channel::types::timeout::TimeoutHeight;
 use crate::ibc::core::handler::types::events::{
     Error as IbcEventError, IbcEvent as RawIbcEvent,
 };
@@ -186,6 +188,46 @@ impl TryFrom<RawIbcEvent> for IbcEvent {
     }
 }
 
+impl IbcEvent {
+    /// The event of a packet sent by a transaction, to be relayed to the
+    /// counterparty chain
+    pub fn send_packet(packet: &Packet) -> Self {
+        let timeout_height = match packet.timeout_height_on_b {
+            TimeoutHeight::Never => "0-0".to_string(),
+            TimeoutHeight::At(height) => height.to_string(),
+        };
+        let attributes = HashMap::from([
+            ("packet_data_hex".to_string(), HEXLOWER.encode(&packet.data)),
+            ("packet_timeout_height".to_string(), timeout_height),
+            (
+                "packet_timeout_timestamp".to_string(),
+                packet.timeout_timestamp_on_b.nanoseconds().to_string(),
+            ),
+            ("packet_sequence".to_string(), packet.seq_on_a.to_string()),
+            (
+                "packet_src_port".to_string(),
+                packet.port_id_on_a.to_string(),
+            ),
+            (
+                "packet_src_channel".to_string(),
+                packet.chan_id_on_a.to_string(),
+            ),
+            (
+                "packet_dst_port".to_string(),
+                packet.port_id_on_b.to_string(),
+            ),
+            (
+                "packet_dst_channel".to_string(),
+                packet.chan_id_on_b.to_string(),
+            ),
+        ]);
+        Self {
+            event_type: "send_packet".to_string(),
+            attributes,
+        }
+    }
+}
+
 /// Returns the trace path and the token string if the denom is an IBC
 /// denom.
 pub fn is_ibc_denom(denom: impl AsRef<str>) -> Option<(TracePath, String)> {
@@ -2,6 +2,8 @@
 
 use borsh::{BorshDeserialize, BorshSerialize};
 
+use crate::types::storage::Key;
+
 /// A result of a wasm call to host functions that may fail.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum HostEnvResult {
@@ -21,6 +23,29 @@ pub struct KeyVal {
     pub val: Vec<u8>,
 }
 
+/// A changed storage key with its values in the prior and the posterior state.
+/// It is used for the `vp_get_changes` WASM host_env function.
+#[derive(Clone, Debug, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct KeyChange {
+    /// The changed storage key
+    pub key: Key,
+    /// The value before the tx execution, if any
+    pub pre: Option<Vec<u8>>,
+    /// The value after the tx execution, if any
+    pub post: Option<Vec<u8>>,
+}
+
+/// A value read from the last committed block state together with its Merkle
+/// proof. It is used for the `vp_read_with_proof` WASM host_env function.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct ValueWithProof {
+    /// The value as arbitrary bytes
+    pub value: Vec<u8>,
+    /// The Tendermint `ProofOps` of the value's existence, encoded with
+    /// protobuf
+    pub proof: Vec<u8>,
+}
+
 impl HostEnvResult {
     /// Convert result to `i64`, which can be passed to wasm
     pub fn to_i64(self) -> i64 {
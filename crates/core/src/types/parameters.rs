@@ -44,6 +44,16 @@ pub struct Parameters {
     pub epochs_per_year: u64,
     /// Maximum number of signature per transaction
     pub max_signatures_per_transaction: u8,
+    /// Max size, in bytes, of a value written by a transaction
+    pub max_value_bytes: u64,
+    /// Max number of keys written by a transaction
+    pub max_writes: u64,
+    /// Max number of events emitted by a transaction
+    pub max_events: u64,
+    /// Max number of verifiers inserted by a transaction
+    pub max_verifiers: u64,
+    /// Max number of VP previews requested by a transaction
+    pub max_vp_previews: u64,
     /// PoS staked ratio (read + write for every epoch)
     pub staked_ratio: Dec,
     /// PoS inflation amount from the last epoch (read + write for every epoch)
@@ -56,6 +66,29 @@ pub struct Parameters {
     pub minimum_gas_price: BTreeMap<Address, token::Amount>,
 }
 
+/// The subset of the protocol parameters needed to construct and pay the fees
+/// of a transaction
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    BorshSchema,
+)]
+pub struct FeeParameters {
+    /// Max gas for block
+    pub max_block_gas: u64,
+    /// Fee unshielding gas limit
+    pub fee_unshielding_gas_limit: u64,
+    /// Fee unshielding descriptions limit
+    pub fee_unshielding_descriptions_limit: u64,
+    /// Map of the cost per gas unit for every token allowed for fee payment
+    pub minimum_gas_price: BTreeMap<Address, token::Amount>,
+}
+
 /// Epoch duration. A new epoch begins as soon as both the `min_num_of_blocks`
 /// and `min_duration` have passed since the beginning of the current epoch.
 #[derive(
@@ -11,6 +11,8 @@ pub enum VpSentinel {
     OutOfGas,
     /// Found invalid transaction signature
     InvalidSignature,
+    /// The Vp explicitly rejected the transaction with the given reason
+    Rejected(String),
 }
 
 impl VpSentinel {
@@ -24,6 +26,19 @@ impl VpSentinel {
         matches!(self, Self::InvalidSignature)
     }
 
+    /// Check if the Vp explicitly rejected the transaction
+    pub fn is_rejected(&self) -> bool {
+        matches!(self, Self::Rejected(_))
+    }
+
+    /// Get the reason given by the Vp for rejecting the transaction, if any
+    pub fn rejection_reason(&self) -> Option<&str> {
+        match self {
+            Self::Rejected(reason) => Some(reason),
+            _ => None,
+        }
+    }
+
     /// Set the sentinel for an out of gas error
     pub fn set_out_of_gas(&mut self) {
         *self = Self::OutOfGas
@@ -33,4 +48,23 @@ impl VpSentinel {
     pub fn set_invalid_signature(&mut self) {
         *self = Self::InvalidSignature
     }
+
+    /// Set the sentinel for an explicit rejection with the given reason,
+    /// unless an invalid signature has already been found
+    pub fn set_rejected(&mut self, reason: String) {
+        if !self.is_invalid_signature() {
+            *self = Self::Rejected(reason)
+        }
+    }
+
+    /// Merge the sentinel of a Vp evaluated by this Vp into this one. The
+    /// rejection of the evaluated Vp is only its verdict returned to this Vp,
+    /// so it's not merged, and an invalid signature is never downgraded.
+    pub fn merge_evaluated(&mut self, evaluated: Self) {
+        match evaluated {
+            Self::OutOfGas => self.set_out_of_gas(),
+            Self::InvalidSignature => self.set_invalid_signature(),
+            Self::None | Self::Rejected(_) => {}
+        }
+    }
 }
@@ -43,6 +43,8 @@ pub const STORAGE_ACCESS_GAS_PER_BYTE: u64 =
 /// The cost of writing data to storage, per byte
 pub const STORAGE_WRITE_GAS_PER_BYTE: u64 =
     MEMORY_ACCESS_GAS_PER_BYTE + 848 + STORAGE_OCCUPATION_GAS_PER_BYTE;
+/// The cost of verifying a Merkle proof, per byte of the proof
+pub const VERIFY_MERKLE_PROOF_GAS_PER_BYTE: u64 = 45;
 /// The cost of verifying a single signature of a transaction
 pub const VERIFY_TX_SIG_GAS: u64 = 9_793;
 /// The cost for requesting one more page in wasm (64KiB)
@@ -94,7 +96,7 @@ impl Gas {
 
     /// Converts the sub gas units to whole ones. If the sub units are not a
     /// multiple of the `SCALE` than ceil the quotient
-    fn get_whole_gas_units(&self) -> u64 {
+    pub fn get_whole_gas_units(&self) -> u64 {
         let quotient = self.sub / SCALE;
         if self.sub % SCALE == 0 {
             quotient
@@ -325,6 +327,11 @@ impl VpGasMeter {
             current_gas: Gas::default(),
         }
     }
+
+    /// Get the gas consumed by the VP alone
+    pub fn get_vp_consumed_gas(&self) -> Gas {
+        self.current_gas
+    }
 }
 
 impl VpsGas {
@@ -13,8 +13,10 @@ use arse_merkle_tree::{
 };
 use eth_bridge_pool::{BridgePoolProof, BridgePoolTree};
 use ics23::commitment_proof::Proof as Ics23Proof;
-use ics23::{CommitmentProof, ExistenceProof, NonExistenceProof};
-use ics23_specs::ibc_leaf_spec;
+use ics23::{
+    CommitmentProof, ExistenceProof, HostFunctionsManager, NonExistenceProof,
+};
+use ics23_specs::{ibc_leaf_spec, ibc_proof_specs, proof_specs};
 use namada_core::borsh::{BorshDeserialize, BorshSerialize, BorshSerializeExt};
 use namada_core::bytes::ByteBuf;
 use namada_core::types::address::{Address, InternalAddress};
@@ -814,6 +816,79 @@ impl From<Proof> for namada_core::tendermint::merkle::proof::ProofOps {
     }
 }
 
+/// Verify that the sub-tree and the base tree existence proofs prove that the
+/// key has the given value in a Merkle tree with the given root. The proofs
+/// of the keys in the Ethereum bridge pool sub-tree are not supported.
+pub fn verify_existence_proof<H: StorageHasher>(
+    root: &MerkleRoot,
+    key: &Key,
+    value: &[u8],
+    sub_proof: &CommitmentProof,
+    base_proof: &CommitmentProof,
+) -> bool {
+    let Ok((store_type, sub_key)) = StoreType::sub_key(key) else {
+        return false;
+    };
+    let specs = match store_type {
+        StoreType::Ibc => ibc_proof_specs::<H>(),
+        StoreType::Account | StoreType::PoS => proof_specs::<H>(),
+        StoreType::Base | StoreType::BridgePool => return false,
+    };
+    let paths = [sub_key.to_string(), store_type.to_string()];
+    let mut value = value.to_vec();
+    // First, the sub proof is verified. Next the base proof is verified with
+    // the sub root
+    for ((commitment_proof, spec), path) in [sub_proof, base_proof]
+        .into_iter()
+        .zip(specs.iter())
+        .zip(paths.iter())
+    {
+        let Some(Ics23Proof::Exist(existence_proof)) = &commitment_proof.proof
+        else {
+            return false;
+        };
+        let Ok(sub_root) = ics23::calculate_existence_root::<
+            HostFunctionsManager,
+        >(existence_proof) else {
+            return false;
+        };
+        if !ics23::verify_membership::<HostFunctionsManager>(
+            commitment_proof,
+            spec,
+            &sub_root,
+            path.as_bytes(),
+            &value,
+        ) {
+            return false;
+        }
+        value = sub_root;
+    }
+    value == root.0
+}
+
+/// Verify that the proof operations, as returned by the storage together with
+/// a value, prove that the key has the given value in a Merkle tree with the
+/// given root. Malformed proof operations don't verify.
+pub fn verify_existence_proof_ops<H: StorageHasher>(
+    root: &MerkleRoot,
+    key: &Key,
+    value: &[u8],
+    proof: &namada_core::tendermint::merkle::proof::ProofOps,
+) -> bool {
+    use prost::Message;
+
+    let [sub_proof, base_proof] = &proof.ops[..] else {
+        return false;
+    };
+    let (Ok(sub_proof), Ok(base_proof)) = (
+        CommitmentProof::decode(sub_proof.data.as_slice()),
+        CommitmentProof::decode(base_proof.data.as_slice()),
+    ) else {
+        return false;
+    };
+    verify_existence_proof::<H>(root, key, value, &sub_proof, &base_proof)
+}
+
 impl<'a, H: StorageHasher + Default> SubTreeRead for &'a Smt<H> {
     fn root(&self) -> MerkleRoot {
         Smt::<H>::root(self).into()
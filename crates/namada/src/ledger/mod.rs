@@ -13,7 +13,7 @@ pub mod storage;
 pub mod vp_host_fns;
 
 #[cfg(feature = "wasm-runtime")]
-pub use dry_run_tx::dry_run_tx;
+pub use dry_run_tx::{dry_run_tx, gas_estimate, simulate_tx};
 pub use namada_core::ledger::replay_protection;
 pub use {
     namada_gas as gas, namada_parameters as parameters,
@@ -22,9 +22,12 @@ pub use {
 
 #[cfg(feature = "wasm-runtime")]
 mod dry_run_tx {
-    use namada_sdk::queries::{EncodedResponseQuery, RequestCtx, RequestQuery};
-    use namada_state::{DBIter, ResultExt, StorageHasher, DB};
-    use namada_tx::data::GasLimit;
+    use namada_sdk::queries::{
+        EncodedResponseQuery, RequestCtx, RequestQuery, TxSimulation,
+    };
+    use namada_state::write_log::StorageModification;
+    use namada_state::{DBIter, ResultExt, StorageHasher, TempWlStorage, DB};
+    use namada_tx::data::{GasLimit, TxResult};
 
     use super::protocol;
     use crate::vm::wasm::{TxCache, VpCache};
@@ -41,8 +44,96 @@ mod dry_run_tx {
         CA: 'static + WasmCacheAccess + Sync,
     {
         use borsh_ext::BorshSerializeExt;
+
+        let (data, _temp_wl_storage) = dry_run(&mut ctx, request, true)?;
+        // NOTE: the keys changed by the wrapper transaction (if any) are not
+        // returned from this function
+        let data = data.serialize_to_vec();
+        Ok(EncodedResponseQuery {
+            data,
+            proof: None,
+            info: Default::default(),
+        })
+    }
+
+    /// Dry run a transaction and return the storage modifications that it
+    /// would make together with its result
+    pub fn simulate_tx<D, H, CA>(
+        mut ctx: RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
+        request: &RequestQuery,
+    ) -> namada_state::StorageResult<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+        CA: 'static + WasmCacheAccess + Sync,
+    {
+        use borsh_ext::BorshSerializeExt;
+
+        let (result, temp_wl_storage) = dry_run(&mut ctx, request, true)?;
+        // NOTE: like for the dry run, the modifications made by the wrapper
+        // transaction (if any) are not included in the write-set
+        let write_set = result
+            .changed_keys
+            .iter()
+            .filter_map(|key| {
+                let value = match temp_wl_storage.write_log.read(key).0? {
+                    StorageModification::Write { value } => Some(value.clone()),
+                    StorageModification::Delete => None,
+                    StorageModification::InitAccount { vp_code_hash } => {
+                        Some(vp_code_hash.0.to_vec())
+                    }
+                    // temporary values are not persisted
+                    StorageModification::Temp { .. } => return None,
+                };
+                Some((key.clone(), value))
+            })
+            .collect();
+        let data = TxSimulation { result, write_set }.serialize_to_vec();
+        Ok(EncodedResponseQuery {
+            data,
+            proof: None,
+            info: Default::default(),
+        })
+    }
+
+    /// Estimate the gas cost of a transaction by dry running only its code,
+    /// without the validity predicates. The estimate is in whole gas units
+    /// and it includes the gas of the wrapper transaction, if any.
+    pub fn gas_estimate<D, H, CA>(
+        mut ctx: RequestCtx<'_, D, H, VpCache<CA>, TxCache<CA>>,
+        request: &RequestQuery,
+    ) -> namada_state::StorageResult<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+        CA: 'static + WasmCacheAccess + Sync,
+    {
+        use borsh_ext::BorshSerializeExt;
+
+        let (result, _temp_wl_storage) = dry_run(&mut ctx, request, false)?;
+        let data = result.gas_used.get_whole_gas_units().serialize_to_vec();
+        Ok(EncodedResponseQuery {
+            data,
+            proof: None,
+            info: Default::default(),
+        })
+    }
+
+    /// Apply a transaction on top of a temporary write log. Returns the result
+    /// of the transaction with the write log holding its modifications. When
+    /// `check_vps` is `false`, only the tx code is applied and the result
+    /// holds only the consumed gas.
+    fn dry_run<'a, D, H, CA>(
+        ctx: &mut RequestCtx<'a, D, H, VpCache<CA>, TxCache<CA>>,
+        request: &RequestQuery,
+        check_vps: bool,
+    ) -> namada_state::StorageResult<(TxResult, TempWlStorage<'a, D, H>)>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+        CA: 'static + WasmCacheAccess + Sync,
+    {
         use namada_gas::{Gas, GasMetering, TxGasMeter};
-        use namada_state::TempWlStorage;
         use namada_tx::data::{DecryptedTx, TxType};
         use namada_tx::Tx;
 
@@ -54,6 +145,8 @@ mod dry_run_tx {
 
         let mut temp_wl_storage = TempWlStorage::new(&ctx.wl_storage.storage);
         let mut cumulated_gas = Gas::default();
+        let fee_payer =
+            tx.header().wrapper().map(|wrapper| wrapper.fee_payer());
 
         // Wrapper dry run to allow estimating the gas cost of a transaction
         let mut tx_gas_meter = match tx.header().tx_type {
@@ -102,16 +195,31 @@ mod dry_run_tx {
             }
         };
 
-        let mut data = protocol::apply_wasm_tx(
-            tx,
-            &TxIndex(0),
-            ShellParams::new(
-                &mut tx_gas_meter,
-                &mut temp_wl_storage,
-                &mut ctx.vp_wasm_cache,
-                &mut ctx.tx_wasm_cache,
-            ),
-        )
+        let shell_params = ShellParams::new(
+            &mut tx_gas_meter,
+            &mut temp_wl_storage,
+            &mut ctx.vp_wasm_cache,
+            &mut ctx.tx_wasm_cache,
+        );
+        let mut data = if check_vps {
+            protocol::apply_wasm_tx(
+                tx,
+                &TxIndex(0),
+                fee_payer.as_ref(),
+                shell_params,
+            )
+        } else {
+            protocol::apply_wasm_tx_code(
+                tx,
+                &TxIndex(0),
+                fee_payer.as_ref(),
+                shell_params,
+            )
+            .map(|gas_used| TxResult {
+                gas_used,
+                ..TxResult::default()
+            })
+        }
         .into_storage_result()?;
         cumulated_gas = cumulated_gas
             .checked_add(tx_gas_meter.get_tx_consumed_gas())
@@ -120,34 +228,35 @@ mod dry_run_tx {
             ))?;
         // Account gas for both inner and wrapper (if available)
         data.gas_used = cumulated_gas;
-        // NOTE: the keys changed by the wrapper transaction (if any) are not
-        // returned from this function
-        let data = data.serialize_to_vec();
-        Ok(EncodedResponseQuery {
-            data,
-            proof: None,
-            info: Default::default(),
-        })
+        Ok((data, temp_wl_storage))
     }
 }
 
 #[cfg(test)]
 mod test {
+    use std::collections::BTreeMap;
+
     use borsh::BorshDeserialize;
     use borsh_ext::BorshSerializeExt;
-    use namada_core::types::address;
-    use namada_core::types::hash::Hash;
-    use namada_core::types::storage::{BlockHeight, Key};
+    use ics23::HostFunctionsManager;
+    use namada_core::types::address::{self, Address, InternalAddress};
+    use namada_core::types::hash::{Hash, Sha256Hasher};
+    use namada_core::types::storage::{BlockHeight, Key, KeySeg};
     use namada_sdk::queries::{
-        EncodedResponseQuery, RequestCtx, RequestQuery, Router, RPC,
+        EncodedResponseQuery, RequestCtx, RequestQuery, Router,
+        DEFAULT_MAX_REQUEST_PATH_LEN, RPC,
     };
     use namada_sdk::tendermint_rpc::{self, Error as RpcError, Response};
+    use namada_state::ics23_specs::ibc_proof_specs;
+    use namada_state::merkle_tree::verify_existence_proof;
     use namada_state::testing::TestWlStorage;
-    use namada_state::StorageWrite;
+    use namada_state::{StorageWrite, StoreType};
+    use namada_test_utils::tx_data::TxWriteData;
     use namada_test_utils::TestWasms;
     use namada_tx::data::decrypted::DecryptedTx;
     use namada_tx::data::TxType;
     use namada_tx::{Code, Data, Tx};
+    use prost::Message;
     use tempfile::TempDir;
 
     use crate::ledger::events::log::EventLog;
@@ -247,11 +356,17 @@ mod test {
                 vp_wasm_cache: self.vp_wasm_cache.clone(),
                 tx_wasm_cache: self.tx_wasm_cache.clone(),
                 storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: None,
             };
             // TODO: this is a hack to propagate errors to the caller, we should
             // really permit error types other than [`std::io::Error`]
             if request.path == "/shell/dry_run_tx" {
                 super::dry_run_tx(ctx, &request)
+            } else if request.path == "/shell/tx/simulate" {
+                super::simulate_tx(ctx, &request)
+            } else if request.path == "/shell/tx/gas_estimate" {
+                super::gas_estimate(ctx, &request)
             } else {
                 self.rpc.handle(ctx, &request)
             }
@@ -369,4 +484,323 @@ mod test {
 
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_storage_value_chunked_query(
+    ) -> namada_state::StorageResult<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+
+        // Write a value that's larger than the chunk size and commit it at a
+        // specific block height
+        let key = Key::parse("large_value").unwrap();
+        let value: Vec<u8> = (0..100_u8).collect();
+        client.wl_storage.storage.block.height = BlockHeight(1);
+        client.wl_storage.write_bytes(&key, &value)?;
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+        let height = client.wl_storage.storage.get_last_block_height();
+        assert_eq!(height, BlockHeight(1));
+
+        // A chunked read requires a pinned height
+        let result = RPC
+            .shell()
+            .storage_value_chunk(&client, None, None, false, &0, &16, &key)
+            .await;
+        assert!(result.is_err());
+
+        // The value is reassembled from the chunks
+        let chunk_size = 16;
+        let read_value = namada_sdk::rpc::query_storage_value_chunked(
+            &client, &key, height, chunk_size,
+        )
+        .await
+        .unwrap();
+        assert_eq!(read_value, Some(value));
+
+        // A missing value is not found
+        let missing_key = Key::parse("missing_value").unwrap();
+        let read_value = namada_sdk::rpc::query_storage_value_chunked(
+            &client,
+            &missing_key,
+            height,
+            chunk_size,
+        )
+        .await
+        .unwrap();
+        assert_eq!(read_value, None);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_storage_value_non_existence_proof(
+    ) -> namada_state::StorageResult<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+
+        // Write an IBC value and commit it, so that the IBC sub-tree is not
+        // empty
+        let ibc_prefix =
+            Key::from(Address::Internal(InternalAddress::Ibc).to_db_key());
+        let key = ibc_prefix.push(&"test2".to_string()).unwrap();
+        client.wl_storage.storage.block.height = BlockHeight(1);
+        client.wl_storage.write_bytes(&key, [2u8; 8])?;
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        // Query a missing key with a proof
+        let missing_key = ibc_prefix.push(&"test".to_string()).unwrap();
+        let response = RPC
+            .shell()
+            .storage_value(&client, None, None, true, &missing_key)
+            .await
+            .unwrap();
+        assert!(response.data.is_empty());
+        let proof = response.proof.expect("The absence proof should exist");
+        assert_eq!(proof.ops.len(), 2);
+        let sub_proof =
+            ics23::CommitmentProof::decode(proof.ops[0].data.as_slice())
+                .unwrap();
+        let base_proof =
+            ics23::CommitmentProof::decode(proof.ops[1].data.as_slice())
+                .unwrap();
+
+        // The sub-tree proof proves the absence of the key
+        let non_existence_proof = match sub_proof.proof.clone().unwrap() {
+            ics23::commitment_proof::Proof::Nonexist(nep) => nep,
+            _ => panic!("Expected a non-existence proof"),
+        };
+        let neighbour = non_existence_proof
+            .left
+            .as_ref()
+            .or(non_existence_proof.right.as_ref())
+            .unwrap();
+        let sub_root =
+            ics23::calculate_existence_root::<HostFunctionsManager>(neighbour)
+                .unwrap();
+        let (store_type, sub_key) = StoreType::sub_key(&missing_key).unwrap();
+        let specs = ibc_proof_specs::<Sha256Hasher>();
+        assert!(ics23::verify_non_membership::<HostFunctionsManager>(
+            &sub_proof,
+            &specs[0],
+            &sub_root,
+            sub_key.to_string().as_bytes(),
+        ));
+
+        // The base tree proof ties the sub-tree root to the committed root
+        let root = client.wl_storage.storage.merkle_root().0.to_vec();
+        assert!(ics23::verify_membership::<HostFunctionsManager>(
+            &base_proof,
+            &specs[1],
+            &root,
+            store_type.to_string().as_bytes(),
+            &sub_root,
+        ));
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_raw_value_query() -> namada_state::StorageResult<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+
+        // Write arbitrary bytes that are not a valid Borsh encoding of any
+        // typed value and commit them
+        let ibc_prefix =
+            Key::from(Address::Internal(InternalAddress::Ibc).to_db_key());
+        let key = ibc_prefix.push(&"raw".to_string()).unwrap();
+        let bytes = vec![0xff, 0x00, 0xde, 0xad, 0xbe, 0xef];
+        client.wl_storage.storage.block.height = BlockHeight(1);
+        client.wl_storage.write_bytes(&key, &bytes)?;
+        client.wl_storage.commit_tx();
+        client.wl_storage.commit_block().unwrap();
+
+        // The bytes are read back verbatim, together with their proof
+        let response = RPC
+            .shell()
+            .raw_value(&client, None, None, true, &key)
+            .await
+            .unwrap();
+        assert_eq!(response.data, bytes);
+        let proof = response.proof.expect("The proof should exist");
+        let [sub_proof, base_proof] = &proof.ops[..] else {
+            panic!("Expected a sub-tree and a base tree proof");
+        };
+        let sub_proof =
+            ics23::CommitmentProof::decode(sub_proof.data.as_slice()).unwrap();
+        let base_proof =
+            ics23::CommitmentProof::decode(base_proof.data.as_slice()).unwrap();
+        let root = client.wl_storage.storage.merkle_root();
+        assert!(verify_existence_proof::<Sha256Hasher>(
+            &root,
+            &key,
+            &bytes,
+            &sub_proof,
+            &base_proof,
+        ));
+
+        // A missing key has no bytes
+        let missing_key = ibc_prefix.push(&"missing".to_string()).unwrap();
+        let response = RPC
+            .shell()
+            .raw_value(&client, None, None, false, &missing_key)
+            .await
+            .unwrap();
+        assert!(response.data.is_empty());
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_simulate_tx() -> namada_state::StorageResult<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+        // Store the wasm codes
+        let tx_write = TestWasms::TxWriteStorageKey.read_bytes();
+        let vp_always_true = TestWasms::VpAlwaysTrue.read_bytes();
+        let tx_hash = Hash::sha256(&tx_write);
+        let vp_hash = Hash::sha256(&vp_always_true);
+        for (hash, code) in [(tx_hash, &tx_write), (vp_hash, &vp_always_true)] {
+            client
+                .wl_storage
+                .storage
+                .write(&Key::wasm_code(&hash), code)
+                .unwrap();
+            client
+                .wl_storage
+                .storage
+                .write(
+                    &Key::wasm_code_len(&hash),
+                    (code.len() as u64).serialize_to_vec(),
+                )
+                .unwrap();
+        }
+        // Initialize an account
+        let owner = address::testing::established_address_1();
+        client
+            .wl_storage
+            .storage
+            .write(&Key::validity_predicate(&owner), vp_hash.serialize_to_vec())
+            .unwrap();
+        let balance_key = Key::from(owner.to_db_key())
+            .push(&"balance".to_owned())
+            .unwrap();
+
+        // Simulate a tx that credits the account
+        let balance = token::Amount::native_whole(1000);
+        let tx_data = TxWriteData {
+            key: balance_key.clone(),
+            value: balance.serialize_to_vec(),
+        };
+        let mut outer_tx =
+            Tx::from_type(TxType::Decrypted(DecryptedTx::Decrypted));
+        outer_tx.header.chain_id = client.wl_storage.storage.chain_id.clone();
+        outer_tx.set_code(Code::from_hash(tx_hash, None));
+        outer_tx.set_data(Data::new(tx_data.serialize_to_vec()));
+        let simulation = RPC
+            .shell()
+            .simulate_tx(&client, Some(outer_tx.to_bytes()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(simulation.result.is_accepted());
+        assert!(simulation.verifiers().contains(&owner));
+        assert!(simulation.result.gas_used > 0.into());
+        // The write-set holds the new balance ...
+        let expected_write_set = BTreeMap::from([(
+            balance_key.clone(),
+            Some(balance.serialize_to_vec()),
+        )]);
+        assert_eq!(simulation.write_set, expected_write_set);
+
+        // ... which is not committed to storage
+        let has_balance_key = RPC
+            .shell()
+            .storage_has_key(&client, &balance_key)
+            .await
+            .unwrap();
+        assert!(!has_balance_key);
+
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_gas_estimate() -> namada_state::StorageResult<()> {
+        // Initialize the `TestClient`
+        let mut client = TestClient::new(RPC);
+        // Store the wasm codes
+        let tx_write = TestWasms::TxWriteStorageKey.read_bytes();
+        let vp_always_false = TestWasms::VpAlwaysFalse.read_bytes();
+        let tx_hash = Hash::sha256(&tx_write);
+        let vp_hash = Hash::sha256(&vp_always_false);
+        for (hash, code) in [(tx_hash, &tx_write), (vp_hash, &vp_always_false)]
+        {
+            client
+                .wl_storage
+                .storage
+                .write(&Key::wasm_code(&hash), code)
+                .unwrap();
+            client
+                .wl_storage
+                .storage
+                .write(
+                    &Key::wasm_code_len(&hash),
+                    (code.len() as u64).serialize_to_vec(),
+                )
+                .unwrap();
+        }
+        // Initialize an account with a VP that rejects everything
+        let owner = address::testing::established_address_1();
+        client
+            .wl_storage
+            .storage
+            .write(&Key::validity_predicate(&owner), vp_hash.serialize_to_vec())
+            .unwrap();
+        let balance_key = Key::from(owner.to_db_key())
+            .push(&"balance".to_owned())
+            .unwrap();
+
+        // Quote the gas of a tx that credits the account
+        let tx_data = TxWriteData {
+            key: balance_key,
+            value: token::Amount::native_whole(1000).serialize_to_vec(),
+        };
+        let mut outer_tx =
+            Tx::from_type(TxType::Decrypted(DecryptedTx::Decrypted));
+        outer_tx.header.chain_id = client.wl_storage.storage.chain_id.clone();
+        outer_tx.set_code(Code::from_hash(tx_hash, None));
+        outer_tx.set_data(Data::new(tx_data.serialize_to_vec()));
+        let tx_bytes = outer_tx.to_bytes();
+        let estimate = RPC
+            .shell()
+            .gas_estimate(&client, Some(tx_bytes.clone()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(estimate > 0);
+
+        // The estimate is stable across calls
+        let repeated = RPC
+            .shell()
+            .gas_estimate(&client, Some(tx_bytes.clone()), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert_eq!(estimate, repeated);
+
+        // The VPs are not run for the estimate, while a dry run is rejected by
+        // the account's VP and accounts for its gas too
+        let dry_run = RPC
+            .shell()
+            .dry_run_tx(&client, Some(tx_bytes), None, false)
+            .await
+            .unwrap()
+            .data;
+        assert!(!dry_run.is_accepted());
+        assert!(dry_run.gas_used.get_whole_gas_units() > estimate);
+
+        Ok(())
+    }
 }
@@ -27,9 +27,11 @@ use crate::state::{ResultExt, State, StorageHasher, StorageRead};
 use crate::types::address::Address;
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
+use crate::types::internal::{KeyChange, ValueWithProof};
 use crate::types::storage::{
     BlockHash, BlockHeight, Epoch, Header, Key, TxIndex,
 };
+use crate::types::time::DateTimeUtc;
 use crate::vm::prefix_iter::PrefixIterators;
 use crate::vm::WasmCacheAccess;
 
@@ -454,6 +456,77 @@ where
         .into_storage_result()
     }
 
+    fn get_block_time(&self) -> Result<DateTimeUtc, state::StorageError> {
+        vp_host_fns::get_block_time(
+            &mut self.gas_meter.borrow_mut(),
+            self.storage,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()?
+        .ok_or(state::StorageError::SimpleMessage(
+            "The block header is not available",
+        ))
+    }
+
+    fn has_verifier(
+        &self,
+        addr: &Address,
+    ) -> Result<bool, state::StorageError> {
+        vp_host_fns::has_verifier(
+            &mut self.gas_meter.borrow_mut(),
+            self.verifiers,
+            addr,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
+    fn read_with_proof(
+        &self,
+        key: &Key,
+    ) -> Result<Option<ValueWithProof>, state::StorageError> {
+        vp_host_fns::read_with_proof(
+            &mut self.gas_meter.borrow_mut(),
+            self.storage,
+            key,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
+    fn verify_merkle_proof(
+        &self,
+        root: &Hash,
+        key: &Key,
+        value: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, state::StorageError> {
+        vp_host_fns::verify_merkle_proof::<H>(
+            &mut self.gas_meter.borrow_mut(),
+            root,
+            key,
+            value,
+            proof,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
+    fn get_changes(
+        &self,
+        prefix: &Key,
+    ) -> Result<Vec<KeyChange>, state::StorageError> {
+        vp_host_fns::get_changes(
+            &mut self.gas_meter.borrow_mut(),
+            self.storage,
+            self.write_log,
+            self.keys_changed,
+            prefix,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
     fn get_block_epoch(&self) -> Result<Epoch, state::StorageError> {
         vp_host_fns::get_block_epoch(
             &mut self.gas_meter.borrow_mut(),
@@ -592,6 +665,15 @@ where
         .into_storage_result()
     }
 
+    fn get_tx_hash(&self) -> Result<Hash, state::StorageError> {
+        vp_host_fns::get_tx_hash(
+            &mut self.gas_meter.borrow_mut(),
+            self.tx,
+            &mut self.sentinel.borrow_mut(),
+        )
+        .into_storage_result()
+    }
+
     fn read_pre<T: borsh::BorshDeserialize>(
         &self,
         key: &Key,
@@ -1,12 +1,14 @@
 //! The ledger's protocol
 use std::collections::BTreeSet;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Mutex;
 
 use borsh_ext::BorshSerializeExt;
 use eyre::{eyre, WrapErr};
 use masp_primitives::transaction::Transaction;
 use namada_core::types::hash::Hash;
 use namada_core::types::storage::Key;
-use namada_gas::TxGasMeter;
+use namada_gas::{Gas, TxGasMeter};
 use namada_sdk::tx::TX_TRANSFER_WASM;
 use namada_state::wl_storage::WriteLogAndStorage;
 use namada_state::StorageRead;
@@ -151,11 +153,15 @@ pub struct WrapperArgs<'a> {
 /// If the given tx is a successfully decrypted payload apply the necessary
 /// vps. Otherwise, we include the tx on chain with the gas charge added
 /// but no further validations.
+///
+/// The `fee_payer` of a decrypted tx is the fee payer of the wrapper in which
+/// it was embedded and it's made available to the tx code.
 #[allow(clippy::too_many_arguments)]
 pub fn dispatch_tx<'a, D, H, CA>(
     tx: Tx,
     tx_bytes: &'a [u8],
     tx_index: TxIndex,
+    fee_payer: Option<&Address>,
     tx_gas_meter: &'a mut TxGasMeter,
     wl_storage: &'a mut WlStorage<D, H>,
     vp_wasm_cache: &'a mut VpCache<CA>,
@@ -172,6 +178,7 @@ where
         TxType::Decrypted(DecryptedTx::Decrypted) => apply_wasm_tx(
             tx,
             &tx_index,
+            fee_payer,
             ShellParams {
                 tx_gas_meter,
                 wl_storage,
@@ -350,6 +357,7 @@ where
                 match apply_wasm_tx(
                     fee_unshielding_tx,
                     &TxIndex::default(),
+                    Some(&wrapper.fee_payer()),
                     ShellParams {
                         tx_gas_meter: &mut tx_gas_meter,
                         wl_storage: *wl_storage,
@@ -563,10 +571,12 @@ where
 }
 
 /// Apply a transaction going via the wasm environment. Gas will be metered and
-/// validity predicates will be triggered in the normal way.
+/// validity predicates will be triggered in the normal way. The given fee
+/// payer, if any, is exposed to the tx code.
 pub fn apply_wasm_tx<'a, D, H, CA, WLS>(
     tx: Tx,
     tx_index: &TxIndex,
+    fee_payer: Option<&Address>,
     shell_params: ShellParams<'a, CA, WLS>,
 ) -> Result<TxResult>
 where
@@ -603,6 +613,7 @@ where
     let verifiers = execute_tx(
         &tx,
         tx_index,
+        fee_payer,
         storage,
         tx_gas_meter,
         write_log,
@@ -635,6 +646,49 @@ where
     })
 }
 
+/// Apply only the code of a wasm transaction, without checking the validity
+/// predicates triggered by it. Returns the gas consumed by the tx code.
+pub fn apply_wasm_tx_code<'a, D, H, CA, WLS>(
+    tx: Tx,
+    tx_index: &TxIndex,
+    fee_payer: Option<&Address>,
+    shell_params: ShellParams<'a, CA, WLS>,
+) -> Result<Gas>
+where
+    CA: 'static + WasmCacheAccess + Sync,
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+    WLS: WriteLogAndStorage<D = D, H = H> + StorageRead,
+{
+    let ShellParams {
+        tx_gas_meter,
+        wl_storage,
+        vp_wasm_cache,
+        tx_wasm_cache,
+    } = shell_params;
+    let (write_log, storage) = wl_storage.split_borrow();
+
+    let tx_hash = tx.raw_header_hash();
+    if let Some(true) = write_log.has_replay_protection_entry(&tx_hash) {
+        // If the same transaction has already been applied in this block, skip
+        // execution and return
+        return Err(Error::ReplayAttempt(tx_hash));
+    }
+
+    execute_tx(
+        &tx,
+        tx_index,
+        fee_payer,
+        storage,
+        tx_gas_meter,
+        write_log,
+        vp_wasm_cache,
+        tx_wasm_cache,
+    )?;
+
+    Ok(tx_gas_meter.get_tx_consumed_gas())
+}
+
 /// Returns [`Error::DisallowedTx`] when the given tx is inner (decrypted) tx
 /// and its code `Hash` is not included in the `tx_allowlist` parameter.
 pub fn check_tx_allowed<D, H>(
@@ -744,6 +798,7 @@ where
 fn execute_tx<D, H, CA>(
     tx: &Tx,
     tx_index: &TxIndex,
+    fee_payer: Option<&Address>,
     storage: &State<D, H>,
     tx_gas_meter: &mut TxGasMeter,
     write_log: &mut WriteLog,
@@ -761,6 +816,7 @@ where
         tx_gas_meter,
         tx_index,
         tx,
+        fee_payer,
         vp_wasm_cache,
         tx_wasm_cache,
     )
@@ -843,234 +899,274 @@ where
     H: 'static + StorageHasher + Sync,
     CA: 'static + WasmCacheAccess + Sync,
 {
-    let vps_result = verifiers
-        .par_iter()
-        .try_fold(VpsResult::default, |mut result, addr| {
-            let mut gas_meter = VpGasMeter::new_from_tx_meter(tx_gas_meter);
-            let accept = match &addr {
-                Address::Implicit(_) | Address::Established(_) => {
-                    let (vp_hash, gas) = storage
-                        .validity_predicate(addr)
-                        .map_err(Error::StateError)?;
-                    gas_meter
-                        .consume(gas)
-                        .map_err(|err| Error::GasError(err.to_string()))?;
-                    let Some(vp_code_hash) = vp_hash else {
-                        return Err(Error::MissingAddress(addr.clone()));
-                    };
+    run_vps_pool(&verifiers, tx_gas_meter, |addr| {
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(tx_gas_meter);
+        let accept = match &addr {
+            Address::Implicit(_) | Address::Established(_) => {
+                let (vp_hash, gas) = storage
+                    .validity_predicate(addr)
+                    .map_err(Error::StateError)?;
+                gas_meter
+                    .consume(gas)
+                    .map_err(|err| Error::GasError(err.to_string()))?;
+                let Some(vp_code_hash) = vp_hash else {
+                    return Err(Error::MissingAddress(addr.clone()));
+                };
+
+                // NOTE: because of the whitelisted gas and the gas
+                // metering for the exposed vm
+                // env functions,    the first
+                // signature verification (if any) is accounted
+                // twice
+                wasm::run::vp_with_rejection_reason(
+                    vp_code_hash,
+                    tx,
+                    tx_index,
+                    addr,
+                    storage,
+                    write_log,
+                    &mut gas_meter,
+                    &keys_changed,
+                    &verifiers,
+                    vp_wasm_cache.clone(),
+                )
+                .map(|(accepted, reason)| {
+                    if let Some(reason) = reason {
+                        tracing::info!(
+                            "VP of {addr} rejected the transaction: {reason}"
+                        );
+                    }
+                    accepted
+                })
+                .map_err(|err| match err {
+                    wasm::run::Error::GasError(msg) => Error::GasError(msg),
+                    wasm::run::Error::InvalidTxSignature => {
+                        Error::InvalidTxSignature
+                    }
+                    _ => Error::VpRunnerError(err),
+                })
+            }
+            Address::Internal(internal_addr) => {
+                let ctx = native_vp::Ctx::new(
+                    addr,
+                    storage,
+                    write_log,
+                    tx,
+                    tx_index,
+                    gas_meter,
+                    &keys_changed,
+                    &verifiers,
+                    vp_wasm_cache.clone(),
+                );
 
-                    // NOTE: because of the whitelisted gas and the gas
-                    // metering for the exposed vm
-                    // env functions,    the first
-                    // signature verification (if any) is accounted
-                    // twice
-                    wasm::run::vp(
-                        vp_code_hash,
-                        tx,
-                        tx_index,
-                        addr,
-                        storage,
-                        write_log,
-                        &mut gas_meter,
-                        &keys_changed,
-                        &verifiers,
-                        vp_wasm_cache.clone(),
-                    )
-                    .map_err(|err| match err {
-                        wasm::run::Error::GasError(msg) => Error::GasError(msg),
-                        wasm::run::Error::InvalidTxSignature => {
-                            Error::InvalidTxSignature
-                        }
-                        _ => Error::VpRunnerError(err),
-                    })
-                }
-                Address::Internal(internal_addr) => {
-                    let ctx = native_vp::Ctx::new(
-                        addr,
-                        storage,
-                        write_log,
-                        tx,
-                        tx_index,
-                        gas_meter,
-                        &keys_changed,
-                        &verifiers,
-                        vp_wasm_cache.clone(),
-                    );
-
-                    let (accepted, sentinel): (Result<bool>, _) =
-                        match internal_addr {
-                            InternalAddress::PoS => {
-                                let pos = PosVP { ctx };
-                                let verifiers_addr_ref = &verifiers;
-                                let pos_ref = &pos;
-                                // TODO this is temporarily ran in a new thread
-                                // to
-                                // avoid crashing the ledger (required
-                                // `UnwindSafe`
-                                // and `RefUnwindSafe` in
-                                // namada/src/ledger/pos/vp.rs)
-                                let keys_changed_ref = &keys_changed;
-                                let result = pos_ref
-                                    .validate_tx(
-                                        tx,
-                                        keys_changed_ref,
-                                        verifiers_addr_ref,
-                                    )
-                                    .map_err(Error::PosNativeVpError);
-                                // Take the gas meter and sentinel
-                                // back
-                                // out of the context
-                                gas_meter = pos.ctx.gas_meter.into_inner();
-                                (result, pos.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::Ibc => {
-                                let ibc = Ibc { ctx };
-                                let result = ibc
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::IbcNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = ibc.ctx.gas_meter.into_inner();
-                                (result, ibc.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::Parameters => {
-                                let parameters = ParametersVp { ctx };
-                                let result = parameters
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::ParametersNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter =
-                                    parameters.ctx.gas_meter.into_inner();
-                                (result, parameters.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::PosSlashPool => {
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = ctx.gas_meter.into_inner();
-                                (
-                                    Err(Error::AccessForbidden(
-                                        (*internal_addr).clone(),
-                                    )),
-                                    ctx.sentinel.into_inner(),
+                let (accepted, sentinel): (Result<bool>, _) =
+                    match internal_addr {
+                        InternalAddress::PoS => {
+                            let pos = PosVP { ctx };
+                            let verifiers_addr_ref = &verifiers;
+                            let pos_ref = &pos;
+                            // TODO this is temporarily ran in a new thread
+                            // to
+                            // avoid crashing the ledger (required
+                            // `UnwindSafe`
+                            // and `RefUnwindSafe` in
+                            // namada/src/ledger/pos/vp.rs)
+                            let keys_changed_ref = &keys_changed;
+                            let result = pos_ref
+                                .validate_tx(
+                                    tx,
+                                    keys_changed_ref,
+                                    verifiers_addr_ref,
                                 )
-                            }
-                            InternalAddress::Governance => {
-                                let governance = GovernanceVp { ctx };
-                                let result = governance
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::GovernanceNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter =
-                                    governance.ctx.gas_meter.into_inner();
-                                (result, governance.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::Multitoken => {
-                                let multitoken = MultitokenVp { ctx };
-                                let result = multitoken
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::MultitokenNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter =
-                                    multitoken.ctx.gas_meter.into_inner();
-                                (result, multitoken.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::EthBridge => {
-                                let bridge = EthBridge { ctx };
-                                let result = bridge
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::EthBridgeNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = bridge.ctx.gas_meter.into_inner();
-                                (result, bridge.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::EthBridgePool => {
-                                let bridge_pool = BridgePoolVp { ctx };
-                                let result = bridge_pool
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::BridgePoolNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter =
-                                    bridge_pool.ctx.gas_meter.into_inner();
-                                (result, bridge_pool.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::Pgf => {
-                                let pgf_vp = PgfVp { ctx };
-                                let result = pgf_vp
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::PgfNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = pgf_vp.ctx.gas_meter.into_inner();
-                                (result, pgf_vp.ctx.sentinel.into_inner())
-                            }
-                            InternalAddress::Nut(_) => {
-                                let non_usable_tokens = NonUsableTokens { ctx };
-                                let result = non_usable_tokens
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::NutNativeVpError);
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = non_usable_tokens
-                                    .ctx
-                                    .gas_meter
-                                    .into_inner();
-                                (
-                                    result,
-                                    non_usable_tokens.ctx.sentinel.into_inner(),
-                                )
-                            }
-                            InternalAddress::IbcToken(_)
-                            | InternalAddress::Erc20(_) => {
-                                // The address should be a part of a multitoken
-                                // key
-                                // Take the gas meter and the sentinel
-                                // back
-                                // out of the context
-                                gas_meter = ctx.gas_meter.into_inner();
-                                (
-                                    Ok(verifiers.contains(&Address::Internal(
-                                        InternalAddress::Multitoken,
-                                    ))),
-                                    ctx.sentinel.into_inner(),
-                                )
-                            }
-                            InternalAddress::Masp => {
-                                let masp = MaspVp { ctx };
-                                let result = masp
-                                    .validate_tx(tx, &keys_changed, &verifiers)
-                                    .map_err(Error::MaspNativeVpError);
-                                // Take the gas meter and the sentinel back out
-                                // of the context
-                                gas_meter = masp.ctx.gas_meter.into_inner();
-                                (result, masp.ctx.sentinel.into_inner())
-                            }
-                        };
-
-                    accepted.map_err(|err| {
-                        // No need to check invalid sig because internal vps
-                        // don't check the signature
-                        if sentinel.is_out_of_gas() {
-                            Error::GasError(err.to_string())
-                        } else {
-                            err
+                                .map_err(Error::PosNativeVpError);
+                            // Take the gas meter and sentinel
+                            // back
+                            // out of the context
+                            gas_meter = pos.ctx.gas_meter.into_inner();
+                            (result, pos.ctx.sentinel.into_inner())
                         }
-                    })
-                }
-            };
+                        InternalAddress::Ibc => {
+                            let ibc = Ibc { ctx };
+                            let result = ibc
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::IbcNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = ibc.ctx.gas_meter.into_inner();
+                            (result, ibc.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::Parameters => {
+                            let parameters = ParametersVp { ctx };
+                            let result = parameters
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::ParametersNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = parameters.ctx.gas_meter.into_inner();
+                            (result, parameters.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::PosSlashPool => {
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = ctx.gas_meter.into_inner();
+                            (
+                                Err(Error::AccessForbidden(
+                                    (*internal_addr).clone(),
+                                )),
+                                ctx.sentinel.into_inner(),
+                            )
+                        }
+                        InternalAddress::Governance => {
+                            let governance = GovernanceVp { ctx };
+                            let result = governance
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::GovernanceNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = governance.ctx.gas_meter.into_inner();
+                            (result, governance.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::Multitoken => {
+                            let multitoken = MultitokenVp { ctx };
+                            let result = multitoken
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::MultitokenNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = multitoken.ctx.gas_meter.into_inner();
+                            (result, multitoken.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::EthBridge => {
+                            let bridge = EthBridge { ctx };
+                            let result = bridge
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::EthBridgeNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = bridge.ctx.gas_meter.into_inner();
+                            (result, bridge.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::EthBridgePool => {
+                            let bridge_pool = BridgePoolVp { ctx };
+                            let result = bridge_pool
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::BridgePoolNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = bridge_pool.ctx.gas_meter.into_inner();
+                            (result, bridge_pool.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::Pgf => {
+                            let pgf_vp = PgfVp { ctx };
+                            let result = pgf_vp
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::PgfNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = pgf_vp.ctx.gas_meter.into_inner();
+                            (result, pgf_vp.ctx.sentinel.into_inner())
+                        }
+                        InternalAddress::Nut(_) => {
+                            let non_usable_tokens = NonUsableTokens { ctx };
+                            let result = non_usable_tokens
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::NutNativeVpError);
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter =
+                                non_usable_tokens.ctx.gas_meter.into_inner();
+                            (
+                                result,
+                                non_usable_tokens.ctx.sentinel.into_inner(),
+                            )
+                        }
+                        InternalAddress::IbcToken(_)
+                        | InternalAddress::Erc20(_) => {
+                            // The address should be a part of a multitoken
+                            // key
+                            // Take the gas meter and the sentinel
+                            // back
+                            // out of the context
+                            gas_meter = ctx.gas_meter.into_inner();
+                            (
+                                Ok(verifiers.contains(&Address::Internal(
+                                    InternalAddress::Multitoken,
+                                ))),
+                                ctx.sentinel.into_inner(),
+                            )
+                        }
+                        InternalAddress::Masp => {
+                            let masp = MaspVp { ctx };
+                            let result = masp
+                                .validate_tx(tx, &keys_changed, &verifiers)
+                                .map_err(Error::MaspNativeVpError);
+                            // Take the gas meter and the sentinel back out
+                            // of the context
+                            gas_meter = masp.ctx.gas_meter.into_inner();
+                            (result, masp.ctx.sentinel.into_inner())
+                        }
+                    };
+
+                accepted.map_err(|err| {
+                    // No need to check invalid sig because internal vps
+                    // don't check the signature
+                    if sentinel.is_out_of_gas() {
+                        Error::GasError(err.to_string())
+                    } else {
+                        err
+                    }
+                })
+            }
+        };
+
+        Ok((accept, gas_meter))
+    })
+}
+
+/// Run the VP `task` of each of the verifiers in parallel and merge their
+/// results.
+///
+/// A task fails fatally when it returns an error or when its VP runs out of
+/// gas. On the first fatal error, the tasks that haven't started yet are
+/// cancelled to bound the work wasted on a tx that cannot be applied and the
+/// error is returned. The tasks already in progress run to completion, but
+/// their results are discarded.
+fn run_vps_pool<F>(
+    verifiers: &BTreeSet<Address>,
+    tx_gas_meter: &TxGasMeter,
+    task: F,
+) -> Result<VpsResult>
+where
+    F: Fn(&Address) -> Result<(Result<bool>, VpGasMeter)> + Sync,
+{
+    let cancelled = AtomicBool::new(false);
+    let first_error = Mutex::new(None);
+    // Record the fatal error, if it's the first one, and cancel the tasks
+    // that haven't started yet
+    let cancel = |err: Error| {
+        let mut first_error = first_error.lock().unwrap();
+        if first_error.is_none() {
+            *first_error = Some(err);
+        }
+        cancelled.store(true, Ordering::SeqCst);
+    };
+
+    let vps_result = verifiers
+        .par_iter()
+        .try_fold(VpsResult::default, |mut result, addr| {
+            if cancelled.load(Ordering::SeqCst) {
+                return Err(());
+            }
+            let (accept, gas_meter) = task(addr).map_err(cancel)?;
 
             match accept {
                 Ok(accepted) => {
@@ -1089,7 +1185,8 @@ where
                     // allows to display a consistent VpsResult across all
                     // nodes and find any invalid signatures
                     Error::GasError(_) => {
-                        return Err(err);
+                        cancel(err);
+                        return Err(());
                     }
                     Error::InvalidTxSignature => {
                         result.invalid_sig = true;
@@ -1106,15 +1203,21 @@ where
             result
                 .gas_used
                 .set(gas_meter)
-                .map_err(|err| Error::GasError(err.to_string()))?;
+                .map_err(|err| cancel(Error::GasError(err.to_string())))?;
 
             Ok(result)
         })
         .try_reduce(VpsResult::default, |a, b| {
-            merge_vp_results(a, b, tx_gas_meter)
-        })?;
-
-    Ok(vps_result)
+            merge_vp_results(a, b, tx_gas_meter).map_err(cancel)
+        });
+
+    vps_result.map_err(|()| {
+        first_error
+            .lock()
+            .unwrap()
+            .take()
+            .expect("The fatal error must have been recorded")
+    })
 }
 
 /// Merge VP results from parallel runs
@@ -1148,6 +1251,7 @@ fn merge_vp_results(
 #[cfg(test)]
 mod tests {
     use std::collections::HashMap;
+    use std::sync::atomic::AtomicUsize;
 
     use borsh::BorshDeserialize;
     use eyre::Result;
@@ -1349,4 +1453,85 @@ mod tests {
             }
         }
     }
+
+    /// Test that the code of a wasm tx that has already been applied in the
+    /// block is not executed again.
+    #[test]
+    fn test_apply_wasm_tx_code_replay() {
+        let (mut wl_storage, _validators) = test_utils::setup_default_storage();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+
+        let mut tx = Tx::new(ChainId::default(), None);
+        tx.update_header(TxType::Decrypted(DecryptedTx::Decrypted));
+        tx.set_code(namada_tx::Code::new(vec![1_u8, 2, 3], None));
+        let tx_hash = tx.raw_header_hash();
+        wl_storage.write_tx_hash(tx_hash).unwrap();
+
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(u64::MAX.into());
+        let result = apply_wasm_tx_code(
+            tx,
+            &TxIndex::default(),
+            None,
+            ShellParams::new(
+                &mut gas_meter,
+                &mut wl_storage,
+                &mut vp_cache,
+                &mut tx_cache,
+            ),
+        );
+        assert_matches!(result, Err(Error::ReplayAttempt(hash)) if hash == tx_hash);
+        assert_eq!(gas_meter.get_tx_consumed_gas(), 0.into());
+    }
+
+    /// Test that a fatal error of a VP cancels the remaining VPs in the pool
+    /// and that the first error is returned.
+    #[test]
+    fn test_vps_pool_cancels_on_error() {
+        let verifiers: BTreeSet<Address> = [
+            address::testing::established_address_1(),
+            address::testing::established_address_2(),
+            address::testing::established_address_3(),
+        ]
+        .into_iter()
+        .collect();
+        let failing = verifiers.first().cloned().unwrap();
+        let tx_gas_meter = TxGasMeter::new(u64::MAX);
+
+        // Run the VPs one at a time, in order, so that the failing VP is the
+        // first one to start
+        let pool = rayon::ThreadPoolBuilder::new()
+            .num_threads(1)
+            .build()
+            .unwrap();
+        let started = AtomicUsize::new(0);
+        let result = pool.install(|| {
+            run_vps_pool(&verifiers, &tx_gas_meter, |addr| {
+                started.fetch_add(1, Ordering::SeqCst);
+                if addr == &failing {
+                    return Err(Error::MissingAddress(addr.clone()));
+                }
+                Ok((Ok(true), VpGasMeter::new_from_tx_meter(&tx_gas_meter)))
+            })
+        });
+
+        assert_matches!(
+            result.unwrap_err(),
+            Error::MissingAddress(addr) if addr == failing
+        );
+        assert_eq!(started.load(Ordering::SeqCst), 1);
+
+        // Without an error, all the VPs run
+        started.store(0, Ordering::SeqCst);
+        let result = pool.install(|| {
+            run_vps_pool(&verifiers, &tx_gas_meter, |_addr| {
+                started.fetch_add(1, Ordering::SeqCst);
+                Ok((Ok(true), VpGasMeter::new_from_tx_meter(&tx_gas_meter)))
+            })
+        });
+        assert_eq!(result.unwrap().accepted_vps, verifiers);
+        assert_eq!(started.load(Ordering::SeqCst), verifiers.len());
+    }
 }
@@ -1,23 +1,33 @@
 //! Host functions for VPs used for both native and WASM VPs.
 
+use std::collections::BTreeSet;
 use std::num::TryFromIntError;
 
 use namada_core::types::address::{Address, ESTABLISHED_ADDRESS_BYTES_LEN};
 use namada_core::types::hash::{Hash, HASH_LENGTH};
+use namada_core::types::internal::{KeyChange, ValueWithProof};
 use namada_core::types::storage::{
     BlockHash, BlockHeight, Epoch, Epochs, Header, Key, TxIndex,
     TX_INDEX_LENGTH,
 };
+use namada_core::types::time::DateTimeUtc;
 use namada_core::types::validity_predicate::VpSentinel;
-use namada_gas::MEMORY_ACCESS_GAS_PER_BYTE;
+use namada_gas::{
+    MEMORY_ACCESS_GAS_PER_BYTE, STORAGE_ACCESS_GAS_PER_BYTE,
+    VERIFY_MERKLE_PROOF_GAS_PER_BYTE,
+};
+use namada_state::merkle_tree::{verify_existence_proof, MerkleRoot};
 use namada_state::write_log::WriteLog;
 use namada_state::{write_log, State, StorageHasher};
 use namada_tx::{Section, Tx};
+use prost::Message;
 use thiserror::Error;
 
 use crate::ledger::gas;
 use crate::ledger::gas::{GasMetering, VpGasMeter};
+use crate::tendermint_proto::v0_37::crypto::ProofOps;
 use crate::types::ibc::IbcEvent;
+use crate::vm::prefix_iter;
 
 /// These runtime errors will abort VP execution immediately
 #[allow(missing_docs)]
@@ -43,6 +53,12 @@ pub enum RuntimeError {
     InvalidCodeHash,
     #[error("No value found in result buffer")]
     NoValueInResultBuffer,
+    #[error("The validity predicate rejected the transaction: {0}")]
+    VpRejected(String),
+    #[error("Prefix iterator error: {0}")]
+    PrefixIterError(prefix_iter::Error),
+    #[error("Address error: {0}")]
+    AddressError(namada_core::types::address::DecodeError),
 }
 
 /// VP environment function result
@@ -103,6 +119,77 @@ where
     }
 }
 
+/// Storage read of the last committed block state together with the Merkle
+/// proof of the value's existence. Unlike [`read_pre`], the changes from the
+/// current block's write log are not visible, so that the value matches the
+/// committed Merkle root.
+pub fn read_with_proof<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &State<DB, H>,
+    key: &Key,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Option<ValueWithProof>>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (value, gas) = storage.read(key).map_err(RuntimeError::StorageError)?;
+    add_gas(gas_meter, gas, sentinel)?;
+    let Some(value) = value else {
+        return Ok(None);
+    };
+    let proof = storage
+        .get_existence_proof(key, &value, BlockHeight(0))
+        .map_err(RuntimeError::StorageError)?;
+    let proof = ProofOps::from(proof).encode_to_vec();
+    add_gas(
+        gas_meter,
+        proof.len() as u64 * STORAGE_ACCESS_GAS_PER_BYTE,
+        sentinel,
+    )?;
+    Ok(Some(ValueWithProof { value, proof }))
+}
+
+/// Verify that the Merkle proof, encoded like the proofs of
+/// [`read_with_proof`], proves that the key has the given value in a Merkle
+/// tree with the given root. A malformed proof doesn't verify.
+pub fn verify_merkle_proof<H>(
+    gas_meter: &mut VpGasMeter,
+    root: &Hash,
+    key: &Key,
+    value: &[u8],
+    proof: &[u8],
+    sentinel: &mut VpSentinel,
+) -> EnvResult<bool>
+where
+    H: StorageHasher,
+{
+    add_gas(
+        gas_meter,
+        proof.len() as u64 * VERIFY_MERKLE_PROOF_GAS_PER_BYTE,
+        sentinel,
+    )?;
+    let Ok(ProofOps { ops }) = ProofOps::decode(proof) else {
+        return Ok(false);
+    };
+    let [sub_proof, base_proof] = &ops[..] else {
+        return Ok(false);
+    };
+    let (Ok(sub_proof), Ok(base_proof)) = (
+        ics23::CommitmentProof::decode(sub_proof.data.as_slice()),
+        ics23::CommitmentProof::decode(base_proof.data.as_slice()),
+    ) else {
+        return Ok(false);
+    };
+    Ok(verify_existence_proof::<H>(
+        &MerkleRoot(root.0),
+        key,
+        value,
+        &sub_proof,
+        &base_proof,
+    ))
+}
+
 /// Storage read posterior state (after tx execution). It will try to read from
 /// the write log first and if no entry found then from the storage.
 pub fn read_post<DB, H>(
@@ -146,6 +233,42 @@ where
     }
 }
 
+/// Get the changed keys with the given prefix together with their values in
+/// the prior and the posterior state. The keys with a temporary value are
+/// skipped, as they don't change the storage.
+pub fn get_changes<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &State<DB, H>,
+    write_log: &WriteLog,
+    keys_changed: &BTreeSet<Key>,
+    prefix: &Key,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Vec<KeyChange>>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut changes = vec![];
+    for key in keys_changed
+        .iter()
+        .filter(|key| key.split_prefix(prefix).is_some())
+    {
+        let post = match read_post(gas_meter, storage, write_log, key, sentinel)
+        {
+            Ok(post) => post,
+            Err(RuntimeError::ReadTemporaryValueError) => continue,
+            Err(err) => return Err(err),
+        };
+        let pre = read_pre(gas_meter, storage, write_log, key, sentinel)?;
+        changes.push(KeyChange {
+            key: key.clone(),
+            pre,
+            post,
+        });
+    }
+    Ok(changes)
+}
+
 /// Storage read temporary state (after tx execution). It will try to read from
 /// only the write log.
 pub fn read_temp(
@@ -283,6 +406,41 @@ where
     Ok(header)
 }
 
+/// Getting the block time. The time is that of the block to which the current
+/// transaction is being applied, if its header is available.
+pub fn get_block_time<DB, H>(
+    gas_meter: &mut VpGasMeter,
+    storage: &State<DB, H>,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Option<DateTimeUtc>>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (header, gas) = storage
+        .get_block_header(None)
+        .map_err(RuntimeError::StorageError)?;
+    add_gas(gas_meter, gas, sentinel)?;
+    Ok(header.map(|header| header.time))
+}
+
+/// Check if the given address is one of the verifiers of the transaction.
+pub fn has_verifier(
+    gas_meter: &mut VpGasMeter,
+    verifiers: &BTreeSet<Address>,
+    addr: &Address,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<bool> {
+    // This is not a storage read, use the same multiplier used for a memory
+    // access
+    add_gas(
+        gas_meter,
+        addr.encode().len() as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        sentinel,
+    )?;
+    Ok(verifiers.contains(addr))
+}
+
 /// Getting the block hash. The height is that of the block to which the
 /// current transaction is being applied.
 pub fn get_block_hash<DB, H>(
@@ -318,6 +476,22 @@ pub fn get_tx_code_hash(
     Ok(hash)
 }
 
+/// Getting the hash of the transaction being validated. This is the hash of
+/// the transaction's raw header, which commits to all of its sections,
+/// including the code and the data.
+pub fn get_tx_hash(
+    gas_meter: &mut VpGasMeter,
+    tx: &Tx,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<Hash> {
+    add_gas(
+        gas_meter,
+        HASH_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        sentinel,
+    )?;
+    Ok(tx.raw_header_hash())
+}
+
 /// Getting the block epoch. The epoch is that of the block to which the
 /// current transaction is being applied.
 pub fn get_block_epoch<DB, H>(
@@ -438,6 +612,50 @@ where
     Ok(iter)
 }
 
+/// Storage prefix iterator for prior state (before tx execution), ordered by
+/// storage keys, positioned after the given key. It will try to get an
+/// iterator from the storage.
+pub fn iter_prefix_pre_after<'a, DB, H>(
+    gas_meter: &mut VpGasMeter,
+    write_log: &'a WriteLog,
+    storage: &'a State<DB, H>,
+    prefix: &Key,
+    last_key: &str,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<namada_state::PrefixIter<'a, DB>>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (iter, gas) = namada_state::iter_prefix_pre_after(
+        write_log, storage, prefix, last_key,
+    );
+    add_gas(gas_meter, gas, sentinel)?;
+    Ok(iter)
+}
+
+/// Storage prefix iterator for posterior state (after tx execution), ordered by
+/// storage keys, positioned after the given key. It will try to get an
+/// iterator from the storage.
+pub fn iter_prefix_post_after<'a, DB, H>(
+    gas_meter: &mut VpGasMeter,
+    write_log: &'a WriteLog,
+    storage: &'a State<DB, H>,
+    prefix: &Key,
+    last_key: &str,
+    sentinel: &mut VpSentinel,
+) -> EnvResult<namada_state::PrefixIter<'a, DB>>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (iter, gas) = namada_state::iter_prefix_post_after(
+        write_log, storage, prefix, last_key,
+    );
+    add_gas(gas_meter, gas, sentinel)?;
+    Ok(iter)
+}
+
 /// Get the next item in a storage prefix iterator (pre or post).
 pub fn iter_next<DB>(
     gas_meter: &mut VpGasMeter,
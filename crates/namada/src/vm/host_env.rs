@@ -1,6 +1,6 @@
 //! Virtual machine's host environment exposes functions that may be called from
 //! within a virtual machine.
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 use std::convert::TryInto;
 use std::num::TryFromIntError;
 
@@ -9,7 +9,7 @@ use borsh_ext::BorshSerializeExt;
 use masp_primitives::transaction::Transaction;
 use namada_core::types::address::ESTABLISHED_ADDRESS_BYTES_LEN;
 use namada_core::types::internal::KeyVal;
-use namada_core::types::storage::{Epochs, TX_INDEX_LENGTH};
+use namada_core::types::storage::{DbKeySeg, Epochs, TX_INDEX_LENGTH};
 use namada_core::types::validity_predicate::VpSentinel;
 use namada_gas::{
     self as gas, GasMetering, TxGasMeter, VpGasMeter,
@@ -28,6 +28,7 @@ use super::wasm::TxCache;
 use super::wasm::VpCache;
 use super::WasmCacheAccess;
 use crate::ledger::vp_host_fns;
+use crate::token;
 use crate::token::storage_key::{
     balance_key, is_any_minted_balance_key, is_any_minter_key,
     is_any_token_balance_key, minted_balance_key, minter_key,
@@ -36,11 +37,24 @@ use crate::types::address::{self, Address};
 use crate::types::hash::Hash;
 use crate::types::ibc::IbcEvent;
 use crate::types::internal::HostEnvResult;
-use crate::types::storage::{BlockHeight, Epoch, Key, TxIndex};
+use crate::types::storage::{BlockHeight, Epoch, Key, KeySeg, TxIndex};
 use crate::vm::memory::VmMemory;
-use crate::vm::prefix_iter::{PrefixIteratorId, PrefixIterators};
+use crate::vm::prefix_iter::{
+    self, ContinuationToken, IteratedState, PrefixIteratorId, PrefixIterators,
+};
 use crate::vm::{HostRef, MutHostRef};
 
+/// Log a trace of a host call at the [`tracing::Level::DEBUG`], if the host
+/// calls log level configured on the current thread or for the process
+/// permits it
+macro_rules! host_call_debug {
+    ($($arg:tt)*) => {
+        if log_level::is_enabled() {
+            tracing::debug!($($arg)*);
+        }
+    };
+}
+
 /// These runtime errors will abort tx WASM execution immediately
 #[allow(missing_docs)]
 #[allow(clippy::result_large_err)]
@@ -80,11 +94,58 @@ pub enum TxRuntimeError {
     NoValueInResultBuffer,
     #[error("VP code is not allowed in allowlist parameter.")]
     DisallowedVp,
+    #[error("Too many verifiers, a transaction can request at most {0}")]
+    TooManyVerifiers(u64),
+    #[error("Value of {len} bytes is over the limit of {max} bytes")]
+    ValueTooLarge { len: u64, max: u64 },
+    #[error("Too many writes, a transaction can write at most {0} keys")]
+    TooManyWrites(u64),
+    #[error("Too many events, a transaction can emit at most {0} events")]
+    TooManyEvents(u64),
+    #[error("Incrementing the value {value} by {delta} overflows")]
+    IncrementOverflow { value: u64, delta: u64 },
+    #[error(
+        "Insufficient balance of {owner} to transfer {amount} of token {token}"
+    )]
+    InsufficientBalance {
+        owner: Address,
+        token: Address,
+        amount: token::Amount,
+    },
+    #[error(
+        "Transferring {amount} of token {token} overflows the balance of \
+         {owner}"
+    )]
+    BalanceOverflow {
+        owner: Address,
+        token: Address,
+        amount: token::Amount,
+    },
+    #[error("Prefix iterator error: {0}")]
+    PrefixIter(#[from] prefix_iter::Error),
+    #[error("Unknown chain parameter: {0}")]
+    UnknownChainParameter(String),
+    #[error("Invalid IBC packet: {0}")]
+    InvalidIbcPacket(String),
+    #[error("Cannot preview the VP of an account without a VP {0}")]
+    MissingVp(Address),
+    #[error("Too many VP previews, a transaction can request at most {0}")]
+    TooManyVpPreviews(u64),
 }
 
 /// Result of a tx host env fn call
 pub type TxResult<T> = std::result::Result<T, TxRuntimeError>;
 
+/// The state of a transaction's host environment that persists across its host
+/// calls
+#[derive(Debug, Default)]
+pub struct TxHostState {
+    /// The tx limits protocol parameters, read from storage on first use
+    pub limits: Option<namada_parameters::TxLimits>,
+    /// The number of VP previews requested by the transaction
+    pub vp_previews: u64,
+}
+
 /// A transaction's host environment
 pub struct TxVmEnv<'a, MEM, DB, H, CA>
 where
@@ -122,10 +183,15 @@ where
     /// The transaction index is used to identify a shielded transaction's
     /// parent
     pub tx_index: HostRef<'a, &'a TxIndex>,
+    /// The address paying the fees of the transaction, established by the
+    /// node from the wrapper before the execution
+    pub fee_payer: HostRef<'a, &'a Option<Address>>,
     /// The verifiers whose validity predicates should be triggered.
     pub verifiers: MutHostRef<'a, &'a BTreeSet<Address>>,
     /// Cache for 2-step reads from host environment.
     pub result_buffer: MutHostRef<'a, &'a Option<Vec<u8>>>,
+    /// The state persisting across the host calls of the transaction
+    pub host_state: MutHostRef<'a, &'a TxHostState>,
     /// VP WASM compilation cache (this is available in tx context, because
     /// we're pre-compiling VPs from [`tx_init_account`])
     #[cfg(feature = "wasm-runtime")]
@@ -162,8 +228,10 @@ where
         sentinel: &mut TxSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        fee_payer: &Option<Address>,
         verifiers: &mut BTreeSet<Address>,
         result_buffer: &mut Option<Vec<u8>>,
+        host_state: &mut TxHostState,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
         #[cfg(feature = "wasm-runtime")] tx_wasm_cache: &mut TxCache<CA>,
     ) -> Self {
@@ -174,8 +242,10 @@ where
         let sentinel = unsafe { MutHostRef::new(sentinel) };
         let tx = unsafe { HostRef::new(tx) };
         let tx_index = unsafe { HostRef::new(tx_index) };
+        let fee_payer = unsafe { HostRef::new(fee_payer) };
         let verifiers = unsafe { MutHostRef::new(verifiers) };
         let result_buffer = unsafe { MutHostRef::new(result_buffer) };
+        let host_state = unsafe { MutHostRef::new(host_state) };
         #[cfg(feature = "wasm-runtime")]
         let vp_wasm_cache = unsafe { MutHostRef::new(vp_wasm_cache) };
         #[cfg(feature = "wasm-runtime")]
@@ -188,8 +258,10 @@ where
             sentinel,
             tx,
             tx_index,
+            fee_payer,
             verifiers,
             result_buffer,
+            host_state,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
             #[cfg(feature = "wasm-runtime")]
@@ -232,8 +304,10 @@ where
             sentinel: self.sentinel.clone(),
             tx: self.tx.clone(),
             tx_index: self.tx_index.clone(),
+            fee_payer: self.fee_payer.clone(),
             verifiers: self.verifiers.clone(),
             result_buffer: self.result_buffer.clone(),
+            host_state: self.host_state.clone(),
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache: self.vp_wasm_cache.clone(),
             #[cfg(feature = "wasm-runtime")]
@@ -488,6 +562,145 @@ where
     }
 }
 
+/// The gas rates charged by a host call. Each rate is multiplied by the number
+/// of bytes that the host call accesses in the respective resource.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct HostCallGasCost {
+    /// Gas per byte read from or written to the guest memory or the write log
+    pub memory_access_per_byte: u64,
+    /// Gas per byte of key and value read from the storage
+    pub storage_access_per_byte: u64,
+    /// Gas per byte of key and value written to the write log
+    pub storage_write_per_byte: u64,
+    /// Gas per byte of a Merkle proof verified
+    pub merkle_proof_per_byte: u64,
+    /// Gas per signature verified
+    pub signature_verify: u64,
+}
+
+const NO_GAS: HostCallGasCost = HostCallGasCost {
+    memory_access_per_byte: 0,
+    storage_access_per_byte: 0,
+    storage_write_per_byte: 0,
+    merkle_proof_per_byte: 0,
+    signature_verify: 0,
+};
+const MEMORY_GAS: HostCallGasCost = HostCallGasCost {
+    memory_access_per_byte: MEMORY_ACCESS_GAS_PER_BYTE,
+    ..NO_GAS
+};
+const READ_GAS: HostCallGasCost = HostCallGasCost {
+    storage_access_per_byte: gas::STORAGE_ACCESS_GAS_PER_BYTE,
+    ..MEMORY_GAS
+};
+const READ_WRITE_GAS: HostCallGasCost = HostCallGasCost {
+    storage_write_per_byte: gas::STORAGE_WRITE_GAS_PER_BYTE,
+    ..READ_GAS
+};
+const SIGNATURE_GAS: HostCallGasCost = HostCallGasCost {
+    signature_verify: gas::VERIFY_TX_SIG_GAS,
+    ..MEMORY_GAS
+};
+const MERKLE_PROOF_GAS: HostCallGasCost = HostCallGasCost {
+    merkle_proof_per_byte: gas::VERIFY_MERKLE_PROOF_GAS_PER_BYTE,
+    ..MEMORY_GAS
+};
+
+/// The gas rates of every host call, sorted by the host function name without
+/// the `namada_` prefix. The host calls that only charge a gas amount given by
+/// the guest have no rates.
+const HOST_CALL_GAS_SCHEDULE: &[(&str, HostCallGasCost)] = &[
+    ("tx_charge_gas", NO_GAS),
+    ("tx_delete", READ_WRITE_GAS),
+    ("tx_delete_prefix", READ_WRITE_GAS),
+    ("tx_emit_ibc_event", READ_WRITE_GAS),
+    ("tx_get_block_epoch", MEMORY_GAS),
+    ("tx_get_block_hash", MEMORY_GAS),
+    ("tx_get_block_header", READ_GAS),
+    ("tx_get_block_height", MEMORY_GAS),
+    ("tx_get_block_time", READ_GAS),
+    ("tx_get_chain_id", MEMORY_GAS),
+    ("tx_get_chain_param", READ_GAS),
+    ("tx_get_fee_payer", MEMORY_GAS),
+    ("tx_get_ibc_events", READ_GAS),
+    ("tx_get_native_token", MEMORY_GAS),
+    ("tx_get_pred_epochs", MEMORY_GAS),
+    ("tx_get_tx_index", MEMORY_GAS),
+    ("tx_has_key", READ_GAS),
+    ("tx_ibc_execute", READ_WRITE_GAS),
+    ("tx_ibc_send_packet", READ_WRITE_GAS),
+    ("tx_increment", READ_WRITE_GAS),
+    ("tx_init_account", READ_WRITE_GAS),
+    ("tx_insert_verifier", READ_GAS),
+    ("tx_iter_next", READ_GAS),
+    ("tx_iter_prefix", READ_GAS),
+    ("tx_log_string", MEMORY_GAS),
+    ("tx_make_key", MEMORY_GAS),
+    ("tx_preview_vp", READ_GAS),
+    ("tx_read", READ_GAS),
+    ("tx_result_buffer", MEMORY_GAS),
+    ("tx_set_commitment_sentinel", MEMORY_GAS),
+    ("tx_transfer", READ_WRITE_GAS),
+    ("tx_update_masp_note_commitment_tree", READ_WRITE_GAS),
+    ("tx_update_validity_predicate", READ_WRITE_GAS),
+    ("tx_verify_tx_section_signature", SIGNATURE_GAS),
+    ("tx_write", READ_WRITE_GAS),
+    ("tx_write_temp", MEMORY_GAS),
+    ("vp_charge_gas", NO_GAS),
+    ("vp_eval", READ_GAS),
+    ("vp_get_block_epoch", MEMORY_GAS),
+    ("vp_get_block_hash", MEMORY_GAS),
+    ("vp_get_block_header", READ_GAS),
+    ("vp_get_block_height", MEMORY_GAS),
+    ("vp_get_block_time", READ_GAS),
+    ("vp_get_chain_id", MEMORY_GAS),
+    ("vp_get_changes", READ_GAS),
+    ("vp_get_ibc_events", READ_GAS),
+    ("vp_get_native_token", MEMORY_GAS),
+    ("vp_get_pred_epochs", MEMORY_GAS),
+    ("vp_get_tx_code_hash", MEMORY_GAS),
+    ("vp_get_tx_hash", MEMORY_GAS),
+    ("vp_get_tx_index", MEMORY_GAS),
+    ("vp_has_key_post", READ_GAS),
+    ("vp_has_key_pre", READ_GAS),
+    ("vp_has_verifier", MEMORY_GAS),
+    ("vp_iter_continuation_token", MEMORY_GAS),
+    ("vp_iter_next", READ_GAS),
+    ("vp_iter_prefix_post", READ_GAS),
+    ("vp_iter_prefix_pre", READ_GAS),
+    ("vp_iter_prefix_self", READ_GAS),
+    ("vp_iter_resume", READ_GAS),
+    ("vp_log_string", MEMORY_GAS),
+    ("vp_read_address_post", READ_GAS),
+    ("vp_read_address_pre", READ_GAS),
+    ("vp_read_amount_post", READ_GAS),
+    ("vp_read_amount_pre", READ_GAS),
+    ("vp_read_post", READ_GAS),
+    ("vp_read_pre", READ_GAS),
+    ("vp_read_temp", MEMORY_GAS),
+    ("vp_read_with_proof", READ_GAS),
+    ("vp_reject", MEMORY_GAS),
+    ("vp_result_buffer", MEMORY_GAS),
+    ("vp_verify_merkle_proof", MERKLE_PROOF_GAS),
+    ("vp_verify_tx_section_signature", SIGNATURE_GAS),
+];
+
+/// Get the gas rates charged by each of the host calls, keyed by the host
+/// function name without the `namada_` prefix.
+pub fn host_call_gas_schedule() -> BTreeMap<&'static str, HostCallGasCost> {
+    HOST_CALL_GAS_SCHEDULE.iter().copied().collect()
+}
+
+/// Get the gas rates charged by the given host call.
+fn host_call_gas_cost(host_call: &str) -> HostCallGasCost {
+    HOST_CALL_GAS_SCHEDULE
+        .binary_search_by_key(&host_call, |(name, _)| *name)
+        .map(|ix| HOST_CALL_GAS_SCHEDULE[ix].1)
+        .unwrap_or_else(|_| {
+            unreachable!("Missing gas cost of the host call {host_call}")
+        })
+}
+
 /// Add a gas cost incured in a transaction
 pub fn tx_charge_gas<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
@@ -549,7 +762,7 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_has_key {}, key {}", key, key_ptr,);
+    host_call_debug!("tx_has_key {}, key {}", key, key_ptr,);
 
     let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
 
@@ -604,10 +817,26 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_read {}, key {}", key, key_ptr,);
+    host_call_debug!("tx_read {}, key {}", key, key_ptr,);
 
     let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
 
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(value) = mock::read(&key) {
+        return Ok(match value {
+            Some(value) => {
+                let len: i64 = value
+                    .len()
+                    .try_into()
+                    .map_err(TxRuntimeError::NumConversionError)?;
+                let result_buffer = unsafe { env.ctx.result_buffer.get() };
+                result_buffer.replace(value);
+                len
+            }
+            None => HostEnvResult::Fail.to_i64(),
+        });
+    }
+
     // try to read from the write log first
     let write_log = unsafe { env.ctx.write_log.get() };
     let (log_val, gas) = write_log.read(&key);
@@ -718,7 +947,7 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_iter_prefix {}", prefix);
+    host_call_debug!("tx_iter_prefix {}", prefix);
 
     let prefix =
         Key::parse(prefix).map_err(TxRuntimeError::StorageDataError)?;
@@ -730,7 +959,7 @@ where
     tx_charge_gas(env, gas)?;
 
     let iterators = unsafe { env.ctx.iterators.get() };
-    Ok(iterators.insert(iter).id())
+    Ok(iterators.insert(iter)?.id())
 }
 
 /// Storage prefix iterator next function exposed to the wasm VM Tx environment.
@@ -749,7 +978,7 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tracing::debug!("tx_iter_next iter_id {}", iter_id,);
+    host_call_debug!("tx_iter_next iter_id {}", iter_id,);
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let iterators = unsafe { env.ctx.iterators.get() };
@@ -814,7 +1043,9 @@ where
 }
 
 /// Storage write function exposed to the wasm VM Tx environment. The given
-/// key/value will be written to the write log.
+/// key/value will be written to the write log. Values larger than the
+/// `max_value_bytes` protocol parameter are rejected, as are writes past the
+/// `max_writes` protocol parameter number of keys modified by the tx.
 pub fn tx_write<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
     key_ptr: u64,
@@ -828,6 +1059,15 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    // Reject oversized values before reading them from memory
+    let max_value_bytes = tx_limits(env)?.max_value_bytes;
+    if val_len > max_value_bytes {
+        return Err(TxRuntimeError::ValueTooLarge {
+            len: val_len,
+            max: max_value_bytes,
+        });
+    }
+
     let (key, gas) = env
         .memory
         .read_string(key_ptr, key_len as _)
@@ -839,20 +1079,257 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_update {}, {:?}", key, value);
+    host_call_debug!("tx_update {}, {:?}", key, value);
+
+    let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
+    write_value(env, &key, value)
+}
+
+/// Increment the Borsh-encoded `u64` value at the given key by the given
+/// delta, function exposed to the wasm VM Tx environment. A key without a
+/// value is incremented from `0`. Returns the new value or fails on overflow.
+pub fn tx_increment<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    key_ptr: u64,
+    key_len: u64,
+    delta: u64,
+) -> TxResult<u64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (key, gas) = env
+        .memory
+        .read_string(key_ptr, key_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+
+    host_call_debug!("tx_increment {}, delta {}", key, delta);
 
     let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
+
+    let value = match read_value(env, &key)? {
+        Some(bytes) => u64::try_from_slice(&bytes)
+            .map_err(TxRuntimeError::EncodingError)?,
+        None => 0,
+    };
+    let new_value = value
+        .checked_add(delta)
+        .ok_or(TxRuntimeError::IncrementOverflow { value, delta })?;
+
+    write_value(env, &key, new_value.serialize_to_vec())?;
+    Ok(new_value)
+}
+
+/// Transfer a token amount between two accounts function exposed to the wasm
+/// VM Tx environment. The transfer is given as Borsh-encoded source, target,
+/// token and amount. The balances are checked and updated in the write log and
+/// both accounts are inserted as verifiers.
+pub fn tx_transfer<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    transfer_ptr: u64,
+    transfer_len: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (transfer, gas) = env
+        .memory
+        .read_bytes(transfer_ptr, transfer_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let (src, dest, token, amount) =
+        <(Address, Address, Address, token::Amount)>::try_from_slice(&transfer)
+            .map_err(TxRuntimeError::EncodingError)?;
+
+    host_call_debug!(
+        "tx_transfer {} of token {} from {} to {}",
+        amount,
+        token,
+        src,
+        dest
+    );
+
+    insert_verifier(env, src.clone())?;
+    insert_verifier(env, dest.clone())?;
+    if amount.is_zero() || src == dest {
+        return Ok(());
+    }
+
+    let read_balance = |owner: &Address| -> TxResult<(Key, token::Amount)> {
+        let key = balance_key(&token, owner);
+        let balance = match read_value(env, &key)? {
+            Some(bytes) => token::Amount::try_from_slice(&bytes)
+                .map_err(TxRuntimeError::EncodingError)?,
+            None => token::Amount::zero(),
+        };
+        Ok((key, balance))
+    };
+    let (src_key, src_balance) = read_balance(&src)?;
+    let new_src_balance = src_balance.checked_sub(amount).ok_or_else(|| {
+        TxRuntimeError::InsufficientBalance {
+            owner: src.clone(),
+            token: token.clone(),
+            amount,
+        }
+    })?;
+    let (dest_key, dest_balance) = read_balance(&dest)?;
+    let new_dest_balance =
+        dest_balance.checked_add(amount).ok_or_else(|| {
+            TxRuntimeError::BalanceOverflow {
+                owner: dest.clone(),
+                token: token.clone(),
+                amount,
+            }
+        })?;
+
+    write_value(env, &src_key, new_src_balance.serialize_to_vec())?;
+    write_value(env, &dest_key, new_dest_balance.serialize_to_vec())
+}
+
+/// Read the value at the given key from the write log, or from the storage if
+/// it hasn't been modified by the tx.
+fn read_value<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    key: &Key,
+) -> TxResult<Option<Vec<u8>>>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    // try to read from the write log first
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let (log_val, gas) = write_log.read(key);
+    tx_charge_gas(env, gas)?;
+    Ok(match log_val {
+        Some(
+            write_log::StorageModification::Write { value }
+            | write_log::StorageModification::Temp { value },
+        ) => Some(value.clone()),
+        Some(write_log::StorageModification::InitAccount { vp_code_hash }) => {
+            Some(vp_code_hash.to_vec())
+        }
+        Some(write_log::StorageModification::Delete) => None,
+        None => {
+            let storage = unsafe { env.ctx.storage.get() };
+            let (value, gas) =
+                storage.read(key).map_err(TxRuntimeError::StateError)?;
+            tx_charge_gas(env, gas)?;
+            value
+        }
+    })
+}
+
+/// Write the given value at the given key to the write log, after checking
+/// that the write is valid and within the limit of written keys.
+fn write_value<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    key: &Key,
+    value: Vec<u8>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
     if key.is_validity_predicate().is_some() {
         tx_validate_vp_code_hash(env, &value, &None)?;
     }
 
-    check_address_existence(env, &key)?;
+    check_address_existence(env, key)?;
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let (gas, _size_diff) = write_log
-        .write(&key, value)
+        .write(key, value)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_charge_gas(env, gas)
+    tx_charge_gas(env, gas)?;
+
+    check_max_writes(env)
+}
+
+/// Check that the number of keys modified by the tx, including the deleted
+/// ones, is within the `max_writes` protocol parameter. Modifying an already
+/// modified key doesn't count towards the limit.
+fn check_max_writes<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let max_writes = tx_limits(env)?.max_writes;
+    if write_log.tx_modified_keys_count() as u64 > max_writes {
+        return Err(TxRuntimeError::TooManyWrites(max_writes));
+    }
+    Ok(())
+}
+
+/// Get the protocol parameters limiting the storage writes, events, verifiers
+/// and VP previews of a tx. The limits are read from the committed state, as
+/// the parameters can only be changed by governance, only once per tx and the
+/// reads are charged to the tx. A limit that is not set falls back to its
+/// default.
+fn tx_limits<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<namada_parameters::TxLimits>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let host_state = unsafe { env.ctx.host_state.get() };
+    if let Some(limits) = host_state.limits {
+        return Ok(limits);
+    }
+
+    let read_limit = |key: &Key, default: u64| -> TxResult<u64> {
+        let storage = unsafe { env.ctx.storage.get() };
+        let (value, gas) =
+            storage.read(key).map_err(TxRuntimeError::StateError)?;
+        tx_charge_gas(env, gas)?;
+        match value {
+            Some(bytes) => u64::try_from_slice(&bytes)
+                .map_err(TxRuntimeError::EncodingError),
+            None => Ok(default),
+        }
+    };
+    let defaults = namada_parameters::TxLimits::default();
+    let limits = namada_parameters::TxLimits {
+        max_value_bytes: read_limit(
+            &namada_parameters::storage::get_max_value_bytes_key(),
+            defaults.max_value_bytes,
+        )?,
+        max_writes: read_limit(
+            &namada_parameters::storage::get_max_writes_key(),
+            defaults.max_writes,
+        )?,
+        max_events: read_limit(
+            &namada_parameters::storage::get_max_events_key(),
+            defaults.max_events,
+        )?,
+        max_verifiers: read_limit(
+            &namada_parameters::storage::get_max_verifiers_key(),
+            defaults.max_verifiers,
+        )?,
+        max_vp_previews: read_limit(
+            &namada_parameters::storage::get_max_vp_previews_key(),
+            defaults.max_vp_previews,
+        )?,
+    };
+    host_state.limits = Some(limits);
+    Ok(limits)
 }
 
 /// Temporary storage write function exposed to the wasm VM Tx environment. The
@@ -882,7 +1359,7 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_write_temp {}, {:?}", key, value);
+    host_call_debug!("tx_write_temp {}, {:?}", key, value);
 
     let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
 
@@ -951,7 +1428,9 @@ where
 }
 
 /// Storage delete function exposed to the wasm VM Tx environment. The given
-/// key/value will be written as deleted to the write log.
+/// key/value will be written as deleted to the write log. Deletions count
+/// towards the `max_writes` protocol parameter number of keys modified by the
+/// tx.
 pub fn tx_delete<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
     key_ptr: u64,
@@ -969,7 +1448,7 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_delete {}", key);
+    host_call_debug!("tx_delete {}", key);
 
     let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
     if key.is_validity_predicate().is_some() {
@@ -980,11 +1459,83 @@ where
     let (gas, _size_diff) = write_log
         .delete(&key)
         .map_err(TxRuntimeError::StorageModificationError)?;
-    tx_charge_gas(env, gas)
+    tx_charge_gas(env, gas)?;
+
+    check_max_writes(env)
+}
+
+/// Storage prefix delete function exposed to the wasm VM Tx environment. It
+/// will delete every key matching the given prefix, both from the write log
+/// and from the storage. The gas is charged for each key deleted and every
+/// deletion counts towards the `max_writes` protocol parameter number of keys
+/// modified by the tx.
+///
+/// The matching keys are collected before any of them is deleted. Prefix
+/// iterators that are already open in the same tx check the write log for
+/// every key they yield, so they will skip the deleted keys.
+pub fn tx_delete_prefix<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    prefix_ptr: u64,
+    prefix_len: u64,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (prefix, gas) = env
+        .memory
+        .read_string(prefix_ptr, prefix_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+
+    host_call_debug!("tx_delete_prefix {}", prefix);
+
+    let prefix =
+        Key::parse(prefix).map_err(TxRuntimeError::StorageDataError)?;
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let (iter, gas) =
+        namada_state::iter_prefix_post(write_log, storage, &prefix);
+    tx_charge_gas(env, gas)?;
+
+    let mut keys = vec![];
+    for (key, _val, iter_gas) in iter {
+        let key = Key::parse(key).map_err(TxRuntimeError::StorageDataError)?;
+        let (log_val, log_gas) = write_log.read(&key);
+        tx_charge_gas(env, iter_gas + log_gas)?;
+        match log_val {
+            Some(&write_log::StorageModification::Delete) => {
+                // the key has already been deleted
+                continue;
+            }
+            Some(&write_log::StorageModification::InitAccount { .. }) => {
+                return Err(TxRuntimeError::CannotDeleteVp);
+            }
+            _ => {}
+        }
+        if key.is_validity_predicate().is_some() {
+            return Err(TxRuntimeError::CannotDeleteVp);
+        }
+        keys.push(key);
+    }
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    for key in keys {
+        let (gas, _size_diff) = write_log
+            .delete(&key)
+            .map_err(TxRuntimeError::StorageModificationError)?;
+        tx_charge_gas(env, gas)?;
+        check_max_writes(env)?;
+    }
+    Ok(())
 }
 
 /// Emitting an IBC event function exposed to the wasm VM Tx environment.
-/// The given IBC event will be set to the write log.
+/// The given IBC event will be set to the write log. Fails if the tx emits
+/// more than the `max_events` protocol parameter number of events.
 pub fn tx_emit_ibc_event<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
     event_ptr: u64,
@@ -1003,78 +1554,274 @@ where
     tx_charge_gas(env, gas)?;
     let event: IbcEvent = BorshDeserialize::try_from_slice(&event)
         .map_err(TxRuntimeError::EncodingError)?;
-    let write_log = unsafe { env.ctx.write_log.get() };
-    let gas = write_log.emit_ibc_event(event);
-    tx_charge_gas(env, gas)
+    emit_ibc_event(env, event)
 }
 
-/// Getting an IBC event function exposed to the wasm VM Tx environment.
-pub fn tx_get_ibc_events<MEM, DB, H, CA>(
+/// Sending an IBC packet function exposed to the wasm VM Tx environment. The
+/// given Protobuf-encoded packet is validated and recorded in the write log as
+/// a `send_packet` IBC event to be relayed.
+pub fn tx_ibc_send_packet<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
-    event_type_ptr: u64,
-    event_type_len: u64,
-) -> TxResult<i64>
+    packet_ptr: u64,
+    packet_len: u64,
+) -> TxResult<()>
 where
     MEM: VmMemory,
     DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    let (event_type, gas) = env
+    use crate::ibc::core::channel::types::packet::Packet;
+    use crate::ibc::primitives::proto::Protobuf;
+
+    let (packet, gas) = env
         .memory
-        .read_string(event_type_ptr, event_type_len as _)
+        .read_bytes(packet_ptr, packet_len as _)
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
-    let write_log = unsafe { env.ctx.write_log.get() };
-    let events: Vec<IbcEvent> = write_log
-        .get_ibc_events()
-        .iter()
-        .filter(|event| event.event_type == event_type)
-        .cloned()
-        .collect();
-    let value = events.serialize_to_vec();
-    let len: i64 = value
-        .len()
-        .try_into()
-        .map_err(TxRuntimeError::NumConversionError)?;
-    let result_buffer = unsafe { env.ctx.result_buffer.get() };
-    result_buffer.replace(value);
-    Ok(len)
+    host_call_debug!("tx_ibc_send_packet of {} bytes", packet.len());
+
+    // Decoding the packet also validates its identifiers, sequence and timeout
+    let packet = Packet::decode_vec(&packet)
+        .map_err(|e| TxRuntimeError::InvalidIbcPacket(e.to_string()))?;
+    if packet.data.is_empty() {
+        return Err(TxRuntimeError::InvalidIbcPacket(
+            "The packet data is empty".to_string(),
+        ));
+    }
+    emit_ibc_event(env, IbcEvent::send_packet(&packet))
 }
 
-/// Storage read prior state (before tx execution) function exposed to the wasm
-/// VM VP environment. It will try to read from the storage.
+/// Previewing the verdict of an account's validity predicate function exposed
+/// to the wasm VM Tx environment. The VP is run against the tx's changes so far
+/// together with the given Borsh encoded `Vec<(Key, Option<Vec<u8>>)>` of
+/// proposed writes (`Some`) and deletions (`None`), which are applied on top of
+/// the write log and reverted afterwards, so nothing is persisted.
 ///
-/// Returns `-1` when the key is not present, or the length of the data when
-/// the key is present (the length may be `0`).
-pub fn vp_read_pre<MEM, DB, H, EVAL, CA>(
-    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
-    key_ptr: u64,
-    key_len: u64,
-) -> vp_host_fns::EnvResult<i64>
+/// The gas of the proposed changes and the gas used by the VP are charged to
+/// the tx, so a preview that runs out of gas aborts the tx, as does requesting
+/// more previews than the `max_vp_previews` protocol parameter. VPs cannot
+/// preview other VPs, so the previews can't recurse. A VP that fails to run is
+/// treated as rejecting the changes.
+///
+/// Returns `1` when the VP would accept the changes, or `-1` otherwise.
+#[cfg(feature = "wasm-runtime")]
+pub fn tx_preview_vp<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    addr_ptr: u64,
+    addr_len: u64,
+    changes_ptr: u64,
+    changes_len: u64,
+) -> TxResult<i64>
 where
     MEM: VmMemory,
-    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
-    H: StorageHasher,
-    EVAL: VpEvaluator,
-    CA: WasmCacheAccess,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
 {
-    let (key, gas) = env
+    let (addr, gas) = env
         .memory
-        .read_string(key_ptr, key_len as _)
-        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
-    let gas_meter = unsafe { env.ctx.gas_meter.get() };
-    let sentinel = unsafe { env.ctx.sentinel.get() };
-    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+        .read_string(addr_ptr, addr_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let addr = Address::decode(addr).map_err(TxRuntimeError::AddressError)?;
 
-    // try to read from the storage
-    let key =
-        Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    let (changes, gas) =
+        env.memory
+            .read_bytes(changes_ptr, changes_len as _)
+            .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let changes: Vec<(Key, Option<Vec<u8>>)> =
+        BorshDeserialize::try_from_slice(&changes)
+            .map_err(TxRuntimeError::EncodingError)?;
+    host_call_debug!("tx_preview_vp addr {}, {} changes", addr, changes.len());
+
+    let max_vp_previews = tx_limits(env)?.max_vp_previews;
+    let host_state = unsafe { env.ctx.host_state.get() };
+    if host_state.vp_previews >= max_vp_previews {
+        return Err(TxRuntimeError::TooManyVpPreviews(max_vp_previews));
+    }
+    host_state.vp_previews += 1;
+
+    // Apply the proposed changes on top of the write log only for the
+    // duration of the preview
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let accept = write_log
+        .with_tx_changes(changes, |preview_log, gas| {
+            tx_charge_gas(env, gas)?;
+            preview_vp(env, &addr, preview_log)
+        })
+        .map_err(TxRuntimeError::StorageModificationError)??;
+    host_call_debug!("tx_preview_vp addr {}, accept {}", addr, accept);
+    Ok(HostEnvResult::from(accept).to_i64())
+}
+
+/// Run the VP of the given account against the given write log with the
+/// changes proposed by the tx applied to it.
+#[cfg(feature = "wasm-runtime")]
+fn preview_vp<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    addr: &Address,
+    preview_log: &WriteLog,
+) -> TxResult<bool>
+where
+    MEM: VmMemory,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    // Find the VP of the account
+    let storage = unsafe { env.ctx.storage.get() };
+    let vp_key = if let Address::Implicit(_) = addr {
+        namada_parameters::storage::get_implicit_vp_key()
+    } else {
+        Key::validity_predicate(&addr)
+    };
+    let (log_val, gas) = preview_log.read(&vp_key);
+    tx_charge_gas(env, gas)?;
+    let vp_code_hash = match log_val {
+        Some(write_log::StorageModification::Write { value }) => {
+            Some(value.clone())
+        }
+        Some(write_log::StorageModification::InitAccount { vp_code_hash }) => {
+            Some(vp_code_hash.to_vec())
+        }
+        Some(
+            write_log::StorageModification::Delete
+            | write_log::StorageModification::Temp { .. },
+        ) => None,
+        None => {
+            let (value, gas) = storage.read(&vp_key)?;
+            tx_charge_gas(env, gas)?;
+            value
+        }
+    }
+    .ok_or_else(|| TxRuntimeError::MissingVp(addr.clone()))?;
+    let vp_code_hash = Hash::try_from(&vp_code_hash[..])
+        .map_err(|e| TxRuntimeError::InvalidVpCodeHash(e.to_string()))?;
+
+    // Run the VP against the changes in the write log
+    let verifiers = unsafe { env.ctx.verifiers.get() };
+    let (mut verifiers, keys_changed) =
+        preview_log.verifiers_and_changed_keys(verifiers);
+    verifiers.insert(addr.clone());
+    let tx = unsafe { env.ctx.tx.get() };
+    let tx_index = unsafe { env.ctx.tx_index.get() };
+    let vp_wasm_cache = unsafe { env.ctx.vp_wasm_cache.get() };
+    let tx_gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let mut gas_meter = VpGasMeter::new_from_tx_meter(tx_gas_meter);
+    let result = crate::vm::wasm::run::vp(
+        vp_code_hash,
+        tx,
+        tx_index,
+        addr,
+        storage,
+        preview_log,
+        &mut gas_meter,
+        &keys_changed,
+        &verifiers,
+        vp_wasm_cache.clone(),
+    );
+    tx_charge_gas(env, gas_meter.get_vp_consumed_gas().into())?;
+
+    Ok(result.unwrap_or_else(|err| {
+        tracing::info!("The preview of the VP of {addr} failed: {err}");
+        false
+    }))
+}
+
+/// Emit the IBC event into the write log. Fails if the tx emits more than the
+/// `max_events` protocol parameter number of events.
+fn emit_ibc_event<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    event: IbcEvent,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let gas = write_log.emit_ibc_event(event);
+    tx_charge_gas(env, gas)?;
+
+    // Re-emitting an identical event doesn't count towards the limit
+    let max_events = tx_limits(env)?.max_events;
+    if write_log.get_ibc_events().len() as u64 > max_events {
+        return Err(TxRuntimeError::TooManyEvents(max_events));
+    }
+    Ok(())
+}
+
+/// Getting an IBC event function exposed to the wasm VM Tx environment.
+pub fn tx_get_ibc_events<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    event_type_ptr: u64,
+    event_type_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (event_type, gas) = env
+        .memory
+        .read_string(event_type_ptr, event_type_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let events: Vec<IbcEvent> = write_log
+        .get_ibc_events()
+        .iter()
+        .filter(|event| event.event_type == event_type)
+        .cloned()
+        .collect();
+    let value = events.serialize_to_vec();
+    let len: i64 = value
+        .len()
+        .try_into()
+        .map_err(TxRuntimeError::NumConversionError)?;
+    let result_buffer = unsafe { env.ctx.result_buffer.get() };
+    result_buffer.replace(value);
+    Ok(len)
+}
+
+/// Storage read prior state (before tx execution) function exposed to the wasm
+/// VM VP environment. It will try to read from the storage.
+///
+/// Returns `-1` when the key is not present, or the length of the data when
+/// the key is present (the length may be `0`).
+pub fn vp_read_pre<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (key, gas) = env
+        .memory
+        .read_string(key_ptr, key_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    // try to read from the storage
+    let key =
+        Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_read_pre", || Some(key.clone()));
     let storage = unsafe { env.ctx.storage.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
     let value =
         vp_host_fns::read_pre(gas_meter, storage, write_log, &key, sentinel)?;
-    tracing::debug!(
+    host_call_debug!(
         "vp_read_pre addr {}, key {}, value {:?}",
         unsafe { env.ctx.address.get() },
         key,
@@ -1094,6 +1841,128 @@ where
     })
 }
 
+/// Storage read of the last committed block state together with the value's
+/// Merkle proof function exposed to the wasm VM VP environment. The result is
+/// a Borsh encoded [`namada_core::types::internal::ValueWithProof`].
+///
+/// Returns `-1` when the key is not present, or the length of the encoded
+/// result when the key is present.
+pub fn vp_read_with_proof<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (key, gas) = env
+        .memory
+        .read_string(key_ptr, key_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let key =
+        Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_read_with_proof", || Some(key.clone()));
+    let storage = unsafe { env.ctx.storage.get() };
+    let value =
+        vp_host_fns::read_with_proof(gas_meter, storage, &key, sentinel)?;
+    host_call_debug!(
+        "vp_read_with_proof addr {}, key {}, value {:?}",
+        unsafe { env.ctx.address.get() },
+        key,
+        value,
+    );
+    Ok(match value {
+        Some(value) => {
+            let value = value.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Merkle proof verification function exposed to the wasm VM VP environment.
+/// The root is a Borsh encoded [`Hash`] and the proof is encoded like the
+/// proofs of [`vp_read_with_proof`].
+///
+/// Returns `1` when the proof proves that the key has the given value in a
+/// Merkle tree with the given root, or `-1` otherwise.
+#[allow(clippy::too_many_arguments)]
+pub fn vp_verify_merkle_proof<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    root_ptr: u64,
+    root_len: u64,
+    key_ptr: u64,
+    key_len: u64,
+    value_ptr: u64,
+    value_len: u64,
+    proof_ptr: u64,
+    proof_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+
+    let (root, gas) = env
+        .memory
+        .read_bytes(root_ptr, root_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+    let root = Hash::try_from_slice(&root)
+        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+
+    let (key, gas) = env
+        .memory
+        .read_string(key_ptr, key_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+    let key =
+        Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_verify_merkle_proof", || Some(key.clone()));
+
+    let (value, gas) = env
+        .memory
+        .read_bytes(value_ptr, value_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let (proof, gas) = env
+        .memory
+        .read_bytes(proof_ptr, proof_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let verified = vp_host_fns::verify_merkle_proof::<H>(
+        gas_meter, &root, &key, &value, &proof, sentinel,
+    )?;
+    host_call_debug!(
+        "vp_verify_merkle_proof root {}, key {}, verified {}",
+        root,
+        key,
+        verified,
+    );
+    Ok(HostEnvResult::from(verified).to_i64())
+}
+
 /// Storage read posterior state (after tx execution) function exposed to the
 /// wasm VM VP environment. It will try to read from the write log first and if
 /// no entry found then from the storage.
@@ -1120,11 +1989,12 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_read_post {}, key {}", key, key_ptr,);
+    host_call_debug!("vp_read_post {}, key {}", key, key_ptr,);
 
     // try to read from the write log first
     let key =
         Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_read_post", || Some(key.clone()));
     let storage = unsafe { env.ctx.storage.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
     let value =
@@ -1168,11 +2038,12 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_read_temp {}, key {}", key, key_ptr);
+    host_call_debug!("vp_read_temp {}, key {}", key, key_ptr);
 
     // try to read from the write log
     let key =
         Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_read_temp", || Some(key.clone()));
     let write_log = unsafe { env.ctx.write_log.get() };
     let value = vp_host_fns::read_temp(gas_meter, write_log, &key, sentinel)?;
     Ok(match value {
@@ -1189,6 +2060,234 @@ where
     })
 }
 
+/// Storage read of a [`token::Amount`] in prior state (before tx execution)
+/// function exposed to the wasm VM VP environment. The value is decoded on the
+/// host side and its canonical encoding is placed in the result buffer.
+///
+/// Returns `-1` when the key is not present, or the length of the encoded
+/// amount when the key is present.
+pub fn vp_read_amount_pre<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_read_decoded::<token::Amount, _, _, _, _, _>(
+        env,
+        key_ptr,
+        key_len,
+        "vp_read_amount_pre",
+        vp_host_fns::read_pre,
+    )
+}
+
+/// Storage read of a [`token::Amount`] in posterior state (after tx
+/// execution) function exposed to the wasm VM VP environment. The value is
+/// decoded on the host side and its canonical encoding is placed in the result
+/// buffer.
+///
+/// Returns `-1` when the key is not present, or the length of the encoded
+/// amount when the key is present.
+pub fn vp_read_amount_post<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_read_decoded::<token::Amount, _, _, _, _, _>(
+        env,
+        key_ptr,
+        key_len,
+        "vp_read_amount_post",
+        vp_host_fns::read_post,
+    )
+}
+
+/// Storage read of an [`Address`] in prior state (before tx execution)
+/// function exposed to the wasm VM VP environment. The value is decoded on the
+/// host side and its canonical encoding is placed in the result buffer.
+///
+/// Returns `-1` when the key is not present, or the length of the encoded
+/// address when the key is present.
+pub fn vp_read_address_pre<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_read_decoded::<Address, _, _, _, _, _>(
+        env,
+        key_ptr,
+        key_len,
+        "vp_read_address_pre",
+        vp_host_fns::read_pre,
+    )
+}
+
+/// Storage read of an [`Address`] in posterior state (after tx execution)
+/// function exposed to the wasm VM VP environment. The value is decoded on the
+/// host side and its canonical encoding is placed in the result buffer.
+///
+/// Returns `-1` when the key is not present, or the length of the encoded
+/// address when the key is present.
+pub fn vp_read_address_post<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    vp_read_decoded::<Address, _, _, _, _, _>(
+        env,
+        key_ptr,
+        key_len,
+        "vp_read_address_post",
+        vp_host_fns::read_post,
+    )
+}
+
+/// Read a value with the given storage read function and decode it as `T`.
+/// A value that fails to decode aborts the VP execution with an encoding
+/// error.
+fn vp_read_decoded<T, MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    key_ptr: u64,
+    key_len: u64,
+    host_fn: &'static str,
+    read: fn(
+        &mut VpGasMeter,
+        &State<DB, H>,
+        &WriteLog,
+        &Key,
+        &mut VpSentinel,
+    ) -> vp_host_fns::EnvResult<Option<Vec<u8>>>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    T: BorshDeserialize + borsh::BorshSerialize + std::fmt::Debug,
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (key, gas) = env
+        .memory
+        .read_string(key_ptr, key_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let key =
+        Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record(host_fn, || Some(key.clone()));
+    let storage = unsafe { env.ctx.storage.get() };
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let value = read(gas_meter, storage, write_log, &key, sentinel)?
+        .map(|bytes| T::try_from_slice(&bytes))
+        .transpose()
+        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+    host_call_debug!(
+        "{} addr {}, key {}, value {:?}",
+        host_fn,
+        unsafe { env.ctx.address.get() },
+        key,
+        value,
+    );
+    Ok(match value {
+        Some(value) => {
+            let value = value.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the changed keys with the given prefix together with their prior
+/// and posterior values function exposed to the wasm VM VP environment. The
+/// result is a Borsh encoded `Vec` of
+/// [`namada_core::types::internal::KeyChange`].
+///
+/// Returns the length of the encoded result.
+pub fn vp_get_changes<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    prefix_ptr: u64,
+    prefix_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (prefix, gas) = env
+        .memory
+        .read_string(prefix_ptr, prefix_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let prefix = Key::parse(prefix)
+        .map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_get_changes", || Some(prefix.clone()));
+    let storage = unsafe { env.ctx.storage.get() };
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let keys_changed = unsafe { env.ctx.keys_changed.get() };
+    let changes = vp_host_fns::get_changes(
+        gas_meter,
+        storage,
+        write_log,
+        keys_changed,
+        &prefix,
+        sentinel,
+    )?;
+    host_call_debug!(
+        "vp_get_changes addr {}, prefix {}, changes {:?}",
+        unsafe { env.ctx.address.get() },
+        prefix,
+        changes,
+    );
+    let value = changes.serialize_to_vec();
+    let len: i64 = value
+        .len()
+        .try_into()
+        .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+    let result_buffer = unsafe { env.ctx.result_buffer.get() };
+    result_buffer.replace(value);
+    Ok(len)
+}
+
 /// This function is a helper to handle the first step of reading var-len
 /// values from the host.
 ///
@@ -1243,10 +2342,11 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_has_key_pre {}, key {}", key, key_ptr,);
+    host_call_debug!("vp_has_key_pre {}, key {}", key, key_ptr,);
 
     let key =
         Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_has_key_pre", || Some(key.clone()));
     let storage = unsafe { env.ctx.storage.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
     let present = vp_host_fns::has_key_pre(
@@ -1278,10 +2378,11 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_has_key_post {}, key {}", key, key_ptr,);
+    host_call_debug!("vp_has_key_post {}, key {}", key, key_ptr,);
 
     let key =
         Key::parse(key).map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_has_key_post", || Some(key.clone()));
     let storage = unsafe { env.ctx.storage.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
     let present = vp_host_fns::has_key_post(
@@ -1314,10 +2415,11 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_iter_prefix_pre {}", prefix);
+    host_call_debug!("vp_iter_prefix_pre {}", prefix);
 
     let prefix = Key::parse(prefix)
         .map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_iter_prefix_pre", || Some(prefix.clone()));
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1326,7 +2428,10 @@ where
     )?;
 
     let iterators = unsafe { env.ctx.iterators.get() };
-    Ok(iterators.insert(iter).id())
+    let id = iterators
+        .insert_resumable(iter, prefix, IteratedState::Pre)
+        .map_err(vp_host_fns::RuntimeError::PrefixIterError)?;
+    Ok(id.id())
 }
 
 /// Storage prefix iterator function for posterior state (after tx execution)
@@ -1353,10 +2458,11 @@ where
     let sentinel = unsafe { env.ctx.sentinel.get() };
     vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
 
-    tracing::debug!("vp_iter_prefix_post {}", prefix);
+    host_call_debug!("vp_iter_prefix_post {}", prefix);
 
     let prefix = Key::parse(prefix)
         .map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+    trace::record("vp_iter_prefix_post", || Some(prefix.clone()));
 
     let write_log = unsafe { env.ctx.write_log.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1365,7 +2471,61 @@ where
     )?;
 
     let iterators = unsafe { env.ctx.iterators.get() };
-    Ok(iterators.insert(iter).id())
+    let id = iterators
+        .insert_resumable(iter, prefix, IteratedState::Post)
+        .map_err(vp_host_fns::RuntimeError::PrefixIterError)?;
+    Ok(id.id())
+}
+
+/// Storage prefix iterator function for posterior state (after tx execution)
+/// of the keys under the VP's own address exposed to the wasm VM VP
+/// environment. The given sub-prefix is appended to the VP's address to form
+/// the prefix. It will try to get an iterator from the storage and return the
+/// corresponding ID of the iterator, ordered by storage keys.
+pub fn vp_iter_prefix_self<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    sub_prefix_ptr: u64,
+    sub_prefix_len: u64,
+) -> vp_host_fns::EnvResult<u64>
+where
+    MEM: VmMemory,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (sub_prefix, gas) = env
+        .memory
+        .read_string(sub_prefix_ptr, sub_prefix_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    host_call_debug!("vp_iter_prefix_self {}", sub_prefix);
+
+    let address = unsafe { env.ctx.address.get() };
+    let prefix = Key::from(address.to_db_key());
+    let prefix = if sub_prefix.is_empty() {
+        prefix
+    } else {
+        let sub_prefix = Key::parse(sub_prefix)
+            .map_err(vp_host_fns::RuntimeError::StorageDataError)?;
+        prefix.join(&sub_prefix)
+    };
+    trace::record("vp_iter_prefix_self", || Some(prefix.clone()));
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let iter = vp_host_fns::iter_prefix_post(
+        gas_meter, write_log, storage, &prefix, sentinel,
+    )?;
+
+    let iterators = unsafe { env.ctx.iterators.get() };
+    let id = iterators
+        .insert_resumable(iter, prefix, IteratedState::Post)
+        .map_err(vp_host_fns::RuntimeError::PrefixIterError)?;
+    Ok(id.id())
 }
 
 /// Storage prefix iterator for prior or posterior state function
@@ -1384,7 +2544,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
-    tracing::debug!("vp_iter_next iter_id {}", iter_id);
+    host_call_debug!("vp_iter_next iter_id {}", iter_id);
 
     let iterators = unsafe { env.ctx.iterators.get() };
     let iter_id = PrefixIteratorId::new(iter_id);
@@ -1394,21 +2554,137 @@ where
         if let Some((key, val)) =
             vp_host_fns::iter_next(gas_meter, iter, sentinel)?
         {
-            let key_val = borsh::to_vec(&KeyVal { key, val })
+            trace::record("vp_iter_next", || Key::parse(&key).ok());
+            let key_val = KeyVal { key, val };
+            let bytes = borsh::to_vec(&key_val)
                 .map_err(vp_host_fns::RuntimeError::EncodingError)?;
-            let len: i64 = key_val
+            let len: i64 = bytes
                 .len()
                 .try_into()
                 .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
             let result_buffer = unsafe { env.ctx.result_buffer.get() };
-            result_buffer.replace(key_val);
+            result_buffer.replace(bytes);
+            iterators.record_position(iter_id, key_val.key);
             return Ok(len);
         }
+        // The iterator is exhausted
+        iterators.remove(iter_id);
     }
     Ok(HostEnvResult::Fail.to_i64())
 }
 
-/// Verifier insertion function exposed to the wasm VM Tx environment.
+/// Prefix iterator continuation token function exposed to the wasm VM VP
+/// environment. The token from which the iteration of the given iterator can
+/// be resumed with [`vp_iter_resume`] is written to the result buffer.
+///
+/// Returns `-1` when there's no token, because no item has been returned from
+/// the iterator yet or it's exhausted, or the length of the encoded token.
+pub fn vp_iter_continuation_token<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    iter_id: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    host_call_debug!("vp_iter_continuation_token iter_id {}", iter_id);
+
+    let iterators = unsafe { env.ctx.iterators.get() };
+    let iter_id = PrefixIteratorId::new(iter_id);
+    match iterators.continuation_token(iter_id) {
+        Some(token) => {
+            let token = borsh::to_vec(&token)
+                .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+            let len: i64 = token
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let gas_meter = unsafe { env.ctx.gas_meter.get() };
+            let sentinel = unsafe { env.ctx.sentinel.get() };
+            vp_host_fns::add_gas(
+                gas_meter,
+                token.len() as u64
+                    * host_call_gas_cost("vp_iter_continuation_token")
+                        .memory_access_per_byte,
+                sentinel,
+            )?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(token);
+            Ok(len)
+        }
+        None => Ok(HostEnvResult::Fail.to_i64()),
+    }
+}
+
+/// Resumed storage prefix iterator function exposed to the wasm VM VP
+/// environment. It will try to get an iterator over the prefix and the state
+/// of the given continuation token, positioned after the last key that was
+/// returned before the token was taken, and return the corresponding ID of the
+/// iterator, ordered by storage keys.
+pub fn vp_iter_resume<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    token_ptr: u64,
+    token_len: u64,
+) -> vp_host_fns::EnvResult<u64>
+where
+    MEM: VmMemory,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    let (token, gas) = env
+        .memory
+        .read_bytes(token_ptr, token_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    let token = ContinuationToken::try_from_slice(&token)
+        .map_err(vp_host_fns::RuntimeError::EncodingError)?;
+
+    host_call_debug!(
+        "vp_iter_resume {} after {}",
+        token.prefix(),
+        token.last_key()
+    );
+    trace::record("vp_iter_resume", || Some(token.prefix().clone()));
+
+    let write_log = unsafe { env.ctx.write_log.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let iter = match token.state() {
+        IteratedState::Pre => vp_host_fns::iter_prefix_pre_after(
+            gas_meter,
+            write_log,
+            storage,
+            token.prefix(),
+            token.last_key(),
+            sentinel,
+        )?,
+        IteratedState::Post => vp_host_fns::iter_prefix_post_after(
+            gas_meter,
+            write_log,
+            storage,
+            token.prefix(),
+            token.last_key(),
+            sentinel,
+        )?,
+    };
+
+    let iterators = unsafe { env.ctx.iterators.get() };
+    let id = iterators
+        .insert_resumed(iter, token)
+        .map_err(vp_host_fns::RuntimeError::PrefixIterError)?;
+    Ok(id.id())
+}
+
+/// Verifier insertion function exposed to the wasm VM Tx environment. The VP of
+/// the inserted address will validate the tx, even if the tx doesn't modify
+/// any of its storage keys.
 pub fn tx_insert_verifier<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
     addr_ptr: u64,
@@ -1426,14 +2702,41 @@ where
         .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
     tx_charge_gas(env, gas)?;
 
-    tracing::debug!("tx_insert_verifier {}, addr_ptr {}", addr, addr_ptr,);
+    host_call_debug!("tx_insert_verifier {}, addr_ptr {}", addr, addr_ptr,);
 
     let addr = Address::decode(&addr).map_err(TxRuntimeError::AddressError)?;
 
+    insert_verifier(env, addr)
+}
+
+/// Insert the given address to the verifiers of the tx, within the limit of
+/// verifiers.
+fn insert_verifier<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    addr: Address,
+) -> TxResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    // The limit bounds the number of VPs that have to be executed for a tx
     let verifiers = unsafe { env.ctx.verifiers.get() };
+    if !verifiers.contains(&addr) {
+        let max_verifiers = tx_limits(env)?.max_verifiers;
+        if verifiers.len() as u64 >= max_verifiers {
+            return Err(TxRuntimeError::TooManyVerifiers(max_verifiers));
+        }
+    }
     // This is not a storage write, use the same multiplier used for a storage
     // read
-    tx_charge_gas(env, addr_len * MEMORY_ACCESS_GAS_PER_BYTE)?;
+    let addr_len = addr.encode().len() as u64;
+    tx_charge_gas(
+        env,
+        addr_len
+            * host_call_gas_cost("tx_insert_verifier").memory_access_per_byte,
+    )?;
     verifiers.insert(addr);
 
     Ok(())
@@ -1462,7 +2765,7 @@ where
     tx_charge_gas(env, gas)?;
 
     let addr = Address::decode(addr).map_err(TxRuntimeError::AddressError)?;
-    tracing::debug!("tx_update_validity_predicate for addr {}", addr);
+    host_call_debug!("tx_update_validity_predicate for addr {}", addr);
 
     let (code_tag, gas) = env
         .memory
@@ -1519,7 +2822,7 @@ where
 
     tx_validate_vp_code_hash(env, &code_hash, &code_tag)?;
 
-    tracing::debug!("tx_init_account");
+    host_call_debug!("tx_init_account");
 
     let storage = unsafe { env.ctx.storage.get() };
     let write_log = unsafe { env.ctx.write_log.get() };
@@ -1586,7 +2889,11 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
-    tx_charge_gas(env, TX_INDEX_LENGTH as u64 * MEMORY_ACCESS_GAS_PER_BYTE)?;
+    tx_charge_gas(
+        env,
+        TX_INDEX_LENGTH as u64
+            * host_call_gas_cost("tx_get_tx_index").memory_access_per_byte,
+    )?;
     let tx_index = unsafe { env.ctx.tx_index.get() };
     Ok(tx_index.0)
 }
@@ -1604,6 +2911,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_tx_index", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let tx_index = unsafe { env.ctx.tx_index.get() };
@@ -1623,6 +2931,15 @@ where
     H: StorageHasher,
     CA: WasmCacheAccess,
 {
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(hash) = mock::block_hash() {
+        let gas = env
+            .memory
+            .write_bytes(result_ptr, hash.0)
+            .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+        return tx_charge_gas(env, gas);
+    }
+
     let storage = unsafe { env.ctx.storage.get() };
     let (hash, gas) = storage.get_block_hash();
     tx_charge_gas(env, gas)?;
@@ -1668,12 +2985,102 @@ where
         .len()
         .try_into()
         .map_err(TxRuntimeError::NumConversionError)?;
-    tx_charge_gas(env, MEMORY_ACCESS_GAS_PER_BYTE * len as u64)?;
+    tx_charge_gas(
+        env,
+        host_call_gas_cost("tx_get_pred_epochs").memory_access_per_byte
+            * len as u64,
+    )?;
     let result_buffer = unsafe { env.ctx.result_buffer.get() };
     result_buffer.replace(bytes);
     Ok(len)
 }
 
+/// Get the value of the protocol parameter with the given name function
+/// exposed to the wasm VM Tx environment. It will try to read from the write
+/// log first and if no entry found then from the storage and then return the
+/// length of the encoded value, which is written into the result buffer.
+///
+/// Returns `-1` when the parameter has no value.
+pub fn tx_get_chain_param<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    name_ptr: u64,
+    name_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (name, gas) = env
+        .memory
+        .read_string(name_ptr, name_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+
+    host_call_debug!("tx_get_chain_param {}", name);
+
+    let key = namada_parameters::storage::get_protocol_parameter_key(&name)
+        .ok_or(TxRuntimeError::UnknownChainParameter(name))?;
+    match env.ctx.read_bytes(&key)? {
+        Some(value) => {
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            Ok(len)
+        }
+        None => Ok(HostEnvResult::Fail.to_i64()),
+    }
+}
+
+/// Make a storage key from the given segments function exposed to the wasm VM
+/// Tx environment. The segments are Borsh-encoded `Vec<DbKeySeg>`. The
+/// encoded key, which is written into the result buffer, is the same as the
+/// one used to pass keys to the other host functions and its length is
+/// returned.
+///
+/// Returns `-1` when the segments don't form a valid key, i.e. when the
+/// encoded key wouldn't be parsed back into the same segments.
+pub fn tx_make_key<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+    segments_ptr: u64,
+    segments_len: u64,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let (segments, gas) = env
+        .memory
+        .read_bytes(segments_ptr, segments_len as _)
+        .map_err(|e| TxRuntimeError::MemoryError(Box::new(e)))?;
+    tx_charge_gas(env, gas)?;
+    let segments: Vec<DbKeySeg> = BorshDeserialize::try_from_slice(&segments)
+        .map_err(TxRuntimeError::EncodingError)?;
+
+    let key = Key { segments };
+    let encoded = key.to_string();
+    host_call_debug!("tx_make_key {}", encoded);
+
+    match Key::parse(&encoded) {
+        Ok(parsed) if parsed == key => {
+            let len: i64 = encoded
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(encoded.into_bytes());
+            Ok(len)
+        }
+        _ => Ok(HostEnvResult::Fail.to_i64()),
+    }
+}
+
 /// Get the native token's address
 pub fn tx_get_native_token<MEM, DB, H, CA>(
     env: &TxVmEnv<MEM, DB, H, CA>,
@@ -1688,7 +3095,8 @@ where
     // Gas for getting the native token address from storage
     tx_charge_gas(
         env,
-        ESTABLISHED_ADDRESS_BYTES_LEN as u64 * MEMORY_ACCESS_GAS_PER_BYTE,
+        ESTABLISHED_ADDRESS_BYTES_LEN as u64
+            * host_call_gas_cost("tx_get_native_token").memory_access_per_byte,
     )?;
     let storage = unsafe { env.ctx.storage.get() };
     let native_token = storage.native_token.clone();
@@ -1731,6 +3139,91 @@ where
     })
 }
 
+/// Getting the fee payer function exposed to the wasm VM Tx environment. The
+/// fee payer is established by the node from the wrapper of the transaction
+/// before its execution. The Borsh-encoded address is written into the result
+/// buffer and its length is returned.
+///
+/// Returns `-1` when the transaction has no fee payer (e.g. a governance
+/// proposal code).
+pub fn tx_get_fee_payer<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    let fee_payer = unsafe { env.ctx.fee_payer.get() };
+    Ok(match fee_payer {
+        Some(addr) => {
+            let value = addr.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            tx_charge_gas(
+                env,
+                len as u64
+                    * host_call_gas_cost("tx_get_fee_payer")
+                        .memory_access_per_byte,
+            )?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Getting the block time function exposed to the wasm VM Tx environment. The
+/// time is that of the block to which the current transaction is being
+/// applied. The Borsh-encoded time is written into the result buffer and its
+/// length is returned.
+///
+/// Returns `-1` when the block header is not available.
+pub fn tx_get_block_time<MEM, DB, H, CA>(
+    env: &TxVmEnv<MEM, DB, H, CA>,
+) -> TxResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    CA: WasmCacheAccess,
+{
+    #[cfg(any(test, feature = "testing"))]
+    if let Some(time) = mock::block_time() {
+        let value = time.serialize_to_vec();
+        let len: i64 = value
+            .len()
+            .try_into()
+            .map_err(TxRuntimeError::NumConversionError)?;
+        let result_buffer = unsafe { env.ctx.result_buffer.get() };
+        result_buffer.replace(value);
+        return Ok(len);
+    }
+
+    let storage = unsafe { env.ctx.storage.get() };
+    let (header, gas) = storage
+        .get_block_header(None)
+        .map_err(TxRuntimeError::StateError)?;
+    tx_charge_gas(env, gas)?;
+    Ok(match header {
+        Some(h) => {
+            let value = h.time.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(TxRuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
 /// Getting the chain ID function exposed to the wasm VM VP environment.
 pub fn vp_get_chain_id<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
@@ -1743,6 +3236,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_chain_id", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1767,6 +3261,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_block_height", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1786,6 +3281,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_block_header", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1808,6 +3304,75 @@ where
     })
 }
 
+/// Getting the block time function exposed to the wasm VM VP environment. The
+/// time is that of the block to which the current transaction is being
+/// applied. The Borsh-encoded time is written into the result buffer and its
+/// length is returned.
+///
+/// Returns `-1` when the block header is not available.
+pub fn vp_get_block_time<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    trace::record("vp_get_block_time", || None);
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    let storage = unsafe { env.ctx.storage.get() };
+    let time = vp_host_fns::get_block_time(gas_meter, storage, sentinel)?;
+    Ok(match time {
+        Some(time) => {
+            let value = time.serialize_to_vec();
+            let len: i64 = value
+                .len()
+                .try_into()
+                .map_err(vp_host_fns::RuntimeError::NumConversionError)?;
+            let result_buffer = unsafe { env.ctx.result_buffer.get() };
+            result_buffer.replace(value);
+            len
+        }
+        None => HostEnvResult::Fail.to_i64(),
+    })
+}
+
+/// Check if the given address is one of the verifiers of the transaction
+/// function exposed to the wasm VM VP environment.
+pub fn vp_has_verifier<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    addr_ptr: u64,
+    addr_len: u64,
+) -> vp_host_fns::EnvResult<i64>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    trace::record("vp_has_verifier", || None);
+    let (addr, gas) = env
+        .memory
+        .read_string(addr_ptr, addr_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    host_call_debug!("vp_has_verifier {}, addr_ptr {}", addr, addr_ptr);
+
+    let addr = Address::decode(addr)
+        .map_err(vp_host_fns::RuntimeError::AddressError)?;
+    let verifiers = unsafe { env.ctx.verifiers.get() };
+    let found =
+        vp_host_fns::has_verifier(gas_meter, verifiers, &addr, sentinel)?;
+    Ok(HostEnvResult::from(found).to_i64())
+}
+
 /// Getting the block hash function exposed to the wasm VM VP environment. The
 /// hash is that of the block to which the current transaction is being applied.
 pub fn vp_get_block_hash<MEM, DB, H, EVAL, CA>(
@@ -1821,6 +3386,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_block_hash", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1832,7 +3398,8 @@ where
     vp_host_fns::add_gas(gas_meter, gas, sentinel)
 }
 
-/// Getting the transaction hash function exposed to the wasm VM VP environment.
+/// Getting the transaction code hash function exposed to the wasm VM VP
+/// environment. A VP can use it to only allow some known tx codes.
 pub fn vp_get_tx_code_hash<MEM, DB, H, EVAL, CA>(
     env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
     result_ptr: u64,
@@ -1844,6 +3411,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_tx_code_hash", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let tx = unsafe { env.ctx.tx.get() };
@@ -1862,6 +3430,31 @@ where
     vp_host_fns::add_gas(gas_meter, gas, sentinel)
 }
 
+/// Getting the transaction hash function exposed to the wasm VM VP
+/// environment. The hash is that of the transaction being validated.
+pub fn vp_get_tx_hash<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    result_ptr: u64,
+) -> vp_host_fns::EnvResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    trace::record("vp_get_tx_hash", || None);
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    let tx = unsafe { env.ctx.tx.get() };
+    let hash = vp_host_fns::get_tx_hash(gas_meter, tx, sentinel)?;
+    let gas = env
+        .memory
+        .write_bytes(result_ptr, hash.0)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)
+}
+
 /// Getting the block epoch function exposed to the wasm VM VP
 /// environment. The epoch is that of the block to which the current
 /// transaction is being applied.
@@ -1875,6 +3468,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_block_epoch", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1893,6 +3487,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_pred_epochs", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -1921,6 +3516,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_ibc_events", || None);
     let (event_type, gas) = env
         .memory
         .read_string(event_type_ptr, event_type_len as _)
@@ -1965,6 +3561,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_verify_tx_section_signature", || None);
     let (hash_list, gas) = env
         .memory
         .read_bytes(hash_list_ptr, hash_list_len as _)
@@ -2004,6 +3601,8 @@ where
         .map_err(vp_host_fns::RuntimeError::EncodingError)?;
 
     let tx = unsafe { env.ctx.tx.get() };
+    let signature_gas =
+        host_call_gas_cost("vp_verify_tx_section_signature").signature_verify;
 
     match tx.verify_signatures(
         &hashes,
@@ -2011,7 +3610,7 @@ where
         &Some(signer),
         threshold,
         max_signatures,
-        || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
+        || gas_meter.consume(signature_gas),
     ) {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
@@ -2212,6 +3811,8 @@ where
         .map_err(TxRuntimeError::EncodingError)?;
 
     let tx = unsafe { env.ctx.tx.get() };
+    let signature_gas =
+        host_call_gas_cost("tx_verify_tx_section_signature").signature_verify;
 
     match tx.verify_signatures(
         &hashes,
@@ -2219,7 +3820,7 @@ where
         &None,
         threshold,
         max_signatures,
-        || gas_meter.consume(gas::VERIFY_TX_SIG_GAS),
+        || gas_meter.consume(signature_gas),
     ) {
         Ok(_) => Ok(HostEnvResult::Success.to_i64()),
         Err(err) => match err {
@@ -2288,6 +3889,7 @@ where
     EVAL: VpEvaluator<Db = DB, H = H, Eval = EVAL, CA = CA>,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_eval", || None);
     let (vp_code_hash, gas) = env
         .memory
         .read_bytes(vp_code_hash_ptr, vp_code_hash_len as _)
@@ -2328,6 +3930,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_get_native_token", || None);
     let gas_meter = unsafe { env.ctx.gas_meter.get() };
     let sentinel = unsafe { env.ctx.sentinel.get() };
     let storage = unsafe { env.ctx.storage.get() };
@@ -2356,6 +3959,7 @@ where
     EVAL: VpEvaluator,
     CA: WasmCacheAccess,
 {
+    trace::record("vp_log_string", || None);
     let (str, _gas) = env
         .memory
         .read_string(str_ptr, str_len as _)
@@ -2364,6 +3968,36 @@ where
     Ok(())
 }
 
+/// Reject the transaction from the wasm VM VP environment with the given
+/// reason. The reason is recorded in the VP sentinel and the execution of the
+/// VP is aborted immediately, which the runner then treats as a rejection.
+pub fn vp_reject<MEM, DB, H, EVAL, CA>(
+    env: &VpVmEnv<MEM, DB, H, EVAL, CA>,
+    reason_ptr: u64,
+    reason_len: u64,
+) -> vp_host_fns::EnvResult<()>
+where
+    MEM: VmMemory,
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+    EVAL: VpEvaluator,
+    CA: WasmCacheAccess,
+{
+    trace::record("vp_reject", || None);
+    let (reason, gas) = env
+        .memory
+        .read_string(reason_ptr, reason_len as _)
+        .map_err(|e| vp_host_fns::RuntimeError::MemoryError(Box::new(e)))?;
+    let gas_meter = unsafe { env.ctx.gas_meter.get() };
+    let sentinel = unsafe { env.ctx.sentinel.get() };
+    vp_host_fns::add_gas(gas_meter, gas, sentinel)?;
+
+    host_call_debug!("vp_reject with reason: {}", reason);
+
+    sentinel.set_rejected(reason.clone());
+    Err(vp_host_fns::RuntimeError::VpRejected(reason))
+}
+
 // Temp. workaround for <https://github.com/anoma/namada/issues/1831>
 use namada_state::StorageRead;
 
@@ -2437,7 +4071,7 @@ where
         ibc_tx_charge_gas(self, gas)?;
 
         let iterators = unsafe { self.iterators.get() };
-        Ok(iterators.insert(iter).id())
+        Ok(iterators.insert(iter).map_err(StorageError::new)?.id())
     }
 
     fn iter_next<'iter>(
@@ -2730,6 +4364,170 @@ where
 {
 }
 
+/// Pre-programmed responses of the tx host calls, for testing the branches of
+/// a wasm code without building full storage fixtures. The mocks are set for
+/// the current thread, on which the wasm is being run.
+#[cfg(any(test, feature = "testing"))]
+pub mod mock {
+    use std::cell::RefCell;
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::types::storage::BlockHash;
+    use crate::types::time::DateTimeUtc;
+
+    /// The responses of the mocked host calls. The host calls that are not
+    /// mocked access the real storage.
+    #[derive(Debug, Clone, Default)]
+    pub struct MockHostResponses {
+        /// The values returned by `tx_read` for the given keys, where `None`
+        /// is a missing value
+        pub reads: BTreeMap<Key, Option<Vec<u8>>>,
+        /// The time returned by `tx_get_block_time`
+        pub block_time: Option<DateTimeUtc>,
+        /// The hash returned by `tx_get_block_hash`
+        pub block_hash: Option<BlockHash>,
+    }
+
+    thread_local! {
+        static MOCKS: RefCell<Option<MockHostResponses>> = RefCell::new(None);
+    }
+
+    /// Run the given function with the host calls on the current thread
+    /// mocked. The mocks are removed once the function returns or panics.
+    pub fn with_mocks<T>(mocks: MockHostResponses, f: impl FnOnce() -> T) -> T {
+        struct ResetMocks;
+        impl Drop for ResetMocks {
+            fn drop(&mut self) {
+                MOCKS.with(|cell| cell.replace(None));
+            }
+        }
+
+        MOCKS.with(|cell| cell.replace(Some(mocks)));
+        let _reset = ResetMocks;
+        f()
+    }
+
+    /// Get the mocked value of a key, if any
+    pub(super) fn read(key: &Key) -> Option<Option<Vec<u8>>> {
+        MOCKS.with(|cell| cell.borrow().as_ref()?.reads.get(key).cloned())
+    }
+
+    /// Get the mocked block time, if any
+    pub(super) fn block_time() -> Option<DateTimeUtc> {
+        MOCKS.with(|cell| cell.borrow().as_ref()?.block_time)
+    }
+
+    /// Get the mocked block hash, if any
+    pub(super) fn block_hash() -> Option<BlockHash> {
+        MOCKS.with(|cell| cell.borrow().as_ref()?.block_hash.clone())
+    }
+}
+
+/// Recording of the host calls made by VPs, to audit what a VP examined
+pub mod trace {
+    use std::cell::RefCell;
+
+    use super::*;
+
+    /// A host call made by a VP
+    #[derive(Debug, Clone, PartialEq, Eq)]
+    pub struct VpHostCall {
+        /// The name of the host function
+        pub name: &'static str,
+        /// The storage key or prefix accessed by the call, if any
+        pub key: Option<Key>,
+    }
+
+    thread_local! {
+        static TRACE: RefCell<Option<Vec<VpHostCall>>> = RefCell::new(None);
+    }
+
+    /// Run the given function while recording the VP host calls made on the
+    /// current thread. Returns the function's result with the recorded calls
+    /// in the order in which they were made.
+    pub fn with_trace<T>(f: impl FnOnce() -> T) -> (T, Vec<VpHostCall>) {
+        struct StopTrace;
+        impl Drop for StopTrace {
+            fn drop(&mut self) {
+                TRACE.with(|cell| cell.replace(None));
+            }
+        }
+
+        TRACE.with(|cell| cell.replace(Some(vec![])));
+        let _stop = StopTrace;
+        let result = f();
+        let trace = TRACE.with(|cell| cell.take()).unwrap_or_default();
+        (result, trace)
+    }
+
+    /// Record a host call, if the recording is on. The key is only built
+    /// when the call is recorded.
+    pub(super) fn record(
+        name: &'static str,
+        key: impl FnOnce() -> Option<Key>,
+    ) {
+        TRACE.with(|cell| {
+            if let Some(trace) = cell.borrow_mut().as_mut() {
+                trace.push(VpHostCall { name, key: key() });
+            }
+        })
+    }
+}
+
+/// The verbosity of the host calls traces, which can be configured for the
+/// process and overridden per runner
+pub mod log_level {
+    use std::cell::Cell;
+    use std::sync::atomic::{AtomicBool, Ordering};
+
+    pub use tracing::level_filters::LevelFilter;
+
+    /// Whether the host calls traces are enabled by the process-wide level.
+    /// Until the level is set, the traces are only filtered by the tracing
+    /// subscriber.
+    static DEFAULT_ENABLED: AtomicBool = AtomicBool::new(true);
+
+    thread_local! {
+        static LOG_LEVEL: Cell<Option<LevelFilter>> = Cell::new(None);
+    }
+
+    /// Set the process-wide level of the host calls traces, e.g. from the
+    /// node's logging config. It applies to the threads without a level set
+    /// with [`with_log_level`]. The traces are logged at the
+    /// [`tracing::Level::DEBUG`], so a less verbose level silences them.
+    pub fn set_default_log_level(level: LevelFilter) {
+        DEFAULT_ENABLED
+            .store(level >= tracing::Level::DEBUG, Ordering::Relaxed);
+    }
+
+    /// Run the given function with the host calls traces on the current
+    /// thread limited to the given level or, if `None`, to the process-wide
+    /// level.
+    pub fn with_log_level<T>(
+        level: Option<LevelFilter>,
+        f: impl FnOnce() -> T,
+    ) -> T {
+        struct RestoreLevel(Option<LevelFilter>);
+        impl Drop for RestoreLevel {
+            fn drop(&mut self) {
+                LOG_LEVEL.with(|cell| cell.set(self.0));
+            }
+        }
+
+        let _restore = RestoreLevel(LOG_LEVEL.with(|cell| cell.replace(level)));
+        f()
+    }
+
+    /// Whether the host calls traces are enabled on the current thread
+    pub(super) fn is_enabled() -> bool {
+        match LOG_LEVEL.with(Cell::get) {
+            Some(level) => level >= tracing::Level::DEBUG,
+            None => DEFAULT_ENABLED.load(Ordering::Relaxed),
+        }
+    }
+}
+
 /// A helper module for testing
 #[cfg(feature = "testing")]
 pub mod testing {
@@ -2752,7 +4550,9 @@ pub mod testing {
         sentinel: &mut TxSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        fee_payer: &Option<Address>,
         result_buffer: &mut Option<Vec<u8>>,
+        host_state: &mut TxHostState,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
         #[cfg(feature = "wasm-runtime")] tx_wasm_cache: &mut TxCache<CA>,
     ) -> TxVmEnv<'static, NativeMemory, DB, H, CA>
@@ -2770,8 +4570,10 @@ pub mod testing {
             sentinel,
             tx,
             tx_index,
+            fee_payer,
             verifiers,
             result_buffer,
+            host_state,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
             #[cfg(feature = "wasm-runtime")]
@@ -2790,7 +4592,9 @@ pub mod testing {
         sentinel: &mut TxSentinel,
         tx: &Tx,
         tx_index: &TxIndex,
+        fee_payer: &Option<Address>,
         result_buffer: &mut Option<Vec<u8>>,
+        host_state: &mut TxHostState,
         #[cfg(feature = "wasm-runtime")] vp_wasm_cache: &mut VpCache<CA>,
         #[cfg(feature = "wasm-runtime")] tx_wasm_cache: &mut TxCache<CA>,
     ) -> TxVmEnv<'static, WasmMemory, DB, H, CA>
@@ -2799,7 +4603,9 @@ pub mod testing {
         H: StorageHasher,
         CA: WasmCacheAccess,
     {
-        let store = crate::vm::wasm::compilation_cache::common::store();
+        let store = crate::vm::wasm::compilation_cache::common::store(
+            crate::vm::wasm::compilation_cache::common::WasmEngine::default(),
+        );
         let initial_memory =
             crate::vm::wasm::memory::prepare_tx_memory(&store).unwrap();
         let mut wasm_memory = WasmMemory::default();
@@ -2814,8 +4620,10 @@ pub mod testing {
             sentinel,
             tx,
             tx_index,
+            fee_payer,
             verifiers,
             result_buffer,
+            host_state,
             #[cfg(feature = "wasm-runtime")]
             vp_wasm_cache,
             #[cfg(feature = "wasm-runtime")]
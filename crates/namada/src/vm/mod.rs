@@ -4,7 +4,10 @@ use std::ffi::c_void;
 use std::marker::PhantomData;
 use std::slice;
 
-use wasmparser::{Validator, WasmFeatures};
+use wasmparser::{
+    DataKind, ExternalKind, Operator, Parser, Payload, TypeRef, Validator,
+    WasmFeatures,
+};
 
 pub mod host_env;
 pub mod memory;
@@ -14,6 +17,9 @@ pub mod types;
 pub mod wasm;
 use thiserror::Error;
 
+/// The size of a wasm memory page in bytes
+const WASM_PAGE_SIZE: u64 = 64 * 1024;
+
 const UNTRUSTED_WASM_FEATURES: WasmFeatures = WasmFeatures {
     mutable_global: false,
     saturating_float_to_int: false,
@@ -44,6 +50,18 @@ pub enum WasmValidationError {
          {UNTRUSTED_WASM_FEATURES:?}"
     )]
     ForbiddenWasmFeatures(wasmparser::BinaryReaderError),
+    #[error(
+        "Data segment at offset {offset} of length {len} exceeds the memory \
+         {memory_index} of {memory_size} bytes"
+    )]
+    DataSegmentOutOfBounds {
+        memory_index: u32,
+        offset: u64,
+        len: u64,
+        memory_size: u64,
+    },
+    #[error("Invalid WASM exporting the mutable global {index} as \"{name}\"")]
+    ExportedMutableGlobal { name: String, index: u32 },
 }
 
 /// WASM Cache access level, used to limit dry-ran transactions to read-only
@@ -235,9 +253,156 @@ impl<'a, T: 'a> MutHostSlice<'a, &[T]> {
 pub fn validate_untrusted_wasm(
     wasm_code: impl AsRef<[u8]>,
 ) -> Result<(), WasmValidationError> {
+    // Checked before the validator, which would otherwise reject the exported
+    // mutable globals as a forbidden feature
+    validate_exported_globals(wasm_code.as_ref())?;
     let mut validator = Validator::new_with_features(UNTRUSTED_WASM_FEATURES);
     let _types = validator
         .validate_all(wasm_code.as_ref())
         .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+    validate_data_segments(wasm_code.as_ref())
+}
+
+/// Check that the code doesn't export any mutable globals, which could carry
+/// state across the instantiations of a module. The module may still use
+/// mutable globals internally.
+fn validate_exported_globals(
+    wasm_code: &[u8],
+) -> Result<(), WasmValidationError> {
+    // The mutability of the imported and then the defined globals, in the
+    // order of their indices
+    let mut globals_mutability: Vec<bool> = vec![];
+    for payload in Parser::new(0).parse_all(wasm_code) {
+        match payload.map_err(WasmValidationError::ForbiddenWasmFeatures)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    if let TypeRef::Global(global) = import.ty {
+                        globals_mutability.push(global.mutable);
+                    }
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                for global in reader {
+                    let global = global
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    globals_mutability.push(global.ty.mutable);
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    let is_mutable = export.kind == ExternalKind::Global
+                        && globals_mutability
+                            .get(export.index as usize)
+                            .copied()
+                            .unwrap_or_default();
+                    if is_mutable {
+                        return Err(
+                            WasmValidationError::ExportedMutableGlobal {
+                                name: export.name.to_string(),
+                                index: export.index,
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
     Ok(())
 }
+
+/// Check that the active data segments with a constant offset fit within the
+/// minimum size of the memory that they initialize. The code must have
+/// already been validated.
+fn validate_data_segments(wasm_code: &[u8]) -> Result<(), WasmValidationError> {
+    // The minimum sizes of the imported and then the defined memories, in
+    // the order of their indices
+    let mut memory_sizes: Vec<u64> = vec![];
+    for payload in Parser::new(0).parse_all(wasm_code) {
+        match payload.map_err(WasmValidationError::ForbiddenWasmFeatures)? {
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    if let TypeRef::Memory(memory) = import.ty {
+                        memory_sizes.push(
+                            memory.initial.saturating_mul(WASM_PAGE_SIZE),
+                        );
+                    }
+                }
+            }
+            Payload::MemorySection(reader) => {
+                for memory in reader {
+                    let memory = memory
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    memory_sizes
+                        .push(memory.initial.saturating_mul(WASM_PAGE_SIZE));
+                }
+            }
+            Payload::DataSection(reader) => {
+                for data in reader {
+                    let data = data
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?;
+                    let DataKind::Active {
+                        memory_index,
+                        offset_expr,
+                    } = data.kind
+                    else {
+                        continue;
+                    };
+                    // Offsets given by an imported global cannot be checked
+                    // statically
+                    let offset = match offset_expr
+                        .get_operators_reader()
+                        .read()
+                        .map_err(WasmValidationError::ForbiddenWasmFeatures)?
+                    {
+                        // The offset is interpreted as an unsigned integer
+                        Operator::I32Const { value } => value as u32 as u64,
+                        _ => continue,
+                    };
+                    let len = data.data.len() as u64;
+                    let memory_size = memory_sizes
+                        .get(memory_index as usize)
+                        .copied()
+                        .unwrap_or_default();
+                    if offset.saturating_add(len) > memory_size {
+                        return Err(
+                            WasmValidationError::DataSegmentOutOfBounds {
+                                memory_index,
+                                offset,
+                                len,
+                                memory_size,
+                            },
+                        );
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+/// Read the contents of the custom section with the given name from a wasm
+/// code, e.g. the metadata (name, version, ABI) embedded by the authors of
+/// a contract. Returns `None` if there is no such section or if the code
+/// cannot be parsed before the section is found.
+pub fn read_custom_section(
+    wasm_code: impl AsRef<[u8]>,
+    name: &str,
+) -> Option<Vec<u8>> {
+    for payload in Parser::new(0).parse_all(wasm_code.as_ref()) {
+        match payload.ok()? {
+            Payload::CustomSection(reader) if reader.name() == name => {
+                return Some(reader.data().to_vec());
+            }
+            _ => {}
+        }
+    }
+    None
+}
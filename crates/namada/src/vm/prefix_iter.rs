@@ -1,12 +1,56 @@
 //! The storage prefix iterators can be used to iterate over a common prefix of
 //! storage keys.
 
+use std::cell::Cell;
 use std::collections::HashMap;
 
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::types::storage::Key;
 use namada_state::PrefixIter;
+use thiserror::Error;
+
+/// The default maximum number of prefix iterators that can be open at the same
+/// time during a wasm run.
+pub const DEFAULT_MAX_OPEN_ITERATORS: usize = 1024;
+
+thread_local! {
+    static MAX_OPEN_ITERATORS: Cell<usize> =
+        Cell::new(DEFAULT_MAX_OPEN_ITERATORS);
+}
+
+/// Run the given function with the wasm runs on the current thread allowed to
+/// open at most `max_open` prefix iterators at the same time.
+pub fn with_max_open_iterators<T>(max_open: usize, f: impl FnOnce() -> T) -> T {
+    struct RestoreMax(usize);
+    impl Drop for RestoreMax {
+        fn drop(&mut self) {
+            MAX_OPEN_ITERATORS.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _restore =
+        RestoreMax(MAX_OPEN_ITERATORS.with(|cell| cell.replace(max_open)));
+    f()
+}
+
+/// The maximum number of prefix iterators that a wasm run on the current
+/// thread can open at the same time. Unless set with
+/// [`with_max_open_iterators`], it's the [`DEFAULT_MAX_OPEN_ITERATORS`].
+pub fn max_open_iterators() -> usize {
+    MAX_OPEN_ITERATORS.with(Cell::get)
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("Too many open prefix iterators, the limit is {0}")]
+    TooManyOpenIterators(usize),
+}
 
 /// A temporary iterators storage, used during a wasm run after which it's
-/// dropped. Each iterator is assigned a [`PrefixIteratorId`].
+/// dropped. Each iterator is assigned a [`PrefixIteratorId`]. Iterators are
+/// dropped once exhausted and the number of iterators open at the same time is
+/// bounded.
 #[derive(Debug)]
 pub struct PrefixIterators<'iter, DB>
 where
@@ -14,26 +58,136 @@ where
 {
     index: PrefixIteratorId,
     iterators: HashMap<PrefixIteratorId, PrefixIter<'iter, DB>>,
+    /// The positions of the resumable iterators
+    positions: HashMap<PrefixIteratorId, Position>,
+    max_open: usize,
 }
 
 impl<'iter, DB> PrefixIterators<'iter, DB>
 where
     DB: namada_state::DB + namada_state::DBIter<'iter>,
 {
-    /// Insert a new prefix iterator to the temporary storage.
-    pub fn insert(&mut self, iter: PrefixIter<'iter, DB>) -> PrefixIteratorId {
+    /// Initialize a temporary iterators storage that allows at most
+    /// `max_open` iterators to be open at the same time.
+    pub fn new(max_open: usize) -> Self {
+        Self {
+            index: PrefixIteratorId::default(),
+            iterators: HashMap::default(),
+            positions: HashMap::default(),
+            max_open,
+        }
+    }
+
+    /// Insert a new prefix iterator to the temporary storage. Returns an error
+    /// if the maximum number of open iterators has been reached.
+    pub fn insert(
+        &mut self,
+        iter: PrefixIter<'iter, DB>,
+    ) -> Result<PrefixIteratorId, Error> {
+        if self.iterators.len() >= self.max_open {
+            return Err(Error::TooManyOpenIterators(self.max_open));
+        }
         let id = self.index;
         self.iterators.insert(id, iter);
         self.index = id.next_id();
-        id
+        Ok(id)
     }
 
-    /// Get the next item in the given prefix iterator.
+    /// Get the next item in the given prefix iterator. The iterator is dropped
+    /// once it's exhausted.
     pub fn next(
         &mut self,
         id: PrefixIteratorId,
     ) -> Option<<PrefixIter<'iter, DB> as Iterator>::Item> {
-        self.iterators.get_mut(&id).and_then(|i| i.next())
+        let next = self.iterators.get_mut(&id).and_then(|i| i.next());
+        if next.is_none() {
+            self.remove(id);
+        }
+        next
+    }
+
+    /// Insert a new prefix iterator over the given prefix of the given state,
+    /// whose position is recorded to be able to take a [`ContinuationToken`]
+    /// from it. Returns an error if the maximum number of open iterators has
+    /// been reached.
+    pub fn insert_resumable(
+        &mut self,
+        iter: PrefixIter<'iter, DB>,
+        prefix: Key,
+        state: IteratedState,
+    ) -> Result<PrefixIteratorId, Error> {
+        let id = self.insert(iter)?;
+        self.positions.insert(
+            id,
+            Position {
+                prefix,
+                state,
+                last_key: None,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Insert a prefix iterator resumed from the given token. The iterator
+    /// must be created over the token's prefix and state, after its last key.
+    /// Returns an error if the maximum number of open iterators has been
+    /// reached.
+    ///
+    /// If the storage has changed since the token was taken, the keys added
+    /// after the last returned key will be iterated, while the keys added
+    /// before it will not. For a consistent iteration, the iterators must be
+    /// created from the same state, e.g. the storage at a pinned block height.
+    pub fn insert_resumed(
+        &mut self,
+        iter: PrefixIter<'iter, DB>,
+        token: ContinuationToken,
+    ) -> Result<PrefixIteratorId, Error> {
+        let ContinuationToken {
+            prefix,
+            state,
+            last_key,
+        } = token;
+        let id = self.insert(iter)?;
+        self.positions.insert(
+            id,
+            Position {
+                prefix,
+                state,
+                last_key: Some(last_key),
+            },
+        );
+        Ok(id)
+    }
+
+    /// Record the last key returned from the given prefix iterator, for an
+    /// item that was taken from it directly via [`PrefixIterators::get_mut`].
+    /// The key is only kept for the resumable iterators.
+    pub fn record_position(&mut self, id: PrefixIteratorId, key: String) {
+        if let Some(position) = self.positions.get_mut(&id) {
+            position.last_key = Some(key);
+        }
+    }
+
+    /// Get a token from which the iteration of the given resumable prefix
+    /// iterator can be resumed later. Returns `None` when no item has been
+    /// returned from the iterator yet, it's exhausted or it's not resumable.
+    pub fn continuation_token(
+        &self,
+        id: PrefixIteratorId,
+    ) -> Option<ContinuationToken> {
+        let position = self.positions.get(&id)?;
+        let last_key = position.last_key.clone()?;
+        Some(ContinuationToken {
+            prefix: position.prefix.clone(),
+            state: position.state,
+            last_key,
+        })
+    }
+
+    /// Drop the prefix iterator with the given ID.
+    pub fn remove(&mut self, id: PrefixIteratorId) {
+        self.iterators.remove(&id);
+        self.positions.remove(&id);
     }
 
     /// Get prefix iterator with the given ID.
@@ -50,10 +204,53 @@ where
     DB: namada_state::DB + namada_state::DBIter<'iter>,
 {
     fn default() -> Self {
-        Self {
-            index: PrefixIteratorId::default(),
-            iterators: HashMap::default(),
-        }
+        Self::new(DEFAULT_MAX_OPEN_ITERATORS)
+    }
+}
+
+/// The state of the storage iterated by a prefix iterator.
+#[derive(
+    Clone, Copy, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize,
+)]
+pub enum IteratedState {
+    /// The prior state, before the tx execution
+    Pre,
+    /// The posterior state, after the tx execution
+    Post,
+}
+
+/// The position of a resumable prefix iterator.
+#[derive(Debug)]
+struct Position {
+    prefix: Key,
+    state: IteratedState,
+    /// The last key returned from the iterator, if any
+    last_key: Option<String>,
+}
+
+/// An opaque token to resume a prefix iteration after the last key that was
+/// returned from it, without returning the already iterated entries again.
+#[derive(Clone, Debug, Eq, PartialEq, BorshSerialize, BorshDeserialize)]
+pub struct ContinuationToken {
+    prefix: Key,
+    state: IteratedState,
+    last_key: String,
+}
+
+impl ContinuationToken {
+    /// The prefix of the iteration
+    pub fn prefix(&self) -> &Key {
+        &self.prefix
+    }
+
+    /// The iterated state of the storage
+    pub fn state(&self) -> IteratedState {
+        self.state
+    }
+
+    /// The last key returned from the iteration, after which it's resumed
+    pub fn last_key(&self) -> &str {
+        &self.last_key
     }
 }
 
@@ -77,3 +274,90 @@ impl PrefixIteratorId {
         PrefixIteratorId(self.0 + 1)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use borsh_ext::BorshSerializeExt;
+    use namada_state::testing::TestWlStorage;
+    use namada_state::{
+        iter_prefix_post, iter_prefix_post_after, StorageWrite,
+    };
+
+    use super::*;
+
+    /// Test that an iteration resumed from a token over the committed state
+    /// returns every entry exactly once.
+    #[test]
+    fn test_resume_prefix_iteration() {
+        let mut wl_storage = TestWlStorage::default();
+        let prefix = Key::parse("prefix").unwrap();
+        let keys: Vec<Key> = (0..10)
+            .map(|i| prefix.push(&format!("{i:02}")).unwrap())
+            .collect();
+        for (i, key) in keys.iter().enumerate() {
+            wl_storage.write(key, i as u64).unwrap();
+        }
+        wl_storage.commit_tx();
+        wl_storage.commit_block().unwrap();
+
+        // A non-resumable iterator doesn't give a token
+        let mut iterators = PrefixIterators::default();
+        let (iter, _gas) = iter_prefix_post(
+            &wl_storage.write_log,
+            &wl_storage.storage,
+            &prefix,
+        );
+        let id = iterators.insert(iter).unwrap();
+        let (key, _val, _gas) = iterators.next(id).unwrap();
+        iterators.record_position(id, key);
+        assert!(iterators.continuation_token(id).is_none());
+
+        // Iterate a few entries and take a token
+        let (iter, _gas) = iter_prefix_post(
+            &wl_storage.write_log,
+            &wl_storage.storage,
+            &prefix,
+        );
+        let id = iterators
+            .insert_resumable(iter, prefix.clone(), IteratedState::Post)
+            .unwrap();
+        assert!(iterators.continuation_token(id).is_none());
+        let mut iterated: Vec<String> = vec![];
+        for _ in 0..4 {
+            let (key, _val, _gas) = iterators.next(id).unwrap();
+            iterators.record_position(id, key.clone());
+            iterated.push(key);
+        }
+        let token = iterators.continuation_token(id).unwrap();
+        drop(iterators);
+
+        // The token can be passed around encoded
+        let token =
+            ContinuationToken::try_from_slice(&token.serialize_to_vec())
+                .unwrap();
+        assert_eq!(token.prefix(), &prefix);
+        assert_eq!(token.state(), IteratedState::Post);
+
+        // Resume the iteration from a new iterator seeked after the token's
+        // last key
+        let mut iterators = PrefixIterators::default();
+        let (iter, _gas) = iter_prefix_post_after(
+            &wl_storage.write_log,
+            &wl_storage.storage,
+            token.prefix(),
+            token.last_key(),
+        );
+        let id = iterators.insert_resumed(iter, token.clone()).unwrap();
+        // The same token is given back before any entry is iterated
+        assert_eq!(iterators.continuation_token(id), Some(token));
+        while let Some((key, _val, _gas)) = iterators.next(id) {
+            iterated.push(key);
+        }
+        let expected: Vec<String> =
+            keys.iter().map(|key| key.to_string()).collect();
+        assert_eq!(iterated, expected);
+
+        // There's nothing left to resume from an exhausted iterator
+        assert!(iterators.continuation_token(id).is_none());
+    }
+}
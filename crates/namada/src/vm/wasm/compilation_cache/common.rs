@@ -10,6 +10,7 @@ use std::fs;
 use std::marker::PhantomData;
 use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, RecvTimeoutError};
 use std::sync::{Arc, RwLock};
 use std::thread::sleep;
 use std::time::Duration;
@@ -30,15 +31,77 @@ pub struct Cache<N: CacheName, A: WasmCacheAccess> {
     /// Cached files directory
     dir: PathBuf,
     /// Compilation progress
-    progress: Arc<RwLock<HashMap<Hash, Compilation>>>,
+    progress: Arc<RwLock<HashMap<CompileCacheKey, Compilation>>>,
     /// In-memory LRU cache of compiled modules
     in_memory: Arc<RwLock<MemoryCache>>,
+    /// The engine used to compile and load modules
+    engine: WasmEngine,
+    /// The identifier of the compiler and its version
+    compiler_id: String,
+    /// The maximum wall-clock time that a module's compilation may take
+    compile_timeout: Option<Duration>,
     /// The cache's name
     name: PhantomData<N>,
     /// Cache access level
     access: PhantomData<A>,
 }
 
+/// The wasmer engine used to compile wasm modules and to load them from the
+/// file system cache.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum WasmEngine {
+    /// Universal engine. Modules are compiled in memory and serialized to
+    /// bytes in the file cache.
+    #[default]
+    Universal,
+    /// Dylib engine. Modules are compiled ahead of time to native shared
+    /// objects, for environments that don't allow to map writable memory as
+    /// executable.
+    #[cfg(feature = "wasm-engine-dylib")]
+    Dylib,
+}
+
+impl WasmEngine {
+    /// Get the identifier of the engine
+    pub fn id(&self) -> &'static str {
+        match self {
+            WasmEngine::Universal => "universal",
+            #[cfg(feature = "wasm-engine-dylib")]
+            WasmEngine::Dylib => "dylib",
+        }
+    }
+}
+
+/// The key of a compiled module in the memory and file caches. Besides the
+/// hash of the wasm code, it identifies the engine and the compiler that
+/// produced the module, so that the modules compiled by a different toolchain
+/// (e.g. before an upgrade) are never loaded.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct CompileCacheKey {
+    /// The hash of the wasm code
+    pub code_hash: Hash,
+    /// The identifier of the engine that compiled the module
+    pub engine_id: &'static str,
+    /// The identifier of the compiler and its version
+    pub compiler_id: String,
+}
+
+impl CompileCacheKey {
+    /// A digest of the key, used to name the files of the module
+    fn digest(&self) -> Hash {
+        Hash::sha256(format!(
+            "{}/{}/{}",
+            self.code_hash, self.engine_id, self.compiler_id
+        ))
+    }
+}
+
+/// Get the identifier of the compiler used for the wasm modules, including
+/// its version
+pub fn compiler_id() -> String {
+    format!("singlepass-wasmer-{}", wasmer::VERSION)
+}
+
 /// This trait is used to give names to different caches
 pub trait CacheName: Clone + std::fmt::Debug {
     /// Get the name of the cache
@@ -46,7 +109,8 @@ pub trait CacheName: Clone + std::fmt::Debug {
 }
 
 /// In-memory LRU cache of compiled modules
-type MemoryCache = CLruCache<Hash, Module, RandomState, ModuleCacheScale>;
+type MemoryCache =
+    CLruCache<CompileCacheKey, Module, RandomState, ModuleCacheScale>;
 
 /// Compilation progress
 #[derive(Debug)]
@@ -60,8 +124,8 @@ enum Compilation {
 #[derive(Debug)]
 struct ModuleCacheScale;
 
-impl WeightScale<Hash, Module> for ModuleCacheScale {
-    fn weight(&self, _key: &Hash, _value: &Module) -> usize {
+impl WeightScale<CompileCacheKey, Module> for ModuleCacheScale {
+    fn weight(&self, _key: &CompileCacheKey, _value: &Module) -> usize {
         1
     }
 }
@@ -87,11 +151,50 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
             dir,
             progress: Default::default(),
             in_memory,
+            engine: WasmEngine::default(),
+            compiler_id: compiler_id(),
+            compile_timeout: None,
             name: Default::default(),
             access: Default::default(),
         }
     }
 
+    /// Set the engine used to compile and load modules. This should be set
+    /// before any module is compiled with the cache, as the modules that are
+    /// already cached in memory are not recompiled.
+    pub fn with_engine(mut self, engine: WasmEngine) -> Self {
+        self.engine = engine;
+        self
+    }
+
+    /// Set a budget for the wall-clock time that a module's compilation may
+    /// take in [`Cache::compile_or_fetch`]. When it's exceeded, the
+    /// compilation fails with [`wasm::run::Error::CompileTimeout`].
+    ///
+    /// Whether the budget is exceeded depends on the node's machine and load,
+    /// so it must not be set on the caches used to process the blocks, where
+    /// all the nodes have to agree on the result of every tx and VP. It's
+    /// meant for the non-consensus runners, e.g. [`wasm::run::AsyncTxRunner`].
+    pub fn with_compile_timeout(mut self, timeout: Duration) -> Self {
+        self.compile_timeout = Some(timeout);
+        self
+    }
+
+    /// Get the engine used to compile and load modules.
+    pub fn engine(&self) -> WasmEngine {
+        self.engine
+    }
+
+    /// Get the key of the module compiled from the code with the given hash
+    /// by this cache's engine and compiler.
+    pub fn key(&self, code_hash: &Hash) -> CompileCacheKey {
+        CompileCacheKey {
+            code_hash: *code_hash,
+            engine_id: self.engine.id(),
+            compiler_id: self.compiler_id.clone(),
+        }
+    }
+
     /// Get a WASM module from LRU cache, from a file or compile it and cache
     /// it. If the cache access is set to [`crate::vm::WasmCacheRwAccess`], it
     /// updates the position in the LRU cache. Otherwise, the compiled
@@ -123,14 +226,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         &mut self,
         hash: &Hash,
     ) -> Result<Option<(Module, Store)>, wasm::run::Error> {
+        let key = self.key(hash);
         let mut in_memory = self.in_memory.write().unwrap();
-        if let Some(module) = in_memory.get(hash) {
+        if let Some(module) = in_memory.get(&key) {
             tracing::trace!(
                 "{} found {} in cache.",
                 N::name(),
                 hash.to_string()
             );
-            return Ok(Some((module.clone(), store())));
+            return Ok(Some((module.clone(), store(self.engine))));
         }
         drop(in_memory);
 
@@ -143,21 +247,21 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         };
         loop {
             let progress = self.progress.read().unwrap();
-            match progress.get(hash) {
+            match progress.get(&key) {
                 Some(Compilation::Done) => {
                     drop(progress);
                     let mut in_memory = self.in_memory.write().unwrap();
-                    if let Some(module) = in_memory.get(hash) {
+                    if let Some(module) = in_memory.get(&key) {
                         tracing::info!(
                             "{} found {} in memory cache.",
                             N::name(),
                             hash.to_string()
                         );
-                        return Ok(Some((module.clone(), store())));
+                        return Ok(Some((module.clone(), store(self.engine))));
                     }
 
                     if let Ok((module, store)) =
-                        file_load_module(&self.dir, hash)
+                        file_load_module(&self.dir, &key, self.engine)
                     {
                         tracing::info!(
                             "{} found {} in file cache.",
@@ -165,8 +269,8 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                             hash.to_string()
                         );
                         // Put into cache, ignore result if it's full
-                        let _ =
-                            in_memory.put_with_weight(*hash, module.clone());
+                        let _ = in_memory
+                            .put_with_weight(key.clone(), module.clone());
 
                         return Ok(Some((module, store)));
                     } else {
@@ -186,30 +290,32 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                 }
                 None => {
                     drop(progress);
-                    let (module, store) = if module_file_exists(&self.dir, hash)
-                    {
-                        tracing::info!(
-                            "Trying to load {} {} from file.",
-                            N::name(),
-                            hash.to_string()
-                        );
-                        if let Ok(res) = file_load_module(&self.dir, hash) {
-                            res
+                    let (module, store) =
+                        if module_file_exists(&self.dir, &key, self.engine) {
+                            tracing::info!(
+                                "Trying to load {} {} from file.",
+                                N::name(),
+                                hash.to_string()
+                            );
+                            if let Ok(res) =
+                                file_load_module(&self.dir, &key, self.engine)
+                            {
+                                res
+                            } else {
+                                return Ok(None);
+                            }
                         } else {
                             return Ok(None);
-                        }
-                    } else {
-                        return Ok(None);
-                    };
+                        };
 
                     // Update progress
                     let mut progress = self.progress.write().unwrap();
-                    progress.insert(*hash, Compilation::Done);
+                    progress.insert(key.clone(), Compilation::Done);
 
                     // Put into cache, ignore the result (fails if the module
                     // cannot fit into the cache)
                     let mut in_memory = self.in_memory.write().unwrap();
-                    let _ = in_memory.put_with_weight(*hash, module.clone());
+                    let _ = in_memory.put_with_weight(key, module.clone());
 
                     return Ok(Some((module, store)));
                 }
@@ -223,14 +329,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         &self,
         hash: &Hash,
     ) -> Result<Option<(Module, Store)>, wasm::run::Error> {
+        let key = self.key(hash);
         let in_memory = self.in_memory.read().unwrap();
-        if let Some(module) = in_memory.peek(hash) {
+        if let Some(module) = in_memory.peek(&key) {
             tracing::info!(
                 "{} found {} in cache.",
                 N::name(),
                 hash.to_string()
             );
-            return Ok(Some((module.clone(), store())));
+            return Ok(Some((module.clone(), store(self.engine))));
         }
         drop(in_memory);
 
@@ -243,21 +350,21 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         };
         loop {
             let progress = self.progress.read().unwrap();
-            match progress.get(hash) {
+            match progress.get(&key) {
                 Some(Compilation::Done) => {
                     drop(progress);
                     let in_memory = self.in_memory.read().unwrap();
-                    if let Some(module) = in_memory.peek(hash) {
+                    if let Some(module) = in_memory.peek(&key) {
                         tracing::info!(
                             "{} found {} in memory cache.",
                             N::name(),
                             hash.to_string()
                         );
-                        return Ok(Some((module.clone(), store())));
+                        return Ok(Some((module.clone(), store(self.engine))));
                     }
 
                     if let Ok((module, store)) =
-                        file_load_module(&self.dir, hash)
+                        file_load_module(&self.dir, &key, self.engine)
                     {
                         tracing::info!(
                             "{} found {} in file cache.",
@@ -283,13 +390,15 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                 None => {
                     drop(progress);
 
-                    return if module_file_exists(&self.dir, hash) {
+                    return if module_file_exists(&self.dir, &key, self.engine) {
                         tracing::info!(
                             "Trying to load {} {} from file.",
                             N::name(),
                             hash.to_string()
                         );
-                        if let Ok(res) = file_load_module(&self.dir, hash) {
+                        if let Ok(res) =
+                            file_load_module(&self.dir, &key, self.engine)
+                        {
                             return Ok(Some(res));
                         } else {
                             return Ok(None);
@@ -308,42 +417,43 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
         code: impl AsRef<[u8]>,
     ) -> Result<Option<(Module, Store)>, wasm::run::Error> {
         let hash = hash_of_code(&code);
+        let key = self.key(&hash);
 
         if !A::is_read_write() {
             // It doesn't update the cache and files
             let progress = self.progress.read().unwrap();
-            match progress.get(&hash) {
+            match progress.get(&key) {
                 Some(_) => return self.peek(&hash),
                 None => {
                     let code = wasm::run::prepare_wasm_code(code)?;
-                    return Ok(Some(compile(code)?));
+                    return Ok(Some(self.compile_within_budget(code)?));
                 }
             }
         }
 
         let mut progress = self.progress.write().unwrap();
-        if progress.get(&hash).is_some() {
+        if progress.get(&key).is_some() {
             drop(progress);
             return self.fetch(&hash);
         }
-        progress.insert(hash, Compilation::Compiling);
+        progress.insert(key.clone(), Compilation::Compiling);
         drop(progress);
 
         tracing::info!("Compiling {} {}.", N::name(), hash.to_string());
 
         match wasm::run::prepare_wasm_code(code) {
-            Ok(code) => match compile(code) {
+            Ok(code) => match self.compile_within_budget(code) {
                 Ok((module, store)) => {
                     // Write the file
-                    file_write_module(&self.dir, &module, &hash);
+                    file_write_module(&self.dir, &module, &key, self.engine);
 
                     // Update progress
                     let mut progress = self.progress.write().unwrap();
-                    progress.insert(hash, Compilation::Done);
+                    progress.insert(key.clone(), Compilation::Done);
 
                     // Put into cache, ignore result if it's full
                     let mut in_memory = self.in_memory.write().unwrap();
-                    let _ = in_memory.put_with_weight(hash, module.clone());
+                    let _ = in_memory.put_with_weight(key, module.clone());
 
                     Ok(Some((module, store)))
                 }
@@ -354,7 +464,7 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                         err
                     );
                     let mut progress = self.progress.write().unwrap();
-                    progress.remove(&hash);
+                    progress.remove(&key);
                     Err(err)
                 }
             },
@@ -365,43 +475,77 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                     err
                 );
                 let mut progress = self.progress.write().unwrap();
-                progress.remove(&hash);
+                progress.remove(&key);
                 Err(err)
             }
         }
     }
 
+    /// Compile a WASM module, failing if the compilation exceeds the
+    /// configured time budget, if any.
+    fn compile_within_budget(
+        &self,
+        code: Vec<u8>,
+    ) -> Result<(Module, Store), wasm::run::Error> {
+        let timeout = match self.compile_timeout {
+            Some(timeout) => timeout,
+            None => return compile(code, self.engine),
+        };
+        // A compilation cannot be interrupted, so it runs in a watchdog thread
+        // that we stop waiting for when the budget is exceeded. The thread
+        // then finishes on its own and its result is discarded.
+        let (sender, receiver) = mpsc::channel();
+        let engine = self.engine;
+        std::thread::spawn(move || {
+            // The receiver is gone if the compilation has timed out
+            let _ = sender.send(compile(code, engine));
+        });
+        match receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => {
+                Err(wasm::run::Error::CompileTimeout(timeout))
+            }
+            Err(RecvTimeoutError::Disconnected) => {
+                panic!("The wasm compilation thread has panicked")
+            }
+        }
+    }
+
     /// Pre-compile a WASM module to a file. The compilation runs in a new OS
     /// thread and the function returns immediately.
     pub fn pre_compile(&mut self, code: impl AsRef<[u8]>) {
         if A::is_read_write() {
             let hash = hash_of_code(&code);
+            let key = self.key(&hash);
             let mut progress = self.progress.write().unwrap();
-            match progress.get(&hash) {
+            match progress.get(&key) {
                 Some(_) => {
                     // Already known, do nothing
                 }
                 None => {
-                    if module_file_exists(&self.dir, &hash) {
-                        progress.insert(hash, Compilation::Done);
+                    if module_file_exists(&self.dir, &key, self.engine) {
+                        progress.insert(key, Compilation::Done);
                         return;
                     }
-                    progress.insert(hash, Compilation::Compiling);
+                    progress.insert(key.clone(), Compilation::Compiling);
                     drop(progress);
                     let progress = self.progress.clone();
                     let code = code.as_ref().to_vec();
                     let dir = self.dir.clone();
+                    let engine = self.engine;
                     std::thread::spawn(move || {
                         tracing::info!("Compiling WASM {}.", hash.to_string());
 
                         let (_module, _store) =
                             match wasm::run::prepare_wasm_code(code) {
-                                Ok(code) => match compile(code) {
+                                Ok(code) => match compile(code, engine) {
                                     Ok((module, store)) => {
                                         let mut progress =
                                             progress.write().unwrap();
-                                        progress
-                                            .insert(hash, Compilation::Done);
+                                        progress.insert(
+                                            key.clone(),
+                                            Compilation::Done,
+                                        );
                                         tracing::info!(
                                             "Finished compiling WASM {hash}."
                                         );
@@ -418,7 +562,9 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                                                 N::name()
                                             )
                                         }
-                                        file_write_module(&dir, &module, &hash);
+                                        file_write_module(
+                                            &dir, &module, &key, engine,
+                                        );
                                         (module, store)
                                     }
                                     Err(err) => {
@@ -429,7 +575,7 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                                             hash.to_string(),
                                             err
                                         );
-                                        progress.remove(&hash);
+                                        progress.remove(&key);
                                         return Err(err);
                                     }
                                 },
@@ -441,7 +587,7 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
                                         hash.to_string(),
                                         err
                                     );
-                                    progress.remove(&hash);
+                                    progress.remove(&key);
                                     return Err(err);
                                 }
                             };
@@ -460,6 +606,9 @@ impl<N: CacheName, A: WasmCacheAccess> Cache<N, A> {
             dir: self.dir.clone(),
             progress: self.progress.clone(),
             in_memory: self.in_memory.clone(),
+            engine: self.engine,
+            compiler_id: self.compiler_id.clone(),
+            compile_timeout: self.compile_timeout,
             name: Default::default(),
             access: Default::default(),
         }
@@ -472,39 +621,61 @@ fn hash_of_code(code: impl AsRef<[u8]>) -> Hash {
 
 fn compile(
     code: impl AsRef<[u8]>,
+    engine: WasmEngine,
 ) -> Result<(Module, Store), wasm::run::Error> {
     // There's an issue with dylib compiler on mac in linker and on linux
-    // with the dylib's store loading the dylib from a file, so we're caching a
-    // module serialized to bytes instead for now.
-    universal::compile(code).map_err(wasm::run::Error::CompileError)
+    // with the dylib's store loading the dylib from a file, so by default
+    // we're caching a module serialized to bytes instead.
+    match engine {
+        WasmEngine::Universal => universal::compile(code),
+        #[cfg(feature = "wasm-engine-dylib")]
+        WasmEngine::Dylib => dylib::compile(code),
+    }
+    .map_err(wasm::run::Error::CompileError)
 }
 
-fn file_ext() -> &'static str {
+fn file_ext(engine: WasmEngine) -> &'static str {
     // This has to be using the file_ext matching the compilation method in the
     // `fn compile`
-    universal::FILE_EXT
+    match engine {
+        WasmEngine::Universal => universal::FILE_EXT,
+        #[cfg(feature = "wasm-engine-dylib")]
+        WasmEngine::Dylib => dylib::FILE_EXT,
+    }
 }
 
-pub(crate) fn store() -> Store {
+pub(crate) fn store(engine: WasmEngine) -> Store {
     // This has to be using the store matching the compilation method in the
     // `fn compile`
-    universal::store()
+    match engine {
+        WasmEngine::Universal => universal::store(),
+        #[cfg(feature = "wasm-engine-dylib")]
+        WasmEngine::Dylib => dylib::store(),
+    }
 }
 
-fn file_write_module(dir: impl AsRef<Path>, module: &Module, hash: &Hash) {
+fn file_write_module(
+    dir: impl AsRef<Path>,
+    module: &Module,
+    key: &CompileCacheKey,
+    engine: WasmEngine,
+) {
     use wasmer_cache::Cache;
-    let mut fs_cache = fs_cache(dir, hash);
-    fs_cache.store(CacheHash::new(hash.0), module).unwrap();
+    let digest = key.digest();
+    let mut fs_cache = fs_cache(dir, &digest, engine);
+    fs_cache.store(CacheHash::new(digest.0), module).unwrap();
 }
 
 fn file_load_module(
     dir: impl AsRef<Path>,
-    hash: &Hash,
+    key: &CompileCacheKey,
+    engine: WasmEngine,
 ) -> Result<(Module, Store), wasmer::DeserializeError> {
     use wasmer_cache::Cache;
-    let fs_cache = fs_cache(dir, hash);
-    let store = store();
-    let hash = CacheHash::new(hash.0);
+    let digest = key.digest();
+    let fs_cache = fs_cache(dir, &digest, engine);
+    let store = store(engine);
+    let hash = CacheHash::new(digest.0);
     let module = unsafe { fs_cache.load(&store, hash) };
     if let Err(err) = module.as_ref() {
         tracing::error!(
@@ -515,22 +686,29 @@ fn file_load_module(
     Ok((module?, store))
 }
 
-fn fs_cache(dir: impl AsRef<Path>, hash: &Hash) -> FileSystemCache {
-    let path = dir.as_ref().join(hash.to_string().to_lowercase());
+/// The file system cache of a module in a directory named by its key's digest
+fn fs_cache(
+    dir: impl AsRef<Path>,
+    digest: &Hash,
+    engine: WasmEngine,
+) -> FileSystemCache {
+    let path = dir.as_ref().join(digest.to_string().to_lowercase());
     let mut fs_cache = FileSystemCache::new(path).unwrap();
-    fs_cache.set_cache_extension(Some(file_ext()));
+    fs_cache.set_cache_extension(Some(file_ext(engine)));
     fs_cache
 }
 
-fn module_file_exists(dir: impl AsRef<Path>, hash: &Hash) -> bool {
-    let file =
-        dir.as_ref()
-            .join(hash.to_string().to_lowercase())
-            .join(format!(
-                "{}.{}",
-                hash.to_string().to_lowercase(),
-                file_ext()
-            ));
+fn module_file_exists(
+    dir: impl AsRef<Path>,
+    key: &CompileCacheKey,
+    engine: WasmEngine,
+) -> bool {
+    let digest = key.digest().to_string().to_lowercase();
+    let file = dir.as_ref().join(&digest).join(format!(
+        "{}.{}",
+        digest,
+        file_ext(engine)
+    ));
     file.exists()
 }
 
@@ -559,6 +737,7 @@ mod universal {
 }
 
 /// A dynamic library engine compilation.
+#[cfg(feature = "wasm-engine-dylib")]
 mod dylib {
     use super::*;
 
@@ -658,20 +837,24 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_read_storage_key.hash),
+                    in_memory.peek(&cache.key(&tx_read_storage_key.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&tx_read_storage_key.hash),
+                    progress.get(&cache.key(&tx_read_storage_key.hash)),
                     Some(Compilation::Done),
                     "The progress must be updated"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_read_storage_key.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
             }
@@ -695,31 +878,39 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_no_op.hash),
+                    in_memory.peek(&cache.key(&tx_no_op.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&tx_no_op.hash),
+                    progress.get(&cache.key(&tx_no_op.hash)),
                     Some(Compilation::Done),
                     "The progress must be updated"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_no_op.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_read_storage_key.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
                 // But it should not be in-memory
                 assert_matches!(
-                    in_memory.peek(&tx_read_storage_key.hash),
+                    in_memory.peek(&cache.key(&tx_read_storage_key.hash)),
                     None,
                     "The module should have been popped from memory"
                 );
@@ -745,31 +936,39 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_read_storage_key.hash),
+                    in_memory.peek(&cache.key(&tx_read_storage_key.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&tx_read_storage_key.hash),
+                    progress.get(&cache.key(&tx_read_storage_key.hash)),
                     Some(Compilation::Done),
                     "The progress must be updated"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_read_storage_key.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_no_op.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
                 // But it should not be in-memory
                 assert_matches!(
-                    in_memory.peek(&tx_no_op.hash),
+                    in_memory.peek(&cache.key(&tx_no_op.hash)),
                     None,
                     "The module should have been popped from memory"
                 );
@@ -786,31 +985,39 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_read_storage_key.hash),
+                    in_memory.peek(&cache.key(&tx_read_storage_key.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&tx_read_storage_key.hash),
+                    progress.get(&cache.key(&tx_read_storage_key.hash)),
                     Some(Compilation::Done),
                     "The progress must be updated"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &tx_read_storage_key.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_read_storage_key.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &tx_no_op.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&tx_no_op.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
                 // But it should not be in-memory
                 assert_matches!(
-                    in_memory.peek(&tx_no_op.hash),
+                    in_memory.peek(&cache.key(&tx_no_op.hash)),
                     None,
                     "The module should have been popped from memory"
                 );
@@ -837,14 +1044,14 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_no_op.hash),
+                    in_memory.peek(&cache.key(&tx_no_op.hash)),
                     None,
                     "The module should not be added back to in-memory cache"
                 );
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&tx_read_storage_key.hash),
+                    in_memory.peek(&cache.key(&tx_read_storage_key.hash)),
                     Some(_),
                     "The previous module must still be in memory"
                 );
@@ -867,20 +1074,72 @@ mod test {
 
         let in_memory = cache.in_memory.read().unwrap();
         assert_matches!(
-            in_memory.peek(&hash),
+            in_memory.peek(&cache.key(&hash)),
             None,
             "There should be no entry for this hash in memory"
         );
 
         let progress = cache.progress.read().unwrap();
-        assert_matches!(progress.get(&hash), None, "Any progress is removed");
+        assert_matches!(
+            progress.get(&cache.key(&hash)),
+            None,
+            "Any progress is removed"
+        );
 
         assert!(
-            !module_file_exists(&cache.dir, &hash),
+            !module_file_exists(&cache.dir, &cache.key(&hash), cache.engine),
             "The file must not be written"
         );
     }
 
+    #[test]
+    fn test_fetch_or_compile_timeout() {
+        // A large module with many functions, which takes a while to compile
+        let funcs = (0..10_000)
+            .map(|i| {
+                format!(
+                    "(func $f{i} (param i64) (result i64)
+                        local.get 0
+                        i64.const {i}
+                        i64.mul
+                        i64.const 1
+                        i64.add)"
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        let large_wasm = wat::parse_str(format!(
+            r#"(module
+                (memory $memory 1)
+                (export "memory" (memory $memory))
+                {funcs})"#
+        ))
+        .unwrap();
+        let hash = hash_of_code(&large_wasm);
+        let (cache, _tmp_dir) = testing::cache::<TestCache>();
+        let mut cache = cache.with_compile_timeout(Duration::from_millis(1));
+
+        // Try to compile it
+        let error = cache
+            .compile_or_fetch(&large_wasm)
+            .expect_err("Compilation should time out");
+        assert_matches!(error, wasm::run::Error::CompileTimeout(_));
+
+        let progress = cache.progress.read().unwrap();
+        assert_matches!(
+            progress.get(&cache.key(&hash)),
+            None,
+            "Any progress is removed"
+        );
+        drop(progress);
+
+        // A module compiles fine within a sufficient budget
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        let mut cache = cache.with_compile_timeout(Duration::from_secs(60));
+        let fetched = cache.compile_or_fetch(&tx_no_op.code).unwrap();
+        assert_matches!(fetched, Some(_), "The code should be compiled");
+    }
+
     #[test]
     fn test_pre_compile_valid_wasm() {
         // Load some WASMs and find their hashes and in-memory size
@@ -904,7 +1163,7 @@ mod test {
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&vp_always_true.hash),
+                    progress.get(&cache.key(&vp_always_true.hash)),
                     Some(Compilation::Done | Compilation::Compiling),
                     "The progress must be updated"
                 );
@@ -921,20 +1180,24 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&vp_always_true.hash),
+                    in_memory.peek(&cache.key(&vp_always_true.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&vp_always_true.hash),
+                    progress.get(&cache.key(&vp_always_true.hash)),
                     Some(Compilation::Done),
                     "The progress must be updated"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &vp_always_true.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&vp_always_true.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
             }
@@ -947,7 +1210,7 @@ mod test {
 
                 let progress = cache.progress.read().unwrap();
                 assert_matches!(
-                    progress.get(&vp_eval.hash),
+                    progress.get(&cache.key(&vp_eval.hash)),
                     Some(Compilation::Done | Compilation::Compiling),
                     "The progress must be updated"
                 );
@@ -964,24 +1227,32 @@ mod test {
 
                 let in_memory = cache.in_memory.read().unwrap();
                 assert_matches!(
-                    in_memory.peek(&vp_eval.hash),
+                    in_memory.peek(&cache.key(&vp_eval.hash)),
                     Some(_),
                     "The module must be in memory"
                 );
 
                 assert!(
-                    module_file_exists(&cache.dir, &vp_eval.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&vp_eval.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
 
                 // The previous module's file should still exist
                 assert!(
-                    module_file_exists(&cache.dir, &vp_always_true.hash),
+                    module_file_exists(
+                        &cache.dir,
+                        &cache.key(&vp_always_true.hash),
+                        cache.engine
+                    ),
                     "The file must be written"
                 );
                 // But it should not be in-memory
                 assert_matches!(
-                    in_memory.peek(&vp_always_true.hash),
+                    in_memory.peek(&cache.key(&vp_always_true.hash)),
                     None,
                     "The module should have been popped from memory"
                 );
@@ -1001,7 +1272,7 @@ mod test {
             cache.pre_compile(&invalid_wasm);
             let progress = cache.progress.read().unwrap();
             assert_matches!(
-                progress.get(&hash),
+                progress.get(&cache.key(&hash)),
                 Some(Compilation::Done | Compilation::Compiling) | None,
                 "The progress must be updated"
             );
@@ -1018,25 +1289,61 @@ mod test {
 
             let in_memory = cache.in_memory.read().unwrap();
             assert_matches!(
-                in_memory.peek(&hash),
+                in_memory.peek(&cache.key(&hash)),
                 None,
                 "There should be no entry for this hash in memory"
             );
 
             let progress = cache.progress.read().unwrap();
             assert_matches!(
-                progress.get(&hash),
+                progress.get(&cache.key(&hash)),
                 None,
                 "Any progress is removed"
             );
 
             assert!(
-                !module_file_exists(&cache.dir, &hash),
+                !module_file_exists(
+                    &cache.dir,
+                    &cache.key(&hash),
+                    cache.engine
+                ),
                 "The file must not be written"
             );
         }
     }
 
+    #[test]
+    fn test_compiler_id_change_misses_cache() {
+        let tx_no_op = load_wasm(TestWasms::TxNoOp.path());
+        let (mut cache, _tmp_dir) = cache(tx_no_op.size * 2);
+
+        // Compile the code with the current compiler
+        let fetched = cache.compile_or_fetch(&tx_no_op.code).unwrap();
+        assert_matches!(fetched, Some(_), "The code should be compiled");
+        let old_key = cache.key(&tx_no_op.hash);
+        assert!(module_file_exists(&cache.dir, &old_key, cache.engine));
+
+        // Simulate a compiler upgrade
+        cache.compiler_id = "singlepass-wasmer-upgraded".to_string();
+        let new_key = cache.key(&tx_no_op.hash);
+        assert_ne!(old_key, new_key);
+        assert_eq!(old_key.code_hash, new_key.code_hash);
+
+        // The identical code is not found in the memory or file cache
+        let fetched = cache.fetch(&tx_no_op.hash).unwrap();
+        assert_matches!(
+            fetched,
+            None,
+            "The module compiled by a different compiler must not be used"
+        );
+        assert!(!module_file_exists(&cache.dir, &new_key, cache.engine));
+
+        // Compiling it again stores it under the new key
+        let fetched = cache.compile_or_fetch(&tx_no_op.code).unwrap();
+        assert_matches!(fetched, Some(_), "The code should be compiled");
+        assert!(module_file_exists(&cache.dir, &new_key, cache.engine));
+    }
+
     /// Get the WASM code bytes, its hash and find the compiled module's size
     fn load_wasm(file: impl AsRef<Path>) -> WasmWithMeta {
         // When `WeightScale` calls `loupe::size_of_val` in the cache, for some
@@ -51,9 +51,9 @@ pub fn tx_imports<DB, H, CA>(
     env: TxVmEnv<'static, WasmMemory, DB, H, CA>,
 ) -> ImportObject
 where
-    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
-    H: StorageHasher,
-    CA: WasmCacheAccess,
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
 {
     wasmer::imports! {
         // default namespace
@@ -68,7 +68,10 @@ where
             "namada_tx_has_key" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_has_key),
             "namada_tx_write" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_write),
             "namada_tx_write_temp" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_write_temp),
+            "namada_tx_increment" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_increment),
+            "namada_tx_transfer" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_transfer),
             "namada_tx_delete" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_delete),
+            "namada_tx_delete_prefix" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_delete_prefix),
             "namada_tx_iter_prefix" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_iter_prefix),
             "namada_tx_iter_next" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_iter_next),
             "namada_tx_insert_verifier" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_insert_verifier),
@@ -76,14 +79,20 @@ where
             "namada_tx_init_account" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_init_account),
             "namada_tx_emit_ibc_event" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_emit_ibc_event),
             "namada_tx_get_ibc_events" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_ibc_events),
+            "namada_tx_ibc_send_packet" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_ibc_send_packet),
+            "namada_tx_preview_vp" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_preview_vp),
             "namada_tx_get_chain_id" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_chain_id),
             "namada_tx_get_tx_index" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_tx_index),
             "namada_tx_get_block_height" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_block_height),
             "namada_tx_get_block_header" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_block_header),
+            "namada_tx_get_block_time" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_block_time),
             "namada_tx_get_block_hash" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_block_hash),
             "namada_tx_get_block_epoch" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_block_epoch),
             "namada_tx_get_pred_epochs" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_pred_epochs),
             "namada_tx_get_native_token" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_native_token),
+            "namada_tx_get_fee_payer" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_fee_payer),
+            "namada_tx_get_chain_param" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_get_chain_param),
+            "namada_tx_make_key" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_make_key),
             "namada_tx_log_string" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_log_string),
             "namada_tx_ibc_execute" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_ibc_execute),
             "namada_tx_set_commitment_sentinel" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_set_commitment_sentinel),
@@ -115,20 +124,33 @@ where
             // Whitelisted gas exposed function, we need two different functions just because of colliding names in the vm_host_env macro to generate implementations
             "namada_vp_charge_gas" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_charge_gas),
             "namada_vp_read_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_pre),
+            "namada_vp_read_with_proof" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_with_proof),
+            "namada_vp_verify_merkle_proof" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_verify_merkle_proof),
+            "namada_vp_get_changes" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_changes),
             "namada_vp_read_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_post),
             "namada_vp_read_temp" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_temp),
+            "namada_vp_read_amount_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_amount_pre),
+            "namada_vp_read_amount_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_amount_post),
+            "namada_vp_read_address_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_address_pre),
+            "namada_vp_read_address_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_read_address_post),
             "namada_vp_result_buffer" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_result_buffer),
             "namada_vp_has_key_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_has_key_pre),
             "namada_vp_has_key_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_has_key_post),
             "namada_vp_iter_prefix_pre" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_prefix_pre),
             "namada_vp_iter_prefix_post" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_prefix_pre),
+            "namada_vp_iter_prefix_self" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_prefix_self),
             "namada_vp_iter_next" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_next),
+            "namada_vp_iter_continuation_token" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_continuation_token),
+            "namada_vp_iter_resume" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_iter_resume),
             "namada_vp_get_chain_id" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_chain_id),
             "namada_vp_get_tx_index" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_tx_index),
             "namada_vp_get_block_height" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_block_height),
             "namada_vp_get_block_header" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_block_header),
+            "namada_vp_get_block_time" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_block_time),
+            "namada_vp_has_verifier" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_has_verifier),
             "namada_vp_get_block_hash" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_block_hash),
             "namada_vp_get_tx_code_hash" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_tx_code_hash),
+            "namada_vp_get_tx_hash" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_tx_hash),
             "namada_vp_get_block_epoch" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_block_epoch),
             "namada_vp_get_pred_epochs" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_pred_epochs),
             "namada_vp_get_ibc_events" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_ibc_events),
@@ -136,6 +158,7 @@ where
             "namada_vp_eval" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_eval),
             "namada_vp_get_native_token" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_get_native_token),
             "namada_vp_log_string" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_log_string),
+            "namada_vp_reject" => Function::new_native_with_env(wasm_store, env.clone(), host_env::vp_reject),
         },
     }
 }
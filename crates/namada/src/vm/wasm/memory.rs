@@ -54,14 +54,53 @@ pub const VP_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
 
 /// Prepare memory for instantiating a transaction module
 pub fn prepare_tx_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+    prepare_tx_memory_with_hint(store, None)
+}
+
+/// Prepare memory for instantiating a transaction module with the initial
+/// pages given by [`tx_memory_init_pages`] for the given hint
+pub fn prepare_tx_memory_with_hint(
+    store: &wasmer::Store,
+    pages_hint: Option<u32>,
+) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
-        TX_MEMORY_INIT_PAGES,
+        tx_memory_init_pages(pages_hint),
         Some(TX_MEMORY_MAX_PAGES),
         false,
     );
     Memory::new(store, mem_type).map_err(Error::InitMemoryError)
 }
 
+/// Get the number of initial pages in tx memory for the given hint. The hint
+/// can only increase the default [`TX_MEMORY_INIT_PAGES`] and it's bounded by
+/// [`TX_MEMORY_MAX_PAGES`].
+pub fn tx_memory_init_pages(pages_hint: Option<u32>) -> u32 {
+    pages_hint.map_or(TX_MEMORY_INIT_PAGES, |pages| {
+        pages.clamp(TX_MEMORY_INIT_PAGES, TX_MEMORY_MAX_PAGES)
+    })
+}
+
+/// Grow the given memory to at least the given number of pages, bounded by
+/// the maximum size of the memory
+pub fn reserve_pages(memory: &Memory, pages: u32) -> Result<()> {
+    let pages = memory
+        .ty()
+        .maximum
+        .map_or(pages, |max_pages| pages.min(max_pages.0));
+    let cur_pages = memory.size().0;
+    if cur_pages < pages {
+        tracing::debug!(
+            cur_pages,
+            pages,
+            "Pre-allocating wasm memory from a hint"
+        );
+        memory
+            .grow(pages - cur_pages)
+            .map_err(Error::MemoryOutOfBounds)?;
+    }
+    Ok(())
+}
+
 /// Prepare memory for instantiating a validity predicate module
 pub fn prepare_vp_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
@@ -5,6 +5,6 @@ pub mod host_env;
 pub mod memory;
 pub mod run;
 
-pub use compilation_cache::common::{Cache, CacheName};
+pub use compilation_cache::common::{Cache, CacheName, WasmEngine};
 pub use compilation_cache::tx::TxCache;
 pub use compilation_cache::vp::VpCache;
@@ -1,9 +1,16 @@
 //! Wasm runners
 
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
 use std::collections::BTreeSet;
 use std::marker::PhantomData;
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use borsh::BorshDeserialize;
+use clru::{CLruCache, CLruCacheConfig, WeightScale};
 use namada_core::types::validity_predicate::VpSentinel;
 use namada_gas::{GasMetering, TxGasMeter, WASM_MEMORY_PAGE_GAS};
 use namada_state::write_log::StorageModification;
@@ -22,18 +29,25 @@ use crate::types::address::Address;
 use crate::types::hash::{Error as TxHashError, Hash};
 use crate::types::internal::HostEnvResult;
 use crate::types::storage::{Key, TxIndex};
-use crate::vm::host_env::{TxVmEnv, VpCtx, VpEvaluator, VpVmEnv};
-use crate::vm::prefix_iter::PrefixIterators;
+use crate::vm::host_env::log_level::with_log_level;
+pub use crate::vm::host_env::log_level::LevelFilter;
+pub use crate::vm::host_env::trace::VpHostCall;
+use crate::vm::host_env::{
+    trace, TxHostState, TxVmEnv, VpCtx, VpEvaluator, VpVmEnv,
+};
+use crate::vm::prefix_iter::{self, PrefixIterators};
 use crate::vm::types::VpInput;
 use crate::vm::wasm::host_env::{tx_imports, vp_imports};
 use crate::vm::wasm::{memory, Cache, CacheName, VpCache};
 use crate::vm::{
-    validate_untrusted_wasm, WasmCacheAccess, WasmValidationError,
+    validate_untrusted_wasm, MutHostRef, WasmCacheAccess, WasmValidationError,
 };
 
 const TX_ENTRYPOINT: &str = "_apply_tx";
 const VP_ENTRYPOINT: &str = "_validate_tx";
 const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
+/// The maximum total size of the prepared wasm code kept in memory
+const PREPARED_CODE_CACHE_MAX_BYTES: usize = 64 * 1024 * 1024;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -83,13 +97,193 @@ pub enum Error {
     ConversionError(String),
     #[error("Invalid transaction signature")]
     InvalidTxSignature,
+    #[error("Unexpected wasm memory layout: {0}")]
+    UnexpectedMemoryLayout(String),
+    #[error("Invalid VP return value {0}, expected 0 or 1")]
+    InvalidVpReturnValue(u64),
+    #[error("Wasm compilation exceeded the time budget of {0:?}")]
+    CompileTimeout(std::time::Duration),
+    #[error("Tx execution diverged between the storage backends: {0}")]
+    Divergence(String),
+    #[error("Wasm instrumentation stripped the required export {0:?}")]
+    InstrumentationStrippedExport(String),
+    #[error("The tx runner is in maintenance mode")]
+    MaintenanceMode,
+}
+
+/// Stable numeric codes of the [`Error`]s, for consumers that need a
+/// machine-readable error, e.g. in RPC responses or over FFI
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u32)]
+pub enum ErrorCode {
+    // WARN: These codes shouldn't be changed between version!
+    // =========================================================================
+    /// Missing tx section
+    MissingSection = 1,
+    /// Wasm memory error
+    MemoryError = 2,
+    /// Unable to inject stack limiter
+    StackLimiterInjection = 3,
+    /// Wasm deserialization error
+    DeserializationError = 4,
+    /// Wasm serialization error
+    SerializationError = 5,
+    /// Unable to inject gas meter
+    GasMeterInjection = 6,
+    /// Wasm compilation error
+    CompileError = 7,
+    /// Missing wasm memory export
+    MissingModuleMemory = 8,
+    /// Missing wasm entrypoint
+    MissingModuleEntrypoint = 9,
+    /// Failed running wasm
+    RuntimeError = 10,
+    /// Failed instantiating wasm module
+    InstantiationError = 11,
+    /// Unexpected module entrypoint interface
+    UnexpectedModuleEntrypointInterface = 12,
+    /// Wasm validation error
+    ValidationError = 13,
+    /// Wasm code hash error
+    CodeHash = 14,
+    /// Unable to load wasm code
+    LoadWasmCode = 15,
+    /// Unable to find compiled wasm code
+    NoCompiledWasmCode = 16,
+    /// Gas error
+    GasError = 17,
+    /// Failed type conversion
+    ConversionError = 18,
+    /// Invalid transaction signature
+    InvalidTxSignature = 19,
+    /// Unexpected wasm memory layout
+    UnexpectedMemoryLayout = 20,
+    /// Invalid VP return value
+    InvalidVpReturnValue = 21,
+    /// Wasm compilation exceeded the time budget
+    CompileTimeout = 22,
+    /// Tx execution diverged between the storage backends
+    Divergence = 23,
+    /// Wasm instrumentation stripped a required export
+    InstrumentationStrippedExport = 24,
+    /// The tx runner is in maintenance mode
+    MaintenanceMode = 25,
+    // =========================================================================
+    // WARN: These codes shouldn't be changed between version!
+}
+
+impl ErrorCode {
+    /// Convert to `u32`.
+    pub fn to_u32(self) -> u32 {
+        self as u32
+    }
+}
+
+impl Error {
+    /// Get the stable numeric code of this error
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            Error::MissingSection(_) => ErrorCode::MissingSection,
+            Error::MemoryError(_) => ErrorCode::MemoryError,
+            Error::StackLimiterInjection => ErrorCode::StackLimiterInjection,
+            Error::DeserializationError(_) => ErrorCode::DeserializationError,
+            Error::SerializationError(_) => ErrorCode::SerializationError,
+            Error::GasMeterInjection => ErrorCode::GasMeterInjection,
+            Error::CompileError(_) => ErrorCode::CompileError,
+            Error::MissingModuleMemory(_) => ErrorCode::MissingModuleMemory,
+            Error::MissingModuleEntrypoint(_) => {
+                ErrorCode::MissingModuleEntrypoint
+            }
+            Error::RuntimeError(_) => ErrorCode::RuntimeError,
+            Error::InstantiationError(_) => ErrorCode::InstantiationError,
+            Error::UnexpectedModuleEntrypointInterface { .. } => {
+                ErrorCode::UnexpectedModuleEntrypointInterface
+            }
+            Error::ValidationError(_) => ErrorCode::ValidationError,
+            Error::CodeHash(_) => ErrorCode::CodeHash,
+            Error::LoadWasmCode(_) => ErrorCode::LoadWasmCode,
+            Error::NoCompiledWasmCode => ErrorCode::NoCompiledWasmCode,
+            Error::GasError(_) => ErrorCode::GasError,
+            Error::ConversionError(_) => ErrorCode::ConversionError,
+            Error::InvalidTxSignature => ErrorCode::InvalidTxSignature,
+            Error::UnexpectedMemoryLayout(_) => {
+                ErrorCode::UnexpectedMemoryLayout
+            }
+            Error::InvalidVpReturnValue(_) => ErrorCode::InvalidVpReturnValue,
+            Error::CompileTimeout(_) => ErrorCode::CompileTimeout,
+            Error::Divergence(_) => ErrorCode::Divergence,
+            Error::InstrumentationStrippedExport(_) => {
+                ErrorCode::InstrumentationStrippedExport
+            }
+            Error::MaintenanceMode => ErrorCode::MaintenanceMode,
+        }
+    }
 }
 
 /// Result for functions that may fail
 pub type Result<T> = std::result::Result<T, Error>;
 
+/// The time spent in the phases of a wasm run
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Timings {
+    /// The time spent compiling the wasm module, or fetching it from the
+    /// compilation cache
+    pub compile: Duration,
+    /// The time spent instantiating the wasm module
+    pub instantiate: Duration,
+    /// The time spent executing the wasm module's entrypoint
+    pub execute: Duration,
+}
+
+thread_local! {
+    static TIMINGS: Cell<Option<Timings>> = Cell::new(None);
+}
+
+/// Run the given function while recording the timings of the wasm runs on
+/// the current thread. The modules of the VPs evaluated from another wasm
+/// module add to the compilation and instantiation times, while their
+/// execution is counted in the execution time of the outer module.
+fn with_timings<T>(f: impl FnOnce() -> T) -> (T, Timings) {
+    struct RestoreTimings(Option<Timings>);
+    impl Drop for RestoreTimings {
+        fn drop(&mut self) {
+            TIMINGS.with(|cell| cell.set(self.0));
+        }
+    }
+
+    let _restore = RestoreTimings(
+        TIMINGS.with(|cell| cell.replace(Some(Timings::default()))),
+    );
+    let result = f();
+    let timings = TIMINGS.with(|cell| cell.get()).unwrap_or_default();
+    (result, timings)
+}
+
+/// Run the given function and record its duration in the timings with the
+/// given `record` function, if the recording is on.
+fn timed<T>(
+    record: impl FnOnce(&mut Timings, Duration),
+    f: impl FnOnce() -> T,
+) -> T {
+    let start = Instant::now();
+    let result = f();
+    let elapsed = start.elapsed();
+    TIMINGS.with(|cell| {
+        if let Some(mut timings) = cell.get() {
+            record(&mut timings, elapsed);
+            cell.set(Some(timings));
+        }
+    });
+    result
+}
+
 /// Execute a transaction code. Returns the set verifiers addresses requested by
 /// the transaction.
+///
+/// The gas is charged directly into the given `gas_meter` as the execution
+/// progresses, so when the execution fails (e.g. the wasm code traps), the
+/// meter still holds the gas consumed up to the failure point and the caller
+/// can charge the transaction for the work it did.
 #[allow(clippy::too_many_arguments)]
 pub fn tx<DB, H, CA>(
     storage: &State<DB, H>,
@@ -97,8 +291,43 @@ pub fn tx<DB, H, CA>(
     gas_meter: &mut TxGasMeter,
     tx_index: &TxIndex,
     tx: &Tx,
+    fee_payer: Option<&Address>,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+) -> Result<BTreeSet<Address>>
+where
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    tx_with_memory_hint(
+        storage,
+        write_log,
+        gas_meter,
+        tx_index,
+        tx,
+        fee_payer,
+        vp_wasm_cache,
+        tx_wasm_cache,
+        None,
+    )
+}
+
+/// Execute a transaction code like [`tx`], with its memory pre-allocated to
+/// the given number of pages, if any. The hint is bounded by
+/// [`memory::TX_MEMORY_MAX_PAGES`]. A transaction that is known to need a lot
+/// of memory can be given a hint to avoid growing its memory repeatedly.
+#[allow(clippy::too_many_arguments)]
+pub fn tx_with_memory_hint<DB, H, CA>(
+    storage: &State<DB, H>,
+    write_log: &mut WriteLog,
+    gas_meter: &mut TxGasMeter,
+    tx_index: &TxIndex,
+    tx: &Tx,
+    fee_payer: Option<&Address>,
     vp_wasm_cache: &mut VpCache<CA>,
     tx_wasm_cache: &mut TxCache<CA>,
+    memory_pages_hint: Option<u32>,
 ) -> Result<BTreeSet<Address>>
 where
     DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
@@ -145,17 +374,26 @@ where
         }
     }
 
-    let (module, store) = fetch_or_compile(
-        tx_wasm_cache,
-        &tx_code.code,
-        write_log,
-        storage,
-        gas_meter,
+    let (module, store) = timed(
+        |timings, elapsed| timings.compile += elapsed,
+        || {
+            fetch_or_compile(
+                tx_wasm_cache,
+                &tx_code.code,
+                write_log,
+                storage,
+                gas_meter,
+            )
+        },
     )?;
 
-    let mut iterators: PrefixIterators<'_, DB> = PrefixIterators::default();
+    let mut iterators: PrefixIterators<'_, DB> =
+        PrefixIterators::new(prefix_iter::max_open_iterators());
     let mut verifiers = BTreeSet::new();
     let mut result_buffer: Option<Vec<u8>> = None;
+    let mut host_state = TxHostState::default();
+
+    let fee_payer = fee_payer.cloned();
 
     let mut sentinel = TxSentinel::default();
     let env = TxVmEnv::new(
@@ -167,19 +405,25 @@ where
         &mut sentinel,
         tx,
         tx_index,
+        &fee_payer,
         &mut verifiers,
         &mut result_buffer,
+        &mut host_state,
         vp_wasm_cache,
         tx_wasm_cache,
     );
 
     let initial_memory =
-        memory::prepare_tx_memory(&store).map_err(Error::MemoryError)?;
+        memory::prepare_tx_memory_with_hint(&store, memory_pages_hint)
+            .map_err(Error::MemoryError)?;
     let imports = tx_imports(&store, initial_memory, env);
 
     // Instantiate the wasm module
-    let instance = wasmer::Instance::new(&module, &imports)
-        .map_err(|e| Error::InstantiationError(Box::new(e)))?;
+    let instance = timed(
+        |timings, elapsed| timings.instantiate += elapsed,
+        || wasmer::Instance::new(&module, &imports),
+    )
+    .map_err(|e| Error::InstantiationError(Box::new(e)))?;
 
     // We need to write the inputs in the memory exported from the wasm
     // module
@@ -187,6 +431,13 @@ where
         .exports
         .get_memory("memory")
         .map_err(Error::MissingModuleMemory)?;
+    if memory_pages_hint.is_some() {
+        memory::reserve_pages(
+            memory,
+            memory::tx_memory_init_pages(memory_pages_hint),
+        )
+        .map_err(Error::MemoryError)?;
+    }
     let memory::TxCallInput {
         tx_data_ptr,
         tx_data_len,
@@ -201,7 +452,11 @@ where
             entrypoint: TX_ENTRYPOINT,
             error,
         })?;
-    apply_tx.call(tx_data_ptr, tx_data_len).map_err(|err| {
+    timed(
+        |timings, elapsed| timings.execute = elapsed,
+        || apply_tx.call(tx_data_ptr, tx_data_len),
+    )
+    .map_err(|err| {
         tracing::debug!("Tx WASM failed with {}", err);
         match sentinel {
             TxSentinel::None => Error::RuntimeError(err),
@@ -215,6 +470,210 @@ where
     Ok(verifiers)
 }
 
+/// Execute a transaction code like [`tx`], while measuring the time spent
+/// compiling, instantiating and executing the wasm module. Returns the set
+/// verifiers addresses requested by the transaction, together with the
+/// [`Timings`] of the run.
+#[allow(clippy::too_many_arguments)]
+pub fn tx_with_timings<DB, H, CA>(
+    storage: &State<DB, H>,
+    write_log: &mut WriteLog,
+    gas_meter: &mut TxGasMeter,
+    tx_index: &TxIndex,
+    tx: &Tx,
+    fee_payer: Option<&Address>,
+    vp_wasm_cache: &mut VpCache<CA>,
+    tx_wasm_cache: &mut TxCache<CA>,
+) -> Result<(BTreeSet<Address>, Timings)>
+where
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    let (result, timings) = with_timings(|| {
+        self::tx(
+            storage,
+            write_log,
+            gas_meter,
+            tx_index,
+            tx,
+            fee_payer,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        )
+    });
+    result.map(|verifiers| (verifiers, timings))
+}
+
+/// The state that is moved to the worker thread of an [`AsyncTxRunner`] to
+/// execute a transaction and handed back once the execution is done.
+#[derive(Debug)]
+pub struct TxRunState<DB, H>
+where
+    DB: namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// The storage
+    pub storage: State<DB, H>,
+    /// The write log
+    pub write_log: WriteLog,
+    /// The gas meter of the transaction
+    pub gas_meter: TxGasMeter,
+    /// The address paying the fees of the transaction, if any
+    pub fee_payer: Option<Address>,
+}
+
+/// The switch of the maintenance mode, in which the execution of any
+/// transaction is rejected with [`Error::MaintenanceMode`], e.g. during
+/// upgrades. The switch is shared between its clones, so that it can be
+/// toggled at runtime for all the runners holding it.
+#[derive(Debug, Clone, Default)]
+pub struct MaintenanceMode(Arc<AtomicBool>);
+
+impl MaintenanceMode {
+    /// Create a new switch of the maintenance mode
+    pub fn new(enabled: bool) -> Self {
+        Self(Arc::new(AtomicBool::new(enabled)))
+    }
+
+    /// Enable or disable the maintenance mode
+    pub fn set(&self, enabled: bool) {
+        self.0.store(enabled, Ordering::SeqCst)
+    }
+
+    /// Check if the maintenance mode is enabled
+    pub fn is_enabled(&self) -> bool {
+        self.0.load(Ordering::SeqCst)
+    }
+
+    /// Return [`Error::MaintenanceMode`] if the maintenance mode is enabled
+    pub fn check(&self) -> Result<()> {
+        if self.is_enabled() {
+            Err(Error::MaintenanceMode)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// An adapter to execute transactions from an async context. The execution is
+/// offloaded to the blocking thread pool of the tokio runtime so that it
+/// doesn't stall the async executor. The wasm instance is created, used and
+/// dropped on the worker thread.
+///
+/// The runner doesn't hold any wasm store - every run creates its own store
+/// together with the instance, while the compilation caches are synchronized
+/// internally. A single runner can therefore be shared by reference between
+/// multiple threads.
+#[derive(Debug, Clone)]
+pub struct AsyncTxRunner<CA>
+where
+    CA: WasmCacheAccess,
+{
+    vp_wasm_cache: VpCache<CA>,
+    tx_wasm_cache: TxCache<CA>,
+    log_level: Option<LevelFilter>,
+    max_open_iterators: usize,
+    maintenance: MaintenanceMode,
+}
+
+impl<CA> AsyncTxRunner<CA>
+where
+    CA: 'static + WasmCacheAccess + Send,
+{
+    /// Create a new async runner with the given compilation caches
+    pub fn new(vp_wasm_cache: VpCache<CA>, tx_wasm_cache: TxCache<CA>) -> Self {
+        Self {
+            vp_wasm_cache,
+            tx_wasm_cache,
+            log_level: None,
+            max_open_iterators: prefix_iter::DEFAULT_MAX_OPEN_ITERATORS,
+            maintenance: MaintenanceMode::default(),
+        }
+    }
+
+    /// Set the level that limits the host calls traces of the transactions
+    /// executed by this runner, instead of the process-wide level
+    pub fn with_log_level(mut self, log_level: LevelFilter) -> Self {
+        self.log_level = Some(log_level);
+        self
+    }
+
+    /// Set the maximum number of prefix iterators that each of the
+    /// transactions executed by this runner can open at the same time
+    pub fn with_max_open_iterators(mut self, max_open: usize) -> Self {
+        self.max_open_iterators = max_open;
+        self
+    }
+
+    /// Set a budget for the wall-clock time that the compilation of the
+    /// modules run by this runner may take. When it's exceeded, the execution
+    /// fails with [`Error::CompileTimeout`].
+    pub fn with_compile_timeout(mut self, timeout: Duration) -> Self {
+        self.vp_wasm_cache = self.vp_wasm_cache.with_compile_timeout(timeout);
+        self.tx_wasm_cache = self.tx_wasm_cache.with_compile_timeout(timeout);
+        self
+    }
+
+    /// Set the switch of the maintenance mode, in which the runner rejects the
+    /// execution of any transaction
+    pub fn with_maintenance(mut self, maintenance: MaintenanceMode) -> Self {
+        self.maintenance = maintenance;
+        self
+    }
+
+    /// Execute a transaction code on a blocking thread. Returns the state
+    /// handed back from the worker thread together with the set of verifiers
+    /// addresses requested by the transaction. In maintenance mode, the state
+    /// is handed back untouched together with [`Error::MaintenanceMode`].
+    pub async fn run<DB, H>(
+        &self,
+        mut state: TxRunState<DB, H>,
+        tx_index: TxIndex,
+        tx_data: Tx,
+    ) -> (TxRunState<DB, H>, Result<BTreeSet<Address>>)
+    where
+        DB: 'static
+            + namada_state::DB
+            + for<'iter> namada_state::DBIter<'iter>
+            + Send,
+        H: 'static + StorageHasher + Send,
+    {
+        if let Err(err) = self.maintenance.check() {
+            return (state, Err(err));
+        }
+        let mut vp_wasm_cache = self.vp_wasm_cache.clone();
+        let mut tx_wasm_cache = self.tx_wasm_cache.clone();
+        let log_level = self.log_level;
+        let max_open_iterators = self.max_open_iterators;
+        let worker = tokio::task::spawn_blocking(move || {
+            let result = with_log_level(log_level, || {
+                prefix_iter::with_max_open_iterators(max_open_iterators, || {
+                    tx(
+                        &state.storage,
+                        &mut state.write_log,
+                        &mut state.gas_meter,
+                        &tx_index,
+                        &tx_data,
+                        state.fee_payer.as_ref(),
+                        &mut vp_wasm_cache,
+                        &mut tx_wasm_cache,
+                    )
+                })
+            });
+            (state, result)
+        });
+        match worker.await {
+            Ok(output) => output,
+            // Propagate a panic from the worker thread
+            Err(err) if err.is_panic() => {
+                std::panic::resume_unwind(err.into_panic())
+            }
+            Err(err) => panic!("The tx worker thread was cancelled: {err}"),
+        }
+    }
+}
+
 /// Execute a validity predicate code. Returns whether the validity
 /// predicate accepted storage modifications performed by the transaction
 /// that triggered the execution.
@@ -229,23 +688,66 @@ pub fn vp<DB, H, CA>(
     gas_meter: &mut VpGasMeter,
     keys_changed: &BTreeSet<Key>,
     verifiers: &BTreeSet<Address>,
-    mut vp_wasm_cache: VpCache<CA>,
+    vp_wasm_cache: VpCache<CA>,
 ) -> Result<bool>
 where
     DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
     H: 'static + StorageHasher,
     CA: 'static + WasmCacheAccess,
 {
-    // Compile the wasm module
-    let (module, store) = fetch_or_compile(
-        &mut vp_wasm_cache,
-        &Commitment::Hash(vp_code_hash),
-        write_log,
+    vp_with_rejection_reason(
+        vp_code_hash,
+        tx,
+        tx_index,
+        address,
         storage,
+        write_log,
         gas_meter,
+        keys_changed,
+        verifiers,
+        vp_wasm_cache,
+    )
+    .map(|(accept, _reason)| accept)
+}
+
+/// Execute a validity predicate code like [`vp`]. Returns whether the
+/// validity predicate accepted the storage modifications, together with the
+/// reason given by the validity predicate if it explicitly rejected the
+/// transaction with `vp_reject`.
+#[allow(clippy::too_many_arguments)]
+pub fn vp_with_rejection_reason<DB, H, CA>(
+    vp_code_hash: Hash,
+    tx: &Tx,
+    tx_index: &TxIndex,
+    address: &Address,
+    storage: &State<DB, H>,
+    write_log: &WriteLog,
+    gas_meter: &mut VpGasMeter,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    mut vp_wasm_cache: VpCache<CA>,
+) -> Result<(bool, Option<String>)>
+where
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    // Compile the wasm module
+    let (module, store) = timed(
+        |timings, elapsed| timings.compile += elapsed,
+        || {
+            fetch_or_compile(
+                &mut vp_wasm_cache,
+                &Commitment::Hash(vp_code_hash),
+                write_log,
+                storage,
+                gas_meter,
+            )
+        },
     )?;
 
-    let mut iterators: PrefixIterators<'_, DB> = PrefixIterators::default();
+    let mut iterators: PrefixIterators<'_, DB> =
+        PrefixIterators::new(prefix_iter::max_open_iterators());
     let mut result_buffer: Option<Vec<u8>> = None;
     let eval_runner = VpEvalWasm {
         db: PhantomData,
@@ -301,12 +803,18 @@ where
                 }
                 Err(Error::InvalidTxSignature)
             } else {
-                Ok(accept)
+                Ok((accept, None))
             }
         }
         Err(err) => {
             if sentinel.is_out_of_gas() {
                 Err(Error::GasError(err.to_string()))
+            } else if sentinel.is_invalid_signature() {
+                Err(Error::InvalidTxSignature)
+            } else if let Some(reason) = sentinel.rejection_reason() {
+                // The VP aborted its execution to explicitly reject the tx,
+                // which is not an error
+                Ok((false, Some(reason.to_owned())))
             } else {
                 Err(err)
             }
@@ -314,6 +822,85 @@ where
     }
 }
 
+/// Execute a validity predicate code like [`vp`], while recording the host
+/// calls that it makes. Returns whether the validity predicate accepted the
+/// storage modifications, together with the ordered trace of its storage
+/// reads, iterations and other host calls, e.g. for an audit of what the
+/// validity predicate examined.
+#[allow(clippy::too_many_arguments)]
+pub fn vp_with_trace<DB, H, CA>(
+    vp_code_hash: Hash,
+    tx: &Tx,
+    tx_index: &TxIndex,
+    address: &Address,
+    storage: &State<DB, H>,
+    write_log: &WriteLog,
+    gas_meter: &mut VpGasMeter,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    vp_wasm_cache: VpCache<CA>,
+) -> Result<(bool, Vec<VpHostCall>)>
+where
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    let (result, trace) = trace::with_trace(|| {
+        vp(
+            vp_code_hash,
+            tx,
+            tx_index,
+            address,
+            storage,
+            write_log,
+            gas_meter,
+            keys_changed,
+            verifiers,
+            vp_wasm_cache,
+        )
+    });
+    result.map(|accept| (accept, trace))
+}
+
+/// Execute a validity predicate code like [`vp`], while measuring the time
+/// spent compiling, instantiating and executing the wasm module. Returns
+/// whether the validity predicate accepted the storage modifications,
+/// together with the [`Timings`] of the run.
+#[allow(clippy::too_many_arguments)]
+pub fn vp_with_timings<DB, H, CA>(
+    vp_code_hash: Hash,
+    tx: &Tx,
+    tx_index: &TxIndex,
+    address: &Address,
+    storage: &State<DB, H>,
+    write_log: &WriteLog,
+    gas_meter: &mut VpGasMeter,
+    keys_changed: &BTreeSet<Key>,
+    verifiers: &BTreeSet<Address>,
+    vp_wasm_cache: VpCache<CA>,
+) -> Result<(bool, Timings)>
+where
+    DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+    H: 'static + StorageHasher,
+    CA: 'static + WasmCacheAccess,
+{
+    let (result, timings) = with_timings(|| {
+        vp(
+            vp_code_hash,
+            tx,
+            tx_index,
+            address,
+            storage,
+            write_log,
+            gas_meter,
+            keys_changed,
+            verifiers,
+            vp_wasm_cache,
+        )
+    });
+    result.map(|accept| (accept, timings))
+}
+
 #[allow(clippy::too_many_arguments)]
 fn run_vp(
     module: wasmer::Module,
@@ -333,8 +920,11 @@ fn run_vp(
     };
 
     // Instantiate the wasm module
-    let instance = wasmer::Instance::new(&module, &vp_imports)
-        .map_err(|e| Error::InstantiationError(Box::new(e)))?;
+    let instance = timed(
+        |timings, elapsed| timings.instantiate += elapsed,
+        || wasmer::Instance::new(&module, &vp_imports),
+    )
+    .map_err(|e| Error::InstantiationError(Box::new(e)))?;
 
     // We need to write the inputs in the memory exported from the wasm
     // module
@@ -363,20 +953,31 @@ fn run_vp(
             entrypoint: VP_ENTRYPOINT,
             error,
         })?;
-    let is_valid = validate_tx
-        .call(
-            addr_ptr,
-            addr_len,
-            data_ptr,
-            data_len,
-            keys_changed_ptr,
-            keys_changed_len,
-            verifiers_ptr,
-            verifiers_len,
-        )
-        .map_err(Error::RuntimeError)?;
+    // The execution time of a nested VP is overwritten by the outer one's,
+    // which includes it
+    let is_valid = timed(
+        |timings, elapsed| timings.execute = elapsed,
+        || {
+            validate_tx.call(
+                addr_ptr,
+                addr_len,
+                data_ptr,
+                data_len,
+                keys_changed_ptr,
+                keys_changed_len,
+                verifiers_ptr,
+                verifiers_len,
+            )
+        },
+    )
+    .map_err(Error::RuntimeError)?;
     tracing::debug!("is_valid {}", is_valid);
-    Ok(is_valid == 1)
+    match is_valid {
+        0 => Ok(false),
+        1 => Ok(true),
+        // Any other value indicates a bug in the VP's ABI
+        value => Err(Error::InvalidVpReturnValue(value)),
+    }
 }
 
 /// Validity predicate wasm evaluator for `eval` host function calls.
@@ -431,7 +1032,7 @@ where
     /// Evaluate the given VP.
     pub fn eval_native_result(
         &self,
-        ctx: VpCtx<'static, DB, H, Self, CA>,
+        mut ctx: VpCtx<'static, DB, H, Self, CA>,
         vp_code_hash: Hash,
         input_data: Tx,
     ) -> Result<bool> {
@@ -442,18 +1043,28 @@ where
         let write_log = unsafe { ctx.write_log.get() };
         let storage = unsafe { ctx.storage.get() };
         let gas_meter = unsafe { ctx.gas_meter.get() };
+        // The evaluated VP gets its own sentinel, which is merged into the
+        // caller's sentinel afterwards
+        let caller_sentinel = unsafe { ctx.sentinel.get() };
+        let mut sentinel = VpSentinel::default();
+        ctx.sentinel = unsafe { MutHostRef::new(&mut sentinel) };
         let env = VpVmEnv {
             memory: WasmMemory::default(),
             ctx,
         };
 
         // Compile the wasm module
-        let (module, store) = fetch_or_compile(
-            vp_wasm_cache,
-            &Commitment::Hash(vp_code_hash),
-            write_log,
-            storage,
-            gas_meter,
+        let (module, store) = timed(
+            |timings, elapsed| timings.compile += elapsed,
+            || {
+                fetch_or_compile(
+                    vp_wasm_cache,
+                    &Commitment::Hash(vp_code_hash),
+                    write_log,
+                    storage,
+                    gas_meter,
+                )
+            },
         )?;
 
         let initial_memory =
@@ -461,7 +1072,7 @@ where
 
         let imports = vp_imports(&store, initial_memory, env);
 
-        run_vp(
+        let result = run_vp(
             module,
             imports,
             &vp_code_hash,
@@ -470,7 +1081,15 @@ where
             keys_changed,
             verifiers,
             gas_meter,
-        )
+        );
+        let rejected = sentinel.is_rejected();
+        caller_sentinel.merge_evaluated(sentinel);
+        match result {
+            // The evaluated VP explicitly rejected the tx, which is returned
+            // to the caller as its verdict
+            Err(_) if rejected => Ok(false),
+            result => result,
+        }
     }
 }
 
@@ -484,10 +1103,69 @@ pub fn untrusted_wasm_store(limit: Limit<BaseTunables>) -> wasmer::Store {
     )
 }
 
-/// Inject gas counter and stack-height limiter into the given wasm code
+/// In-memory LRU cache of prepared wasm code, keyed by the hash of the raw
+/// code
+type PreparedCodeCache =
+    CLruCache<Hash, Vec<u8>, RandomState, PreparedCodeCacheScale>;
+
+/// Configures the cache scale of prepared code that limits the maximum total
+/// size of the code kept in the cache.
+#[derive(Debug)]
+struct PreparedCodeCacheScale;
+
+impl WeightScale<Hash, Vec<u8>> for PreparedCodeCacheScale {
+    fn weight(&self, _key: &Hash, value: &Vec<u8>) -> usize {
+        value.len()
+    }
+}
+
+/// Get the process-wide cache of prepared wasm code. It's separate from the
+/// compiled modules cache and it allows to skip the injection round-trip for
+/// code that's been prepared before.
+fn prepared_code_cache() -> &'static Mutex<PreparedCodeCache> {
+    static CACHE: OnceLock<Mutex<PreparedCodeCache>> = OnceLock::new();
+    CACHE.get_or_init(|| {
+        Mutex::new(CLruCache::with_config(
+            CLruCacheConfig::new(
+                NonZeroUsize::new(PREPARED_CODE_CACHE_MAX_BYTES).unwrap(),
+            )
+            .with_scale(PreparedCodeCacheScale),
+        ))
+    })
+}
+
+#[cfg(test)]
+thread_local! {
+    /// The number of times the wasm code has been injected in this thread,
+    /// used to test the cache of prepared code
+    static INJECTION_COUNT: std::cell::Cell<usize> = std::cell::Cell::new(0);
+}
+
+/// Inject gas counter and stack-height limiter into the given wasm code. The
+/// prepared code is cached, so that the same code is only injected once.
 pub fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
-    let module: elements::Module = elements::deserialize_buffer(code.as_ref())
+    let hash = Hash::sha256(code.as_ref());
+    if let Some(prepared) = prepared_code_cache().lock().unwrap().get(&hash) {
+        return Ok(prepared.clone());
+    }
+    let prepared = inject_wasm_code(code.as_ref())?;
+    // Put into cache, ignore result if it's full
+    let _ = prepared_code_cache()
+        .lock()
+        .unwrap()
+        .put_with_weight(hash, prepared.clone());
+    Ok(prepared)
+}
+
+/// Inject gas counter and stack-height limiter into the given wasm code
+fn inject_wasm_code(code: &[u8]) -> Result<Vec<u8>> {
+    #[cfg(test)]
+    INJECTION_COUNT.with(|count| count.set(count.get() + 1));
+
+    let module: elements::Module = elements::deserialize_buffer(code)
         .map_err(Error::DeserializationError)?;
+    check_memory_layout(&module)?;
+    let required_exports = required_exports(&module);
     let module = wasm_instrument::gas_metering::inject(
         module,
         wasm_instrument::gas_metering::host_function::Injector::new(
@@ -499,9 +1177,84 @@ pub fn prepare_wasm_code<T: AsRef<[u8]>>(code: T) -> Result<Vec<u8>> {
     let module =
         wasm_instrument::inject_stack_limiter(module, WASM_STACK_LIMIT)
             .map_err(|_original_module| Error::StackLimiterInjection)?;
+    check_required_exports(&module, &required_exports)?;
     elements::serialize(module).map_err(Error::SerializationError)
 }
 
+/// The exports of the given module that the runners look up - its memory and
+/// the tx or VP entrypoint
+fn required_exports(module: &elements::Module) -> Vec<&'static str> {
+    ["memory", TX_ENTRYPOINT, VP_ENTRYPOINT]
+        .into_iter()
+        .filter(|name| has_export(module, name))
+        .collect()
+}
+
+/// Check that the instrumented module still has all the required exports of
+/// the original module, as otherwise it would fail later on with a confusing
+/// error.
+fn check_required_exports(
+    module: &elements::Module,
+    required_exports: &[&str],
+) -> Result<()> {
+    match required_exports
+        .iter()
+        .find(|name| !has_export(module, name))
+    {
+        Some(name) => {
+            Err(Error::InstrumentationStrippedExport(name.to_string()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Whether the given module has an export with the given name
+fn has_export(module: &elements::Module, name: &str) -> bool {
+    module.export_section().map_or(false, |section| {
+        section.entries().iter().any(|entry| entry.field() == name)
+    })
+}
+
+/// Check that the module has exactly one memory, either imported or defined,
+/// and that it's exported as `"memory"`, which is where the runners expect to
+/// find it.
+fn check_memory_layout(module: &elements::Module) -> Result<()> {
+    let imported = module
+        .import_section()
+        .map(|section| {
+            section
+                .entries()
+                .iter()
+                .filter(|entry| {
+                    matches!(entry.external(), elements::External::Memory(_))
+                })
+                .count()
+        })
+        .unwrap_or_default();
+    let defined = module
+        .memory_section()
+        .map(|section| section.entries().len())
+        .unwrap_or_default();
+    let memories = imported + defined;
+    if memories != 1 {
+        return Err(Error::UnexpectedMemoryLayout(format!(
+            "Expected exactly one memory, found {memories}"
+        )));
+    }
+    let exported = module.export_section().map_or(false, |section| {
+        section.entries().iter().any(|entry| {
+            entry.field() == "memory"
+                && matches!(entry.internal(), elements::Internal::Memory(0))
+        })
+    });
+    if !exported {
+        return Err(Error::UnexpectedMemoryLayout(
+            "The memory must be exported as \"memory\"".to_string(),
+        ));
+    }
+    Ok(())
+}
+
 // Fetch or compile a WASM code from the cache or storage. Account for the
 // loading and code compilation gas costs.
 fn fetch_or_compile<DB, H, CN, CA>(
@@ -628,12 +1381,283 @@ fn get_gas_rules() -> wasm_instrument::gas_metering::ConstantCostRules {
     )
 }
 
-#[cfg(test)]
-mod tests {
+/// Testing helpers
+#[cfg(any(test, feature = "testing"))]
+pub mod testing {
+    use borsh_ext::BorshSerializeExt;
+    use namada_gas::Gas;
+    use namada_tx::data::TxType;
+
+    use super::*;
+    use crate::state::testing::TestStorage;
+    pub use crate::vm::host_env::mock::{with_mocks, MockHostResponses};
+    use crate::vm::wasm;
+
+    /// The default gas limit of a VP run
+    pub const VP_RUN_GAS_LIMIT: u64 = 10_000_000_000;
+
+    /// A builder of the inputs of a validity predicate run with [`vp`]. The
+    /// inputs that are not set are defaulted to an empty state and write log,
+    /// a raw tx with no sections, no changed keys and no verifiers.
+    pub struct VpRunBuilder {
+        storage: TestStorage,
+        write_log: WriteLog,
+        tx: Tx,
+        tx_index: TxIndex,
+        address: Option<Address>,
+        gas_limit: u64,
+        keys_changed: BTreeSet<Key>,
+        verifiers: BTreeSet<Address>,
+        log_level: Option<LevelFilter>,
+        max_open_iterators: usize,
+    }
+
+    impl Default for VpRunBuilder {
+        fn default() -> Self {
+            Self {
+                storage: TestStorage::default(),
+                write_log: WriteLog::default(),
+                tx: Tx::from_type(TxType::Raw),
+                tx_index: TxIndex::default(),
+                address: None,
+                gas_limit: VP_RUN_GAS_LIMIT,
+                keys_changed: BTreeSet::default(),
+                verifiers: BTreeSet::default(),
+                log_level: None,
+                max_open_iterators: prefix_iter::DEFAULT_MAX_OPEN_ITERATORS,
+            }
+        }
+    }
+
+    impl VpRunBuilder {
+        /// Set the storage
+        pub fn storage(mut self, storage: TestStorage) -> Self {
+            self.storage = storage;
+            self
+        }
+
+        /// Set the write log
+        pub fn write_log(mut self, write_log: WriteLog) -> Self {
+            self.write_log = write_log;
+            self
+        }
+
+        /// Set the tx being validated
+        pub fn tx(mut self, tx: Tx) -> Self {
+            self.tx = tx;
+            self
+        }
+
+        /// Set the index of the tx being validated
+        pub fn tx_index(mut self, tx_index: TxIndex) -> Self {
+            self.tx_index = tx_index;
+            self
+        }
+
+        /// Set the address of the VP's owner. When not set, a new address
+        /// is generated from the storage.
+        pub fn address(mut self, address: Address) -> Self {
+            self.address = Some(address);
+            self
+        }
+
+        /// Set the gas limit
+        pub fn gas_limit(mut self, gas_limit: u64) -> Self {
+            self.gas_limit = gas_limit;
+            self
+        }
+
+        /// Set the keys changed by the tx
+        pub fn keys_changed(mut self, keys_changed: BTreeSet<Key>) -> Self {
+            self.keys_changed = keys_changed;
+            self
+        }
+
+        /// Set the verifiers of the tx
+        pub fn verifiers(mut self, verifiers: BTreeSet<Address>) -> Self {
+            self.verifiers = verifiers;
+            self
+        }
+
+        /// Set the level that limits the host calls traces of the VP, instead
+        /// of the process-wide level
+        pub fn log_level(mut self, log_level: LevelFilter) -> Self {
+            self.log_level = Some(log_level);
+            self
+        }
+
+        /// Set the maximum number of prefix iterators that the VP can open at
+        /// the same time
+        pub fn max_open_iterators(mut self, max_open: usize) -> Self {
+            self.max_open_iterators = max_open;
+            self
+        }
+
+        /// Store the given VP code in the storage and run it with the inputs
+        pub fn run(self, vp_code: impl AsRef<[u8]>) -> Result<bool> {
+            let Self {
+                mut storage,
+                write_log,
+                tx,
+                tx_index,
+                address,
+                gas_limit,
+                keys_changed,
+                verifiers,
+                log_level,
+                max_open_iterators,
+            } = self;
+            let address = address.unwrap_or_else(|| {
+                storage.address_gen.generate_address("rng seed")
+            });
+            let mut gas_meter = VpGasMeter::new_from_tx_meter(
+                &TxGasMeter::new_from_sub_limit(gas_limit.into()),
+            );
+            let (vp_cache, _tmp_dir) =
+                wasm::compilation_cache::common::testing::cache();
+            // store the vp code
+            let vp_code = vp_code.as_ref();
+            let code_hash = Hash::sha256(vp_code);
+            let code_len = (vp_code.len() as u64).serialize_to_vec();
+            storage.write(&Key::wasm_code(&code_hash), vp_code).unwrap();
+            storage
+                .write(&Key::wasm_code_len(&code_hash), code_len)
+                .unwrap();
+
+            with_log_level(log_level, || {
+                prefix_iter::with_max_open_iterators(max_open_iterators, || {
+                    vp(
+                        code_hash,
+                        &tx,
+                        &tx_index,
+                        &address,
+                        &storage,
+                        &write_log,
+                        &mut gas_meter,
+                        &keys_changed,
+                        &verifiers,
+                        vp_cache,
+                    )
+                })
+            })
+        }
+    }
+
+    /// Execute a transaction code with some of its host calls returning the
+    /// given pre-programmed responses instead of accessing the storage.
+    #[allow(clippy::too_many_arguments)]
+    pub fn tx_with_mocks<DB, H, CA>(
+        mocks: MockHostResponses,
+        storage: &State<DB, H>,
+        write_log: &mut WriteLog,
+        gas_meter: &mut TxGasMeter,
+        tx_index: &TxIndex,
+        tx: &Tx,
+        vp_wasm_cache: &mut VpCache<CA>,
+        tx_wasm_cache: &mut TxCache<CA>,
+    ) -> Result<BTreeSet<Address>>
+    where
+        DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+        H: 'static + StorageHasher,
+        CA: 'static + WasmCacheAccess,
+    {
+        with_mocks(mocks, || {
+            super::tx(
+                storage,
+                write_log,
+                gas_meter,
+                tx_index,
+                tx,
+                None,
+                vp_wasm_cache,
+                tx_wasm_cache,
+            )
+        })
+    }
+
+    /// Execute a transaction code against two storage backends, e.g. the live
+    /// DB and a reference in-memory DB, to check that they're consistent. Each
+    /// execution starts from a copy of the given write log and gets its own
+    /// gas meter with the given limit. Returns [`Error::Divergence`] when only
+    /// one of the executions fails, when they request different verifiers or
+    /// when their resulting write logs are not identical. Otherwise, the
+    /// write log is updated with the changes of the transaction and the result
+    /// of the execution against `storage` is returned.
+    #[allow(clippy::too_many_arguments)]
+    pub fn tx_tee<DB, H, RefDB, RefH, CA>(
+        storage: &State<DB, H>,
+        reference_storage: &State<RefDB, RefH>,
+        write_log: &mut WriteLog,
+        gas_limit: Gas,
+        tx_index: &TxIndex,
+        tx: &Tx,
+        vp_wasm_cache: &mut VpCache<CA>,
+        tx_wasm_cache: &mut TxCache<CA>,
+    ) -> Result<BTreeSet<Address>>
+    where
+        DB: 'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+        H: 'static + StorageHasher,
+        RefDB:
+            'static + namada_state::DB + for<'iter> namada_state::DBIter<'iter>,
+        RefH: 'static + StorageHasher,
+        CA: 'static + WasmCacheAccess,
+    {
+        let mut reference_write_log = write_log.clone();
+        let result = super::tx(
+            storage,
+            write_log,
+            &mut TxGasMeter::new_from_sub_limit(gas_limit),
+            tx_index,
+            tx,
+            None,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        );
+        let reference_result = super::tx(
+            reference_storage,
+            &mut reference_write_log,
+            &mut TxGasMeter::new_from_sub_limit(gas_limit),
+            tx_index,
+            tx,
+            None,
+            vp_wasm_cache,
+            tx_wasm_cache,
+        );
+
+        match (&result, &reference_result) {
+            (Ok(verifiers), Ok(reference_verifiers))
+                if verifiers != reference_verifiers =>
+            {
+                return Err(Error::Divergence(format!(
+                    "requested verifiers {verifiers:?} and \
+                     {reference_verifiers:?} differ"
+                )));
+            }
+            (Ok(_), Err(err)) | (Err(err), Ok(_)) => {
+                return Err(Error::Divergence(format!(
+                    "only one of the executions failed with: {err}"
+                )));
+            }
+            _ => {}
+        }
+        if *write_log != reference_write_log {
+            return Err(Error::Divergence(
+                "the resulting write logs differ".to_string(),
+            ));
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, HashSet};
     use std::error::Error as StdErrorTrait;
+    use std::sync::Arc;
 
     use borsh_ext::BorshSerializeExt;
     use itertools::Either;
+    use namada_test_utils::tx_data::TxWriteData;
     use namada_test_utils::TestWasms;
     use namada_tx::data::TxType;
     use namada_tx::{Code, Data};
@@ -641,14 +1665,100 @@ mod tests {
     use wasmer_vm::TrapCode;
 
     use super::*;
+    use crate::ledger::vp_host_fns;
     use crate::state::testing::TestStorage;
     use crate::tx::data::eval_vp::EvalVp;
     use crate::types::hash::Hash;
+    use crate::vm::host_env::log_level::set_default_log_level;
     use crate::vm::host_env::TxRuntimeError;
-    use crate::vm::wasm;
+    use crate::vm::{read_custom_section, wasm};
 
     const TX_GAS_LIMIT: u64 = 10_000_000_000;
 
+    /// Test that each error maps to its documented stable code and that the
+    /// codes are unique.
+    #[test]
+    fn test_error_codes() {
+        let runtime_error = || wasmer::RuntimeError::new("test");
+        let errors = [
+            (Error::MissingSection("test".to_string()), 1),
+            (Error::MemoryError(memory::Error::UninitializedMemory), 2),
+            (Error::StackLimiterInjection, 3),
+            (
+                Error::DeserializationError(elements::Error::Other("test")),
+                4,
+            ),
+            (Error::SerializationError(elements::Error::Other("test")), 5),
+            (Error::GasMeterInjection, 6),
+            (
+                Error::CompileError(wasmer::CompileError::Validate(
+                    "test".to_string(),
+                )),
+                7,
+            ),
+            (
+                Error::MissingModuleMemory(wasmer::ExportError::Missing(
+                    "memory".to_string(),
+                )),
+                8,
+            ),
+            (
+                Error::MissingModuleEntrypoint(wasmer::ExportError::Missing(
+                    "entrypoint".to_string(),
+                )),
+                9,
+            ),
+            (Error::RuntimeError(runtime_error()), 10),
+            (
+                Error::InstantiationError(Box::new(
+                    wasmer::InstantiationError::Start(runtime_error()),
+                )),
+                11,
+            ),
+            (
+                Error::UnexpectedModuleEntrypointInterface {
+                    entrypoint: "test",
+                    error: runtime_error(),
+                },
+                12,
+            ),
+            (
+                Error::ValidationError(
+                    WasmValidationError::DataSegmentOutOfBounds {
+                        memory_index: 0,
+                        offset: 0,
+                        len: 1,
+                        memory_size: 0,
+                    },
+                ),
+                13,
+            ),
+            (
+                Error::CodeHash(TxHashError::Temporary {
+                    error: "test".to_string(),
+                }),
+                14,
+            ),
+            (Error::LoadWasmCode("test".to_string()), 15),
+            (Error::NoCompiledWasmCode, 16),
+            (Error::GasError("test".to_string()), 17),
+            (Error::ConversionError("test".to_string()), 18),
+            (Error::InvalidTxSignature, 19),
+            (Error::UnexpectedMemoryLayout("test".to_string()), 20),
+            (Error::InvalidVpReturnValue(2), 21),
+            (Error::CompileTimeout(std::time::Duration::from_secs(1)), 22),
+            (Error::Divergence("test".to_string()), 23),
+            (Error::InstrumentationStrippedExport("test".to_string()), 24),
+            (Error::MaintenanceMode, 25),
+        ];
+        let mut codes = HashSet::new();
+        for (error, expected) in errors {
+            let code = error.code().to_u32();
+            assert_eq!(code, expected, "Unexpected code of {error}");
+            assert!(codes.insert(code), "Duplicate code {code}");
+        }
+    }
+
     /// Test that we sanitize accesses to invalid addresses in wasm memory.
     #[test]
     fn test_tx_sanitize_invalid_addrs() {
@@ -792,6 +1902,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         );
@@ -809,6 +1920,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         )
@@ -1031,6 +2143,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         );
@@ -1165,6 +2278,7 @@ mod tests {
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
         )
@@ -1307,131 +2421,1116 @@ mod tests {
         assert!(!passed);
     }
 
-    fn execute_tx_with_code(tx_code: Vec<u8>) -> Result<BTreeSet<Address>> {
-        let tx_data = vec![];
-        let tx_index = TxIndex::default();
+    /// Test that a transaction can be compiled and executed with the dylib
+    /// engine.
+    #[cfg(feature = "wasm-engine-dylib")]
+    #[test]
+    fn test_tx_with_dylib_engine() {
         let storage = TestStorage::default();
         let mut write_log = WriteLog::default();
         let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
-        let (mut vp_cache, _) =
-            wasm::compilation_cache::common::testing::cache();
-        let (mut tx_cache, _) =
-            wasm::compilation_cache::common::testing::cache();
+        let tx_index = TxIndex::default();
 
-        // store the tx code
+        let tx_code = TestWasms::TxNoOp.read_bytes();
         let code_hash = Hash::sha256(&tx_code);
-        let code_len = (tx_code.len() as u64).serialize_to_vec();
         let key = Key::wasm_code(&code_hash);
         let len_key = Key::wasm_code_len(&code_hash);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
         write_log.write(&key, tx_code).unwrap();
         write_log.write(&len_key, code_len).unwrap();
 
+        let (vp_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let mut vp_cache = vp_cache.with_engine(wasm::WasmEngine::Dylib);
+        let (tx_cache, _tx_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let mut tx_cache = tx_cache.with_engine(wasm::WasmEngine::Dylib);
+
         let mut outer_tx = Tx::from_type(TxType::Raw);
         outer_tx.set_code(Code::from_hash(code_hash, None));
-        outer_tx.set_data(Data::new(tx_data));
-
-        tx(
+        outer_tx.set_data(Data::new(vec![]));
+        let result = tx(
             &storage,
             &mut write_log,
             &mut gas_meter,
             &tx_index,
             &outer_tx,
+            None,
             &mut vp_cache,
             &mut tx_cache,
-        )
+        );
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
     }
 
-    fn loop_in_tx_wasm(loops: u32) -> Result<BTreeSet<Address>> {
-        // A transaction with a recursive loop.
-        // The boilerplate code is generated from tx_template.wasm using
-        // `wasm2wat` and the loop code is hand-written.
+    /// Test that a transaction can be executed from an async context with the
+    /// [`AsyncTxRunner`].
+    #[tokio::test]
+    async fn test_async_tx_runner() {
+        let mut write_log = WriteLog::default();
+
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let code_hash = Hash::sha256(&tx_code);
+        let key = Key::wasm_code(&code_hash);
+        let len_key = Key::wasm_code_len(&code_hash);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+        write_log.write(&key, tx_code).unwrap();
+        write_log.write(&len_key, code_len).unwrap();
+
+        let (vp_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let (tx_cache, _tx_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let runner = AsyncTxRunner::new(vp_cache, tx_cache);
+
+        let state = TxRunState {
+            storage: TestStorage::default(),
+            write_log,
+            gas_meter: TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+            fee_payer: None,
+        };
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(vec![]));
+        let (state, result) =
+            runner.run(state, TxIndex::default(), outer_tx).await;
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+        // The state is handed back from the worker thread
+        assert!(state.write_log.read(&key).0.is_some());
+    }
+
+    /// Test that a single [`AsyncTxRunner`] can be shared by reference between
+    /// multiple threads, each running its own transaction.
+    #[test]
+    fn test_async_tx_runner_shared_between_threads() {
+        fn assert_send_sync<T: Send + Sync>(_: &T) {}
+
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let code_hash = Hash::sha256(&tx_code);
+        let key = Key::wasm_code(&code_hash);
+        let len_key = Key::wasm_code_len(&code_hash);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+
+        let (vp_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let (tx_cache, _tx_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let runner = AsyncTxRunner::new(vp_cache, tx_cache);
+        assert_send_sync(&runner);
+
+        const THREADS: usize = 4;
+        std::thread::scope(|scope| {
+            let workers: Vec<_> = (0..THREADS)
+                .map(|i| {
+                    let runner = &runner;
+                    let (tx_code, code_len) =
+                        (tx_code.clone(), code_len.clone());
+                    let (key, len_key) = (key.clone(), len_key.clone());
+                    scope.spawn(move || {
+                        // Every thread has its own state with a distinct value
+                        let mut write_log = WriteLog::default();
+                        write_log.write(&key, tx_code).unwrap();
+                        write_log.write(&len_key, code_len).unwrap();
+                        let thread_key =
+                            Key::parse(format!("thread_{i}")).unwrap();
+                        write_log.write(&thread_key, vec![i as u8]).unwrap();
+                        let state = TxRunState {
+                            storage: TestStorage::default(),
+                            write_log,
+                            gas_meter: TxGasMeter::new_from_sub_limit(
+                                TX_GAS_LIMIT.into(),
+                            ),
+                            fee_payer: None,
+                        };
+                        let mut outer_tx = Tx::from_type(TxType::Raw);
+                        outer_tx.set_code(Code::from_hash(code_hash, None));
+                        outer_tx.set_data(Data::new(vec![]));
+
+                        let rt = tokio::runtime::Builder::new_current_thread()
+                            .build()
+                            .unwrap();
+                        let (state, result) = rt.block_on(runner.run(
+                            state,
+                            TxIndex(i as u32),
+                            outer_tx,
+                        ));
+                        (i, thread_key, state, result)
+                    })
+                })
+                .collect();
+
+            for worker in workers {
+                let (i, thread_key, state, result) = worker.join().unwrap();
+                assert!(result.is_ok(), "Expected success, got {:?}", result);
+                // Each thread gets its own state back
+                match state.write_log.read(&thread_key).0 {
+                    Some(StorageModification::Write { value }) => {
+                        assert_eq!(value, &vec![i as u8])
+                    }
+                    other => panic!("Unexpected modification {other:?}"),
+                }
+                assert!(state.gas_meter.get_tx_consumed_gas() > 0.into());
+            }
+        });
+    }
+
+    /// Test that an [`AsyncTxRunner`] in maintenance mode rejects the
+    /// transactions without executing them, and that disabling the maintenance
+    /// mode at runtime restores the execution.
+    #[test]
+    fn test_async_tx_runner_maintenance() {
+        let tx_code = TestWasms::TxNoOp.read_bytes();
+        let code_hash = Hash::sha256(&tx_code);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&Key::wasm_code(&code_hash), tx_code)
+            .unwrap();
+        write_log
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+        let state = TxRunState {
+            storage: TestStorage::default(),
+            write_log,
+            gas_meter: TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+            fee_payer: None,
+        };
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(vec![]));
+
+        let (vp_cache, _vp_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let (tx_cache, _tx_cache_dir) =
+            wasm::compilation_cache::common::testing::cache();
+        let maintenance = MaintenanceMode::new(true);
+        let runner = AsyncTxRunner::new(vp_cache, tx_cache)
+            .with_maintenance(maintenance.clone());
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .build()
+            .unwrap();
+
+        // The tx is rejected without consuming any gas
+        let (state, result) = rt.block_on(runner.run(
+            state,
+            TxIndex::default(),
+            outer_tx.clone(),
+        ));
+        assert!(
+            matches!(result, Err(Error::MaintenanceMode)),
+            "Expected a maintenance mode error, got {result:?}"
+        );
+        assert_eq!(state.gas_meter.get_tx_consumed_gas(), 0.into());
+
+        // Once the maintenance is over, the tx is executed
+        maintenance.set(false);
+        let (state, result) =
+            rt.block_on(runner.run(state, TxIndex::default(), outer_tx));
+        assert!(result.is_ok(), "Expected success, got {result:?}");
+        assert!(state.gas_meter.get_tx_consumed_gas() > 0.into());
+    }
+
+    /// Test that a transaction wasm without any memory is rejected with a
+    /// specific error.
+    #[test]
+    fn test_tx_without_memory() {
         let tx_code = wasmer::wat2wasm(
-            format!(
-                r#"
+            r#"
             (module
                 (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
 
-                ;; recursive loop, the param is the number of loops
-                (func $loop (param i64) (result i64)
-                (if
-                (result i64)
-                (i64.eqz (get_local 0))
-                (then (get_local 0))
-                (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
-
-                (func $_apply_tx (type 0) (param i64 i64)
-                (call $loop (i64.const {}))
-                drop)
+        let error = execute_tx_with_code(tx_code)
+            .expect_err("Expected an unexpected memory layout error");
+        assert!(
+            matches!(error, Error::UnexpectedMemoryLayout(_)),
+            "Expected an unexpected memory layout error, got {error}"
+        );
+    }
 
-                (table (;0;) 1 1 funcref)
+    /// Test that the instrumentation keeps the required exports of a module
+    /// and that a module that lost one of them is rejected.
+    #[test]
+    fn test_instrumentation_required_exports() {
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
                 (memory (;0;) 16)
-                (global (;0;) (mut i32) (i32.const 1048576))
                 (export "memory" (memory 0))
                 (export "_apply_tx" (func $_apply_tx)))
-            "#,
-                loops
-            )
+            "#
             .as_bytes(),
         )
         .expect("unexpected error converting wat2wasm")
         .into_owned();
-
-        execute_tx_with_code(tx_code)
+        let module: elements::Module =
+            elements::deserialize_buffer(&tx_code).unwrap();
+        let required = required_exports(&module);
+        assert_eq!(required, vec!["memory", TX_ENTRYPOINT]);
+
+        // A normal module keeps its exports
+        let prepared = inject_wasm_code(&tx_code).unwrap();
+        let prepared: elements::Module =
+            elements::deserialize_buffer(&prepared).unwrap();
+        assert_eq!(required_exports(&prepared), required);
+
+        // A module that lost its entrypoint is rejected
+        let mut stripped = prepared;
+        stripped
+            .export_section_mut()
+            .unwrap()
+            .entries_mut()
+            .retain(|entry| entry.field() != TX_ENTRYPOINT);
+        let error = check_required_exports(&stripped, &required)
+            .expect_err("Expected a stripped export error");
+        assert!(
+            matches!(
+                &error,
+                Error::InstrumentationStrippedExport(name)
+                    if name == TX_ENTRYPOINT
+            ),
+            "Expected a stripped export error, got {error}"
+        );
     }
 
-    fn loop_in_vp_wasm(loops: u32) -> Result<bool> {
-        // A validity predicate with a recursive loop.
-        // The boilerplate code is generated from vp_template.wasm using
-        // `wasm2wat` and the loop code is hand-written.
-        let vp_code = wasmer::wat2wasm(format!(
+    /// Test that a transaction wasm with a memory that's not exported as
+    /// `"memory"` is rejected with a specific error.
+    #[test]
+    fn test_tx_with_misnamed_memory() {
+        let tx_code = wasmer::wat2wasm(
             r#"
             (module
-                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
-
-                ;; recursive loop, the param is the number of loops
-                (func $loop (param i64) (result i64)
-                (if
-                (result i64)
-                (i64.eqz (get_local 0))
-                (then (get_local 0))
-                (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (memory (;0;) 16)
+                (export "mem" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
 
-                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
-                (call $loop (i64.const {})))
+        let error = execute_tx_with_code(tx_code)
+            .expect_err("Expected an unexpected memory layout error");
+        assert!(
+            matches!(error, Error::UnexpectedMemoryLayout(_)),
+            "Expected an unexpected memory layout error, got {error}"
+        );
+    }
 
-                (table (;0;) 1 1 funcref)
-                (memory (;0;) 16)
-                (global (;0;) (mut i32) (i32.const 1048576))
+    /// Test that a wasm module with a data segment that doesn't fit within its
+    /// declared memory is rejected by the validation.
+    #[test]
+    fn test_data_segment_out_of_bounds() {
+        // A single page of memory with a data segment written past its end
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (memory (;0;) 1)
+                (data (i32.const 65534) "out of bounds")
                 (export "memory" (memory 0))
-                (export "_validate_tx" (func $_validate_tx)))
-            "#, loops).as_bytes(),
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
         )
-            .expect("unexpected error converting wat2wasm").into_owned();
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
 
-        let outer_tx = Tx::from_type(TxType::Raw);
-        let tx_index = TxIndex::default();
-        let mut storage = TestStorage::default();
-        let addr = storage.address_gen.generate_address("rng seed");
-        let write_log = WriteLog::default();
-        let mut gas_meter = VpGasMeter::new_from_tx_meter(
-            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        let error = validate_untrusted_wasm(&tx_code)
+            .expect_err("Expected the data segment to be rejected");
+        assert!(
+            matches!(
+                error,
+                WasmValidationError::DataSegmentOutOfBounds {
+                    memory_index: 0,
+                    offset: 65534,
+                    len: 13,
+                    memory_size: 65536,
+                }
+            ),
+            "Expected a data segment out of bounds error, got {error}"
         );
-        let keys_changed = BTreeSet::new();
-        let verifiers = BTreeSet::new();
-        let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
-        // store the vp code
-        let code_hash = Hash::sha256(&vp_code);
-        let code_len = (vp_code.len() as u64).serialize_to_vec();
-        let key = Key::wasm_code(&code_hash);
-        let len_key = Key::wasm_code_len(&code_hash);
-        storage.write(&key, vp_code).unwrap();
-        storage.write(&len_key, code_len).unwrap();
 
-        vp(
-            code_hash,
-            &outer_tx,
-            &tx_index,
-            &addr,
+        // The same segment fits within two pages of memory
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (memory (;0;) 2)
+                (data (i32.const 65534) "out of bounds")
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        validate_untrusted_wasm(&tx_code).expect("Expected a valid wasm");
+    }
+
+    /// Test that a custom section is read from a wasm module by its name.
+    #[test]
+    fn test_read_custom_section() {
+        let mut code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (memory (;0;) 1)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        // Append a custom section with its id, size, name and contents
+        let name = b"metadata";
+        let contents = br#"{"name":"test","version":"0.1.0"}"#;
+        code.push(0);
+        code.push((1 + name.len() + contents.len()) as u8);
+        code.push(name.len() as u8);
+        code.extend(name);
+        code.extend(contents);
+        validate_untrusted_wasm(&code).expect("Expected a valid wasm");
+
+        assert_eq!(
+            read_custom_section(&code, "metadata"),
+            Some(contents.to_vec())
+        );
+        assert_eq!(read_custom_section(&code, "abi"), None);
+    }
+
+    /// Test that the validation rejects a wasm module exporting a mutable
+    /// global, while the internal mutable globals are allowed.
+    #[test]
+    fn test_exported_mutable_global() {
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (global (;0;) (mut i32) (i32.const 0))
+                (memory (;0;) 1)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx))
+                (export "counter" (global 0)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let error = validate_untrusted_wasm(&tx_code)
+            .expect_err("Expected the mutable global export to be rejected");
+        assert!(
+            matches!(
+                &error,
+                WasmValidationError::ExportedMutableGlobal { name, index: 0 }
+                    if name == "counter"
+            ),
+            "Expected an exported mutable global error, got {error}"
+        );
+
+        // The same module with an internal mutable global and an exported
+        // immutable one is valid
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64)
+                (global.set 0 (i32.const 1)))
+                (global (;0;) (mut i32) (i32.const 0))
+                (global (;1;) i32 (i32.const 0))
+                (memory (;0;) 1)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx))
+                (export "constant" (global 1)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        validate_untrusted_wasm(&tx_code).expect("Expected a valid wasm");
+    }
+
+    /// Test that a VP calling `vp_reject` deep in its call stack aborts
+    /// immediately and that the runner treats it as a rejection with its
+    /// reason rather than an error.
+    #[test]
+    fn test_vp_reject_in_nested_call() {
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (import "env" "namada_vp_reject" (func $reject (param i64 i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+
+                (func $deeper
+                (call $reject (i64.const 524288) (i64.const 14)))
+
+                (func $deep
+                (call $deeper))
+
+                ;; the VP would accept if the rejection didn't abort it
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (call $deep)
+                (i64.const 1))
+
+                (memory (;0;) 16)
+                (data (i32.const 524288) "not authorized")
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let result = execute_vp_with_code_and_reason(vp_code);
+        assert!(
+            matches!(
+                &result,
+                Ok((false, Some(reason))) if reason == "not authorized"
+            ),
+            "Expected the VP to reject the tx, got {result:?}"
+        );
+    }
+
+    /// Test that the maximum number of open prefix iterators set on the runner
+    /// applies to the VP.
+    #[test]
+    fn test_vp_max_open_iterators() {
+        // This code opens 3 prefix iterators without exhausting them
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (import "env" "namada_vp_iter_prefix_pre" (func $iter (param i64 i64) (result i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (drop (call $iter (i64.const 524288) (i64.const 6)))
+                (drop (call $iter (i64.const 524288) (i64.const 6)))
+                (drop (call $iter (i64.const 524288) (i64.const 6)))
+                (i64.const 1))
+
+                (memory (;0;) 16)
+                (data (i32.const 524288) "prefix")
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let result = testing::VpRunBuilder::default()
+            .max_open_iterators(3)
+            .run(&vp_code);
+        assert!(matches!(result, Ok(true)), "Unexpected result {result:?}");
+
+        let error = testing::VpRunBuilder::default()
+            .max_open_iterators(2)
+            .run(&vp_code)
+            .expect_err("The VP should open too many iterators");
+        let Error::RuntimeError(rt_error) = &error else {
+            panic!("Unexpected error {error}");
+        };
+        let source_err =
+            rt_error.source().expect("No runtime error source found");
+        let vp_rt_err: &vp_host_fns::RuntimeError = source_err
+            .downcast_ref()
+            .unwrap_or_else(|| panic!("Unexpected error {source_err}"));
+        assert!(
+            matches!(
+                vp_rt_err,
+                vp_host_fns::RuntimeError::PrefixIterError(
+                    prefix_iter::Error::TooManyOpenIterators(2)
+                )
+            ),
+            "Unexpected error {vp_rt_err}"
+        );
+    }
+
+    /// Test that an invalid signature found by a VP evaluated from another VP
+    /// is still reported by the calling VP, even when the evaluated VP then
+    /// explicitly rejects the tx.
+    #[test]
+    fn test_vp_eval_invalid_signature() {
+        let mut storage = TestStorage::default();
+        let addr = storage.address_gen.generate_address("rng seed");
+        let write_log = WriteLog::default();
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let keys_changed = BTreeSet::new();
+        let verifiers = BTreeSet::new();
+        let tx_index = TxIndex::default();
+
+        // This code will call `eval` with the other VP below
+        let vp_eval = TestWasms::VpEval.read_bytes();
+        let code_hash = Hash::sha256(&vp_eval);
+        let code_len = (vp_eval.len() as u64).serialize_to_vec();
+        storage.write(&Key::wasm_code(&code_hash), vp_eval).unwrap();
+        storage
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+
+        // This code fails a signature check for the tx without any signature,
+        // using the zeroed memory for the encoded arguments, and then rejects
+        // the tx
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (import "env" "namada_vp_verify_tx_section_signature" (func $verify (param i64 i64 i64 i64 i64 i64 i32 i64 i64) (result i64)))
+                (import "env" "namada_vp_reject" (func $reject (param i64 i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (drop (call $verify
+                    (i64.const 700000) (i64.const 32)
+                    (i64.const 700000) (i64.const 8)
+                    (i64.const 700000) (i64.const 21)
+                    (i32.const 1)
+                    (i64.const 700000) (i64.const 1)))
+                (call $reject (i64.const 524288) (i64.const 14))
+                (i64.const 1))
+
+                (memory (;0;) 16)
+                (data (i32.const 524288) "not authorized")
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        let eval_code_hash = Hash::sha256(&vp_code);
+        let code_len = (vp_code.len() as u64).serialize_to_vec();
+        storage
+            .write(&Key::wasm_code(&eval_code_hash), vp_code)
+            .unwrap();
+        storage
+            .write(&Key::wasm_code_len(&eval_code_hash), code_len)
+            .unwrap();
+
+        let mut tx = Tx::new(storage.chain_id.clone(), None);
+        tx.add_code(vec![], None).add_serialized_data(vec![]);
+        let eval_vp = EvalVp {
+            vp_code_hash: eval_code_hash,
+            input: tx,
+        };
+        let mut outer_tx = Tx::new(storage.chain_id.clone(), None);
+        outer_tx.add_code(vec![], None).add_data(eval_vp);
+
+        let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
+        let result = vp(
+            code_hash,
+            &outer_tx,
+            &tx_index,
+            &addr,
+            &storage,
+            &write_log,
+            &mut gas_meter,
+            &keys_changed,
+            &verifiers,
+            vp_cache,
+        );
+        assert!(
+            matches!(result, Err(Error::InvalidTxSignature)),
+            "Expected the invalid signature to be reported, got {result:?}"
+        );
+    }
+
+    /// Test that the gas charged by a transaction before it traps is still
+    /// accounted in the gas meter.
+    #[test]
+    fn test_tx_partial_gas_on_trap() {
+        const PAGES: u64 = 3;
+        // A transaction that grows its memory, which is charged by the
+        // injected gas counter, and then traps
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64)
+                (drop (memory.grow (i32.const {PAGES})))
+                unreachable)
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        let error = execute_tx_with_code_and_gas_meter(tx_code, &mut gas_meter)
+            .expect_err("Expected the tx to trap");
+        assert!(
+            matches!(error, Error::RuntimeError(_)),
+            "Expected a runtime error, got {error}"
+        );
+        // The memory growth was charged before the trap
+        assert!(
+            gas_meter.get_tx_consumed_gas()
+                >= (PAGES * WASM_MEMORY_PAGE_GAS).into()
+        );
+    }
+
+    /// Test that a transaction whose memory is pre-allocated from a hint
+    /// doesn't need to grow it, so it's not charged for the growth.
+    #[test]
+    fn test_tx_memory_hint() {
+        const INIT_PAGES: u64 = 16;
+        const PAGES: u64 = 150;
+        // A transaction that grows its memory one page at a time until it
+        // has the required number of pages
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64)
+                (block $done
+                (loop $grow
+                (br_if $done (i32.ge_u (memory.size) (i32.const {PAGES})))
+                (drop (memory.grow (i32.const 1)))
+                (br $grow))))
+                (memory (;0;) {INIT_PAGES})
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let mut default_gas_meter =
+            TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        execute_tx_with_code_and_memory_hint(
+            tx_code.clone(),
+            &mut default_gas_meter,
+            None,
+        )
+        .expect("unexpected error running the tx");
+        let default_gas = default_gas_meter.get_tx_consumed_gas();
+
+        let mut hinted_gas_meter =
+            TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        execute_tx_with_code_and_memory_hint(
+            tx_code,
+            &mut hinted_gas_meter,
+            Some(PAGES as u32),
+        )
+        .expect("unexpected error running the tx");
+        let hinted_gas = hinted_gas_meter.get_tx_consumed_gas();
+
+        // Without the hint, every page above the module's initial memory is
+        // grown and charged
+        assert_eq!(
+            default_gas.checked_sub(hinted_gas),
+            Some(((PAGES - INIT_PAGES) * WASM_MEMORY_PAGE_GAS).into())
+        );
+    }
+
+    /// Test that preparing the same wasm code again returns the cached code
+    /// without injecting it again.
+    #[test]
+    fn test_prepare_wasm_code_cached() {
+        // A unique code, so that it cannot have been prepared by other tests
+        let code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64)
+                (drop (i64.const 1420)))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let injections = || INJECTION_COUNT.with(|count| count.get());
+        let initial = injections();
+
+        let prepared = prepare_wasm_code(&code).unwrap();
+        assert_eq!(injections(), initial + 1);
+
+        let prepared_again = prepare_wasm_code(&code).unwrap();
+        assert_eq!(prepared_again, prepared);
+        assert_eq!(
+            injections(),
+            initial + 1,
+            "The code must not be injected again"
+        );
+    }
+
+    /// Test that the trace of a VP run lists its host calls in order.
+    #[test]
+    fn test_vp_with_trace() {
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (import "env" "namada_vp_read_pre" (func $read_pre (param i64 i64) (result i64)))
+                (import "env" "namada_vp_read_post" (func $read_post (param i64 i64) (result i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (drop (call $read_pre (i64.const 524288) (i64.const 4)))
+                (drop (call $read_post (i64.const 524292) (i64.const 4)))
+                (i64.const 1))
+                (memory (;0;) 16)
+                (data (i32.const 524288) "key1key2")
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let outer_tx = Tx::from_type(TxType::Raw);
+        let tx_index = TxIndex::default();
+        let mut storage = TestStorage::default();
+        let addr = storage.address_gen.generate_address("rng seed");
+        let key1 = Key::parse("key1").unwrap();
+        let key2 = Key::parse("key2").unwrap();
+        storage.write(&key1, 1_u64.serialize_to_vec()).unwrap();
+        let mut write_log = WriteLog::default();
+        write_log.write(&key2, 2_u64.serialize_to_vec()).unwrap();
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
+        let code_hash = Hash::sha256(&vp_code);
+        let code_len = (vp_code.len() as u64).serialize_to_vec();
+        storage.write(&Key::wasm_code(&code_hash), vp_code).unwrap();
+        storage
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+
+        let (accept, trace) = vp_with_trace(
+            code_hash,
+            &outer_tx,
+            &tx_index,
+            &addr,
+            &storage,
+            &write_log,
+            &mut gas_meter,
+            &BTreeSet::from([key2.clone()]),
+            &BTreeSet::new(),
+            vp_cache,
+        )
+        .unwrap();
+        assert!(accept);
+        assert_eq!(
+            trace,
+            vec![
+                VpHostCall {
+                    name: "vp_read_pre",
+                    key: Some(key1),
+                },
+                VpHostCall {
+                    name: "vp_read_post",
+                    key: Some(key2),
+                },
+            ]
+        );
+    }
+
+    /// Test that the timings of the compilation, instantiation and execution
+    /// of a tx and a VP run are measured.
+    #[test]
+    fn test_run_timings() {
+        let tx_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+                (func $_apply_tx (type 0) (param i64 i64))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (i64.const 1))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let tx_index = TxIndex::default();
+        let mut storage = TestStorage::default();
+        let addr = storage.address_gen.generate_address("rng seed");
+        let mut write_log = WriteLog::default();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        for code in [&tx_code, &vp_code] {
+            let code_hash = Hash::sha256(code);
+            let code_len = (code.len() as u64).serialize_to_vec();
+            storage.write(&Key::wasm_code(&code_hash), code).unwrap();
+            storage
+                .write(&Key::wasm_code_len(&code_hash), code_len)
+                .unwrap();
+        }
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(Hash::sha256(&tx_code), None));
+        outer_tx.set_data(Data::new(vec![]));
+
+        let assert_measured = |timings: Timings| {
+            assert!(timings.compile > Duration::ZERO, "{timings:?}");
+            assert!(timings.instantiate > Duration::ZERO, "{timings:?}");
+            assert!(timings.execute > Duration::ZERO, "{timings:?}");
+        };
+
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        let (_verifiers, timings) = tx_with_timings(
+            &storage,
+            &mut write_log,
+            &mut gas_meter,
+            &tx_index,
+            &outer_tx,
+            None,
+            &mut vp_cache,
+            &mut tx_cache,
+        )
+        .unwrap();
+        assert_measured(timings);
+
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let (accept, timings) = vp_with_timings(
+            Hash::sha256(&vp_code),
+            &outer_tx,
+            &tx_index,
+            &addr,
+            &storage,
+            &write_log,
+            &mut gas_meter,
+            &BTreeSet::new(),
+            &BTreeSet::new(),
+            vp_cache,
+        )
+        .unwrap();
+        assert!(accept);
+        assert_measured(timings);
+
+        // The timings are only recorded within a timed run
+        assert_eq!(TIMINGS.with(|cell| cell.get()), None);
+    }
+
+    /// Test that a VP returning a value other than 0 or 1 is rejected with a
+    /// specific error rather than being accepted.
+    #[test]
+    fn test_vp_invalid_return_value() {
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (i64.const 2))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let result = execute_vp_with_code(vp_code);
+        assert!(
+            matches!(result, Err(Error::InvalidVpReturnValue(2))),
+            "Expected an invalid VP return value error, got {result:?}"
+        );
+    }
+
+    fn execute_tx_with_code(tx_code: Vec<u8>) -> Result<BTreeSet<Address>> {
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        execute_tx_with_code_and_gas_meter(tx_code, &mut gas_meter)
+    }
+
+    fn execute_tx_with_code_and_gas_meter(
+        tx_code: Vec<u8>,
+        gas_meter: &mut TxGasMeter,
+    ) -> Result<BTreeSet<Address>> {
+        execute_tx_with_code_and_memory_hint(tx_code, gas_meter, None)
+    }
+
+    fn execute_tx_with_code_and_memory_hint(
+        tx_code: Vec<u8>,
+        gas_meter: &mut TxGasMeter,
+        memory_pages_hint: Option<u32>,
+    ) -> Result<BTreeSet<Address>> {
+        let tx_data = vec![];
+        let tx_index = TxIndex::default();
+        let storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+
+        // store the tx code
+        let code_hash = Hash::sha256(&tx_code);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+        let key = Key::wasm_code(&code_hash);
+        let len_key = Key::wasm_code_len(&code_hash);
+        write_log.write(&key, tx_code).unwrap();
+        write_log.write(&len_key, code_len).unwrap();
+
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(tx_data));
+
+        tx_with_memory_hint(
+            &storage,
+            &mut write_log,
+            gas_meter,
+            &tx_index,
+            &outer_tx,
+            None,
+            &mut vp_cache,
+            &mut tx_cache,
+            memory_pages_hint,
+        )
+    }
+
+    fn loop_in_tx_wasm(loops: u32) -> Result<BTreeSet<Address>> {
+        // A transaction with a recursive loop.
+        // The boilerplate code is generated from tx_template.wasm using
+        // `wasm2wat` and the loop code is hand-written.
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (type (;0;) (func (param i64 i64)))
+
+                ;; recursive loop, the param is the number of loops
+                (func $loop (param i64) (result i64)
+                (if
+                (result i64)
+                (i64.eqz (get_local 0))
+                (then (get_local 0))
+                (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
+
+                (func $_apply_tx (type 0) (param i64 i64)
+                (call $loop (i64.const {}))
+                drop)
+
+                (table (;0;) 1 1 funcref)
+                (memory (;0;) 16)
+                (global (;0;) (mut i32) (i32.const 1048576))
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $_apply_tx)))
+            "#,
+                loops
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        execute_tx_with_code(tx_code)
+    }
+
+    fn loop_in_vp_wasm(loops: u32) -> Result<bool> {
+        // A validity predicate with a recursive loop.
+        // The boilerplate code is generated from vp_template.wasm using
+        // `wasm2wat` and the loop code is hand-written.
+        let vp_code = wasmer::wat2wasm(format!(
+            r#"
+            (module
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+
+                ;; recursive loop, the param is the number of loops
+                (func $loop (param i64) (result i64)
+                (if
+                (result i64)
+                (i64.eqz (get_local 0))
+                (then (get_local 0))
+                (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
+
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (call $loop (i64.const {})))
+
+                (table (;0;) 1 1 funcref)
+                (memory (;0;) 16)
+                (global (;0;) (mut i32) (i32.const 1048576))
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#, loops).as_bytes(),
+        )
+            .expect("unexpected error converting wat2wasm").into_owned();
+
+        execute_vp_with_code(vp_code)
+    }
+
+    fn execute_vp_with_code(vp_code: Vec<u8>) -> Result<bool> {
+        execute_vp_with_code_and_reason(vp_code).map(|(accept, _reason)| accept)
+    }
+
+    fn execute_vp_with_code_and_reason(
+        vp_code: Vec<u8>,
+    ) -> Result<(bool, Option<String>)> {
+        let outer_tx = Tx::from_type(TxType::Raw);
+        let tx_index = TxIndex::default();
+        let mut storage = TestStorage::default();
+        let addr = storage.address_gen.generate_address("rng seed");
+        let write_log = WriteLog::default();
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let keys_changed = BTreeSet::new();
+        let verifiers = BTreeSet::new();
+        let (vp_cache, _) = wasm::compilation_cache::common::testing::cache();
+        // store the vp code
+        let code_hash = Hash::sha256(&vp_code);
+        let code_len = (vp_code.len() as u64).serialize_to_vec();
+        let key = Key::wasm_code(&code_hash);
+        let len_key = Key::wasm_code_len(&code_hash);
+        storage.write(&key, vp_code).unwrap();
+        storage.write(&len_key, code_len).unwrap();
+
+        vp_with_rejection_reason(
+            code_hash,
+            &outer_tx,
+            &tx_index,
+            &addr,
             &storage,
             &write_log,
             &mut gas_meter,
@@ -1441,6 +3540,270 @@ mod tests {
         )
     }
 
+    /// Test that running a VP with the inputs from [`testing::VpRunBuilder`]
+    /// gives the same result as assembling them by hand.
+    #[test]
+    fn test_vp_run_builder() {
+        for (vp_code, expected) in [
+            (TestWasms::VpAlwaysTrue.read_bytes(), true),
+            (TestWasms::VpAlwaysFalse.read_bytes(), false),
+        ] {
+            let passed = execute_vp_with_code(vp_code.clone()).unwrap();
+            assert_eq!(passed, expected);
+            let passed =
+                testing::VpRunBuilder::default().run(&vp_code).unwrap();
+            assert_eq!(passed, expected);
+        }
+
+        // The inputs can be overridden, e.g. to run out of gas
+        let vp_code = TestWasms::VpAlwaysTrue.read_bytes();
+        let error = testing::VpRunBuilder::default()
+            .gas_limit(1)
+            .run(vp_code)
+            .expect_err("The VP should run out of gas");
+        assert!(matches!(error, Error::GasError(_)));
+    }
+
+    /// The lock to be held by the tests that change or depend on the
+    /// process-wide level of the host calls traces
+    static DEFAULT_LOG_LEVEL_LOCK: Mutex<()> = Mutex::new(());
+
+    /// Run a VP that reads a key with the given log level on its runner and
+    /// return whether the host call was traced.
+    fn vp_host_calls_traced(log_level: Option<LevelFilter>) -> bool {
+        /// A writer of the logs into a shared buffer
+        #[derive(Clone, Default)]
+        struct CapturedLogs(Arc<Mutex<Vec<u8>>>);
+
+        impl std::io::Write for CapturedLogs {
+            fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+                self.0.lock().unwrap().extend_from_slice(buf);
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> std::io::Result<()> {
+                Ok(())
+            }
+        }
+
+        let vp_code = wasmer::wat2wasm(
+            r#"
+            (module
+                (import "env" "namada_vp_read_pre" (func $read_pre (param i64 i64) (result i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
+                (func $_validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                (drop (call $read_pre (i64.const 524288) (i64.const 4)))
+                (i64.const 1))
+                (memory (;0;) 16)
+                (data (i32.const 524288) "key1")
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $_validate_tx)))
+            "#
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+
+        let logs = CapturedLogs::default();
+        let writer = logs.clone();
+        let subscriber = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::DEBUG)
+            .with_writer(move || writer.clone())
+            .finish();
+        let accept = tracing::subscriber::with_default(subscriber, || {
+            let builder = testing::VpRunBuilder::default();
+            match log_level {
+                Some(log_level) => builder.log_level(log_level),
+                None => builder,
+            }
+            .run(&vp_code)
+        })
+        .unwrap();
+        assert!(accept);
+        let logs = logs.0.lock().unwrap();
+        String::from_utf8_lossy(&logs).contains("vp_read_pre")
+    }
+
+    /// Test that the host calls traces of a VP are only emitted when the log
+    /// level configured on its runner permits them.
+    #[test]
+    fn test_vp_host_calls_log_level() {
+        assert!(vp_host_calls_traced(Some(LevelFilter::TRACE)));
+        assert!(vp_host_calls_traced(Some(LevelFilter::DEBUG)));
+        assert!(!vp_host_calls_traced(Some(LevelFilter::INFO)));
+        assert!(!vp_host_calls_traced(Some(LevelFilter::OFF)));
+    }
+
+    /// Test that the process-wide level of the host calls traces applies to
+    /// the runners without their own level.
+    #[test]
+    fn test_vp_host_calls_default_log_level() {
+        /// Restores the default level, even if the test fails
+        struct RestoreDefault;
+        impl Drop for RestoreDefault {
+            fn drop(&mut self) {
+                set_default_log_level(LevelFilter::TRACE);
+            }
+        }
+
+        let _lock = DEFAULT_LOG_LEVEL_LOCK
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner);
+        assert!(vp_host_calls_traced(None));
+
+        let _restore = RestoreDefault;
+        set_default_log_level(LevelFilter::INFO);
+        assert!(!vp_host_calls_traced(None));
+        // The level of the runner takes precedence
+        assert!(vp_host_calls_traced(Some(LevelFilter::DEBUG)));
+    }
+
+    /// Test that a tx executed against two equivalent storage backends with
+    /// [`testing::tx_tee`] doesn't diverge and that diverging backends are
+    /// flagged.
+    #[test]
+    fn test_tx_tee() {
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let key = Key::parse("key").unwrap();
+        let store_code = |write_log: &mut WriteLog, tx_code: Vec<u8>| {
+            let code_hash = Hash::sha256(&tx_code);
+            let code_len = (tx_code.len() as u64).serialize_to_vec();
+            write_log
+                .write(&Key::wasm_code(&code_hash), tx_code)
+                .unwrap();
+            write_log
+                .write(&Key::wasm_code_len(&code_hash), code_len)
+                .unwrap();
+            code_hash
+        };
+
+        // Two equivalent backends
+        let mut storage = TestStorage::default();
+        let mut reference_storage = TestStorage::default();
+        for storage in [&mut storage, &mut reference_storage] {
+            storage.write(&key, "existing".serialize_to_vec()).unwrap();
+        }
+        let mut write_log = WriteLog::default();
+        let code_hash = store_code(
+            &mut write_log,
+            TestWasms::TxWriteStorageKey.read_bytes(),
+        );
+        let tx_data = TxWriteData {
+            key: key.clone(),
+            value: b"value".to_vec(),
+        };
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(tx_data.serialize_to_vec()));
+
+        let result = testing::tx_tee(
+            &storage,
+            &reference_storage,
+            &mut write_log,
+            TX_GAS_LIMIT.into(),
+            &TxIndex::default(),
+            &outer_tx,
+            &mut vp_cache,
+            &mut tx_cache,
+        );
+        assert!(result.is_ok(), "Expected no divergence, got {:?}", result);
+        // The write log holds the changes of the tx
+        match write_log.read(&key).0 {
+            Some(StorageModification::Write { value }) => {
+                assert_eq!(value, b"value")
+            }
+            other => panic!("Unexpected modification {other:?}"),
+        }
+
+        // Diverging backends, where only one of them has the value that the
+        // tx reads
+        let mut storage = TestStorage::default();
+        storage.write(&key, vec![1_u8].serialize_to_vec()).unwrap();
+        let reference_storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let code_hash = store_code(
+            &mut write_log,
+            TestWasms::TxReadStorageKey.read_bytes(),
+        );
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(key.serialize_to_vec()));
+
+        let error = testing::tx_tee(
+            &storage,
+            &reference_storage,
+            &mut write_log,
+            TX_GAS_LIMIT.into(),
+            &TxIndex::default(),
+            &outer_tx,
+            &mut vp_cache,
+            &mut tx_cache,
+        )
+        .expect_err("Expected the executions to diverge");
+        assert!(
+            matches!(error, Error::Divergence(_)),
+            "Expected a divergence error, got {error}"
+        );
+    }
+
+    /// Test that a tx run with [`testing::tx_with_mocks`] gets the mocked
+    /// response of its read host call instead of the value in storage.
+    #[test]
+    fn test_tx_with_mocked_read() {
+        let storage = TestStorage::default();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let tx_code = TestWasms::TxReadStorageKey.read_bytes();
+        let code_hash = Hash::sha256(&tx_code);
+        let code_len = (tx_code.len() as u64).serialize_to_vec();
+        let mut write_log = WriteLog::default();
+        write_log
+            .write(&Key::wasm_code(&code_hash), tx_code)
+            .unwrap();
+        write_log
+            .write(&Key::wasm_code_len(&code_hash), code_len)
+            .unwrap();
+        // The tx fails when the key it reads has no value
+        let key = Key::parse("key").unwrap();
+        let mut outer_tx = Tx::from_type(TxType::Raw);
+        outer_tx.set_code(Code::from_hash(code_hash, None));
+        outer_tx.set_data(Data::new(key.serialize_to_vec()));
+        let mut run = |reads: BTreeMap<Key, Option<Vec<u8>>>| {
+            let mocks = testing::MockHostResponses {
+                reads,
+                ..Default::default()
+            };
+            testing::tx_with_mocks(
+                mocks,
+                &storage,
+                &mut write_log.clone(),
+                &mut TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+                &TxIndex::default(),
+                &outer_tx,
+                &mut vp_cache,
+                &mut tx_cache,
+            )
+        };
+
+        // The key is missing from the storage, but its read is mocked
+        let mocked_value = vec![1_u8].serialize_to_vec();
+        let result = run(BTreeMap::from([(key.clone(), Some(mocked_value))]));
+        assert!(result.is_ok(), "Expected success, got {:?}", result);
+
+        // A read mocked as a missing value fails the tx
+        let result = run(BTreeMap::from([(key, None)]));
+        assert!(result.is_err(), "Expected failure, got {:?}", result);
+
+        // Without the mocks, the read accesses the storage
+        let result = run(BTreeMap::new());
+        assert!(result.is_err(), "Expected failure, got {:?}", result);
+    }
+
     fn get_trap_code(error: &Error) -> Either<TrapCode, String> {
         if let Error::RuntimeError(err) = error {
             if let Some(trap_code) = err.clone().to_trap() {
@@ -1465,4 +3828,94 @@ mod tests {
                 Either::Left(wasmer_vm::TrapCode::StackOverflow),
         );
     }
+
+    /// Test that every host function registered in the wasm imports has its
+    /// gas rates in the host call gas schedule.
+    #[test]
+    fn test_host_call_gas_schedule_covers_imports() {
+        let schedule = crate::vm::host_env::host_call_gas_schedule();
+        let check_imports = |imports: wasmer::ImportObject| {
+            let exports = imports.get_namespace_exports("env").unwrap();
+            let mut host_calls = 0;
+            for (name, _) in exports.iter() {
+                if let Some(host_call) = name.strip_prefix("namada_") {
+                    assert!(
+                        schedule.contains_key(host_call),
+                        "Missing gas cost for {host_call}"
+                    );
+                    host_calls += 1;
+                }
+            }
+            assert!(host_calls > 0);
+        };
+
+        let mut storage = TestStorage::default();
+        let address = storage.address_gen.generate_address("rng seed");
+        let mut write_log = WriteLog::default();
+        let mut gas_meter = TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into());
+        let tx = Tx::from_type(TxType::Raw);
+        let tx_index = TxIndex::default();
+        let (mut vp_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+        let (mut tx_cache, _) =
+            wasm::compilation_cache::common::testing::cache();
+
+        let mut iterators: PrefixIterators<'_, _> = PrefixIterators::default();
+        let mut verifiers = BTreeSet::new();
+        let mut result_buffer: Option<Vec<u8>> = None;
+        let mut host_state = TxHostState::default();
+        let mut sentinel = TxSentinel::default();
+        let env = TxVmEnv::new(
+            WasmMemory::default(),
+            &storage,
+            &mut write_log,
+            &mut iterators,
+            &mut gas_meter,
+            &mut sentinel,
+            &tx,
+            &tx_index,
+            &None,
+            &mut verifiers,
+            &mut result_buffer,
+            &mut host_state,
+            &mut vp_cache,
+            &mut tx_cache,
+        );
+        let store = untrusted_wasm_store(memory::tx_limit());
+        let initial_memory = memory::prepare_tx_memory(&store).unwrap();
+        check_imports(tx_imports(&store, initial_memory, env));
+
+        let mut gas_meter = VpGasMeter::new_from_tx_meter(
+            &TxGasMeter::new_from_sub_limit(TX_GAS_LIMIT.into()),
+        );
+        let mut iterators: PrefixIterators<'_, _> = PrefixIterators::default();
+        let verifiers = BTreeSet::new();
+        let keys_changed = BTreeSet::new();
+        let mut result_buffer: Option<Vec<u8>> = None;
+        let mut sentinel = VpSentinel::default();
+        let eval_runner = VpEvalWasm {
+            db: PhantomData,
+            hasher: PhantomData,
+            cache_access: PhantomData,
+        };
+        let env = VpVmEnv::new(
+            WasmMemory::default(),
+            &address,
+            &storage,
+            &write_log,
+            &mut gas_meter,
+            &mut sentinel,
+            &tx,
+            &tx_index,
+            &mut iterators,
+            &verifiers,
+            &mut result_buffer,
+            &keys_changed,
+            &eval_runner,
+            &mut vp_cache,
+        );
+        let store = untrusted_wasm_store(memory::vp_limit());
+        let initial_memory = memory::prepare_vp_memory(&store).unwrap();
+        check_imports(vp_imports(&store, initial_memory, env));
+    }
 }
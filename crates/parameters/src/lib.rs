@@ -59,6 +59,11 @@ where
         implicit_vp_code_hash,
         epochs_per_year,
         max_signatures_per_transaction,
+        max_value_bytes,
+        max_writes,
+        max_events,
+        max_verifiers,
+        max_vp_previews,
         staked_ratio,
         pos_inflation_amount,
         minimum_gas_price,
@@ -95,6 +100,19 @@ where
         fee_unshielding_descriptions_limit,
     )?;
 
+    // write the limits of the storage writes, events, verifiers and VP
+    // previews of a tx
+    let max_value_bytes_key = storage::get_max_value_bytes_key();
+    storage.write(&max_value_bytes_key, max_value_bytes)?;
+    let max_writes_key = storage::get_max_writes_key();
+    storage.write(&max_writes_key, max_writes)?;
+    let max_events_key = storage::get_max_events_key();
+    storage.write(&max_events_key, max_events)?;
+    let max_verifiers_key = storage::get_max_verifiers_key();
+    storage.write(&max_verifiers_key, max_verifiers)?;
+    let max_vp_previews_key = storage::get_max_vp_previews_key();
+    storage.write(&max_vp_previews_key, max_vp_previews)?;
+
     // write vp allowlist parameter
     let vp_allowlist_key = storage::get_vp_allowlist_storage_key();
     let vp_allowlist = vp_allowlist
@@ -320,6 +338,99 @@ where
     Ok(gas_cost_table.get(token).map(|amount| amount.to_owned()))
 }
 
+/// Read the fee parameters from storage
+pub fn read_fee_parameters<S>(
+    storage: &S,
+) -> namada_storage::Result<FeeParameters>
+where
+    S: StorageRead,
+{
+    let max_block_gas: u64 = storage
+        .read(&storage::get_max_block_gas_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+    let fee_unshielding_gas_limit: u64 = storage
+        .read(&storage::get_fee_unshielding_gas_limit_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+    let fee_unshielding_descriptions_limit: u64 = storage
+        .read(&storage::get_fee_unshielding_descriptions_limit_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+    let minimum_gas_price: BTreeMap<Address, token::Amount> = storage
+        .read(&storage::get_gas_cost_key())?
+        .ok_or(ReadError::ParametersMissing)
+        .into_storage_result()?;
+
+    Ok(FeeParameters {
+        max_block_gas,
+        fee_unshielding_gas_limit,
+        fee_unshielding_descriptions_limit,
+        minimum_gas_price,
+    })
+}
+
+/// The subset of the protocol parameters limiting the storage writes, events,
+/// verifiers and VP previews of a transaction
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TxLimits {
+    /// Max size, in bytes, of a value written by a transaction
+    pub max_value_bytes: u64,
+    /// Max number of keys written by a transaction
+    pub max_writes: u64,
+    /// Max number of events emitted by a transaction
+    pub max_events: u64,
+    /// Max number of verifiers inserted by a transaction
+    pub max_verifiers: u64,
+    /// Max number of VP previews requested by a transaction
+    pub max_vp_previews: u64,
+}
+
+impl Default for TxLimits {
+    fn default() -> Self {
+        Self {
+            max_value_bytes: storage::DEFAULT_MAX_VALUE_BYTES,
+            max_writes: storage::DEFAULT_MAX_WRITES,
+            max_events: storage::DEFAULT_MAX_EVENTS,
+            max_verifiers: storage::DEFAULT_MAX_VERIFIERS,
+            max_vp_previews: storage::DEFAULT_MAX_VP_PREVIEWS,
+        }
+    }
+}
+
+/// Read the limits of the storage writes, events, verifiers and VP previews of
+/// a transaction from storage. A limit that is not set falls back to its
+/// default.
+pub fn read_tx_limits<S>(storage: &S) -> namada_storage::Result<TxLimits>
+where
+    S: StorageRead,
+{
+    let defaults = TxLimits::default();
+    let max_value_bytes: u64 = storage
+        .read(&storage::get_max_value_bytes_key())?
+        .unwrap_or(defaults.max_value_bytes);
+    let max_writes: u64 = storage
+        .read(&storage::get_max_writes_key())?
+        .unwrap_or(defaults.max_writes);
+    let max_events: u64 = storage
+        .read(&storage::get_max_events_key())?
+        .unwrap_or(defaults.max_events);
+    let max_verifiers: u64 = storage
+        .read(&storage::get_max_verifiers_key())?
+        .unwrap_or(defaults.max_verifiers);
+    let max_vp_previews: u64 = storage
+        .read(&storage::get_max_vp_previews_key())?
+        .unwrap_or(defaults.max_vp_previews);
+
+    Ok(TxLimits {
+        max_value_bytes,
+        max_writes,
+        max_events,
+        max_verifiers,
+        max_vp_previews,
+    })
+}
+
 /// Read all the parameters from storage. Returns the parameters and gas
 /// cost.
 pub fn read<S>(storage: &S) -> namada_storage::Result<Parameters>
@@ -409,6 +520,16 @@ where
         .ok_or(ReadError::ParametersMissing)
         .into_storage_result()?;
 
+    // read the limits of the storage writes, events, verifiers and VP
+    // previews of a tx
+    let TxLimits {
+        max_value_bytes,
+        max_writes,
+        max_events,
+        max_verifiers,
+        max_vp_previews,
+    } = read_tx_limits(storage)?;
+
     // read staked ratio
     let staked_ratio_key = storage::get_staked_ratio_key();
     let value = storage.read(&staked_ratio_key)?;
@@ -448,6 +569,11 @@ where
         implicit_vp_code_hash: Some(implicit_vp_code_hash),
         epochs_per_year,
         max_signatures_per_transaction,
+        max_value_bytes,
+        max_writes,
+        max_events,
+        max_verifiers,
+        max_vp_previews,
         staked_ratio,
         pos_inflation_amount,
         minimum_gas_price,
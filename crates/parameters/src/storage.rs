@@ -45,6 +45,11 @@ struct Keys {
     fee_unshielding_gas_limit: &'static str,
     fee_unshielding_descriptions_limit: &'static str,
     max_signatures_per_transaction: &'static str,
+    max_value_bytes: &'static str,
+    max_writes: &'static str,
+    max_events: &'static str,
+    max_verifiers: &'static str,
+    max_vp_previews: &'static str,
 }
 
 /// Returns if the key is a parameter key.
@@ -65,6 +70,17 @@ pub fn is_protocol_parameter_key(key: &Key) -> bool {
     Keys::ALL.binary_search(&segment).is_ok()
 }
 
+/// Storage key of the protocol parameter with the given name, if it's a known
+/// parameter.
+pub fn get_protocol_parameter_key(name: &str) -> Option<Key> {
+    Keys::ALL.binary_search(&name).ok().map(|_| Key {
+        segments: vec![
+            DbKeySeg::AddressSeg(ADDRESS),
+            DbKeySeg::StringSeg(name.to_string()),
+        ],
+    })
+}
+
 /// Returns if the key is an epoch storage key.
 pub fn is_epoch_duration_storage_key(key: &Key) -> bool {
     is_epoch_duration_key_at_addr(key, &ADDRESS)
@@ -190,6 +206,52 @@ pub fn get_max_signatures_per_transaction_key() -> Key {
     get_max_signatures_per_transaction_key_at_addr(ADDRESS)
 }
 
+/// Storage key used for the max bytes of a value written by a transaction
+pub fn get_max_value_bytes_key() -> Key {
+    get_max_value_bytes_key_at_addr(ADDRESS)
+}
+
+/// The max bytes of a value written by a transaction used when the
+/// `max_value_bytes` parameter is not set
+pub const DEFAULT_MAX_VALUE_BYTES: u64 = 1024 * 1024;
+
+/// Storage key used for the max number of keys written by a transaction
+pub fn get_max_writes_key() -> Key {
+    get_max_writes_key_at_addr(ADDRESS)
+}
+
+/// The max number of keys written by a transaction used when the `max_writes`
+/// parameter is not set
+pub const DEFAULT_MAX_WRITES: u64 = 10_000;
+
+/// Storage key used for the max number of events emitted by a transaction
+pub fn get_max_events_key() -> Key {
+    get_max_events_key_at_addr(ADDRESS)
+}
+
+/// The max number of events emitted by a transaction used when the
+/// `max_events` parameter is not set
+pub const DEFAULT_MAX_EVENTS: u64 = 1_000;
+
+/// Storage key used for the max number of verifiers inserted by a transaction
+pub fn get_max_verifiers_key() -> Key {
+    get_max_verifiers_key_at_addr(ADDRESS)
+}
+
+/// The max number of verifiers inserted by a transaction used when the
+/// `max_verifiers` parameter is not set
+pub const DEFAULT_MAX_VERIFIERS: u64 = 128;
+
+/// Storage key used for the max number of VP previews requested by a
+/// transaction
+pub fn get_max_vp_previews_key() -> Key {
+    get_max_vp_previews_key_at_addr(ADDRESS)
+}
+
+/// The max number of VP previews requested by a transaction used when the
+/// `max_vp_previews` parameter is not set
+pub const DEFAULT_MAX_VP_PREVIEWS: u64 = 16;
+
 /// Helper function to retrieve the `max_block_gas` protocol parameter from
 /// storage
 pub fn get_max_block_gas(
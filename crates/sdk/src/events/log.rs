@@ -2,11 +2,15 @@
 //!
 //! The log can only hold `N` events at a time, where `N` is a configurable
 //! parameter. If the log is holding `N` events, and a new event is logged,
-//! old events are pruned.
+//! old events are pruned. The keys changed by the applied txs are held in the
+//! log too and they're pruned in the same way.
 
+use std::collections::BTreeSet;
 use std::default::Default;
 
 use circular_queue::CircularQueue;
+use namada_core::types::hash::Hash;
+use namada_core::types::storage::Key;
 
 use crate::events::Event;
 
@@ -20,6 +24,9 @@ pub struct Params {
     /// If the number of events in the log exceeds this value, the log
     /// will be pruned.
     pub max_log_events: usize,
+    /// Limit on the maximum number of txs whose changed keys the event log
+    /// can hold.
+    pub max_log_tx_changed_keys: usize,
 }
 
 impl Default for Params {
@@ -27,6 +34,7 @@ impl Default for Params {
         // TODO: tune the default params
         Self {
             max_log_events: 50000,
+            max_log_tx_changed_keys: 10000,
         }
     }
 }
@@ -36,6 +44,7 @@ impl Default for Params {
 #[derive(Debug)]
 pub struct EventLog {
     queue: CircularQueue<Event>,
+    tx_changed_keys: CircularQueue<(Hash, BTreeSet<Key>)>,
 }
 
 impl Default for EventLog {
@@ -49,9 +58,32 @@ impl EventLog {
     pub fn new(params: Params) -> Self {
         Self {
             queue: CircularQueue::with_capacity(params.max_log_events),
+            tx_changed_keys: CircularQueue::with_capacity(
+                params.max_log_tx_changed_keys,
+            ),
         }
     }
 
+    /// Log the keys changed by a batch of applied txs, identified by their
+    /// hashes.
+    pub fn log_tx_changed_keys<K>(&mut self, tx_changed_keys: K)
+    where
+        K: IntoIterator<Item = (Hash, BTreeSet<Key>)>,
+    {
+        for entry in tx_changed_keys.into_iter() {
+            self.tx_changed_keys.push(entry);
+        }
+    }
+
+    /// Find the keys changed by the applied tx with the given hash, if they're
+    /// still held in the log.
+    pub fn tx_changed_keys(&self, hash: &Hash) -> Option<&BTreeSet<Key>> {
+        self.tx_changed_keys
+            .iter()
+            .find(|(tx_hash, _)| tx_hash == hash)
+            .map(|(_, changed_keys)| changed_keys)
+    }
+
     /// Log a new batch of events into the event log.
     pub fn log_events<E>(&mut self, events: E)
     where
@@ -164,6 +196,7 @@ mod tests {
 
         let mut log = EventLog::new(Params {
             max_log_events: LOG_CAP,
+            ..Default::default()
         });
 
         // completely fill the log with events
@@ -0,0 +1,194 @@
+//! A query client wrapper that caches the responses of the requests for the
+//! latest block height for a short duration.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use namada_core::types::storage::BlockHeight;
+
+use super::{Client, EncodedResponseQuery};
+use crate::control_flow::time::{Duration, Instant};
+use crate::tendermint_rpc::{self, Error as RpcError};
+use crate::MaybeSync;
+
+/// The key of a cached request - its path, data and whether a proof was
+/// requested
+type RequestKey = (String, Vec<u8>, bool);
+
+/// A [`Client`] that serves repeated requests for the latest block height
+/// from a cache, for as long as their responses are younger than the
+/// configured time-to-live. Once a response expires, the request is
+/// forwarded to the inner client again.
+///
+/// The responses to the latest block height may change with every new block,
+/// so the TTL should be kept short. The requests for a specific block height
+/// and the Tendermint RPC requests are never cached.
+pub struct CachingClient<C> {
+    /// The wrapped client
+    inner: C,
+    /// How long a cached response can be served for
+    ttl: Duration,
+    /// The cached responses with the time at which they were fetched
+    cache: Mutex<HashMap<RequestKey, (Instant, EncodedResponseQuery)>>,
+}
+
+impl<C> CachingClient<C> {
+    /// Wrap the given client with a cache of the given TTL
+    pub fn new(inner: C, ttl: Duration) -> Self {
+        Self {
+            inner,
+            ttl,
+            cache: Default::default(),
+        }
+    }
+
+    /// Get the wrapped client
+    pub fn inner(&self) -> &C {
+        &self.inner
+    }
+
+    /// Get a response from the cache, if it hasn't expired yet
+    fn get_cached(&self, key: &RequestKey) -> Option<EncodedResponseQuery> {
+        let mut cache = self.cache.lock().unwrap();
+        match cache.get(key) {
+            Some((fetched_at, response)) if fetched_at.elapsed() < self.ttl => {
+                Some(response.clone())
+            }
+            Some(_) => {
+                cache.remove(key);
+                None
+            }
+            None => None,
+        }
+    }
+}
+
+#[cfg_attr(feature = "async-send", async_trait::async_trait)]
+#[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+impl<C> Client for CachingClient<C>
+where
+    C: Client + MaybeSync,
+{
+    type Error = C::Error;
+
+    async fn request(
+        &self,
+        path: String,
+        data: Option<Vec<u8>>,
+        height: Option<BlockHeight>,
+        prove: bool,
+    ) -> Result<EncodedResponseQuery, Self::Error> {
+        // `0` means the latest block height
+        let is_latest = height.map_or(true, |height| height == BlockHeight(0));
+        if !is_latest {
+            return self.inner.request(path, data, height, prove).await;
+        }
+
+        let key = (path, data.unwrap_or_default(), prove);
+        if let Some(response) = self.get_cached(&key) {
+            return Ok(response);
+        }
+        let (path, data, prove) = key;
+        let response = self
+            .inner
+            .request(path.clone(), Some(data.clone()), height, prove)
+            .await?;
+        self.cache
+            .lock()
+            .unwrap()
+            .insert((path, data, prove), (Instant::now(), response.clone()));
+        Ok(response)
+    }
+
+    async fn perform<R>(&self, request: R) -> Result<R::Output, RpcError>
+    where
+        R: tendermint_rpc::SimpleRequest,
+    {
+        self.inner.perform(request).await
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::atomic::{AtomicU64, Ordering};
+
+    use tendermint_rpc::Response;
+
+    use super::*;
+    use crate::control_flow::time::sleep;
+
+    /// A client that counts the requests that reach it
+    #[derive(Default)]
+    struct CountingClient {
+        count: AtomicU64,
+    }
+
+    #[cfg_attr(feature = "async-send", async_trait::async_trait)]
+    #[cfg_attr(not(feature = "async-send"), async_trait::async_trait(?Send))]
+    impl Client for CountingClient {
+        type Error = std::io::Error;
+
+        async fn request(
+            &self,
+            _path: String,
+            _data: Option<Vec<u8>>,
+            _height: Option<BlockHeight>,
+            _prove: bool,
+        ) -> Result<EncodedResponseQuery, Self::Error> {
+            let count = self.count.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(EncodedResponseQuery {
+                data: count.to_le_bytes().to_vec(),
+                ..Default::default()
+            })
+        }
+
+        async fn perform<R>(&self, _request: R) -> Result<R::Output, RpcError>
+        where
+            R: tendermint_rpc::SimpleRequest,
+        {
+            Ok(R::Response::from_string("TODO").unwrap().into())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_caching_client_ttl() {
+        let ttl = Duration::from_millis(500);
+        let client = CachingClient::new(CountingClient::default(), ttl);
+        let count = || client.inner().count.load(Ordering::SeqCst);
+        let path = "/shell/epoch".to_string();
+
+        // The first request reaches the inner client ...
+        let first = client.request(path.clone(), None, None, false).await;
+        assert_eq!(count(), 1);
+
+        // ... and the repeated ones within the TTL are served from the cache
+        let repeated = client
+            .request(path.clone(), None, Some(BlockHeight(0)), false)
+            .await;
+        assert_eq!(count(), 1);
+        assert_eq!(first.unwrap().data, repeated.unwrap().data);
+
+        // Requests with different data or for a specific height are not
+        // served from the cache
+        client
+            .request(path.clone(), Some(vec![1]), None, false)
+            .await
+            .unwrap();
+        assert_eq!(count(), 2);
+        client
+            .request(path.clone(), None, Some(BlockHeight(1)), false)
+            .await
+            .unwrap();
+        client
+            .request(path.clone(), None, Some(BlockHeight(1)), false)
+            .await
+            .unwrap();
+        assert_eq!(count(), 4);
+
+        // After the TTL, the request is fetched again
+        sleep(ttl).await;
+        let refetched = client.request(path, None, None, false).await.unwrap();
+        assert_eq!(count(), 5);
+        assert_eq!(refetched.data, 5_u64.to_le_bytes().to_vec());
+    }
+}
@@ -2,13 +2,20 @@
 //! defined via `router!` macro.
 
 // Re-export to show in rustdoc!
+#[cfg(any(test, feature = "async-client"))]
+pub use caching::CachingClient;
 use namada_core::types::storage::BlockHeight;
 use namada_state::{DBIter, StorageHasher, DB};
-pub use shell::Shell;
+#[cfg(any(test, feature = "async-client"))]
+pub use router::TypedClient;
 use shell::SHELL;
+pub use shell::{
+    Shell, StorageValueChunk, TxSimulation, VersionInfo, PROTOCOL_VERSION,
+};
 pub use types::{
-    EncodedResponseQuery, Error, RequestCtx, RequestQuery, ResponseQuery,
-    Router,
+    EncodedResponseQuery, Error, RequestCtx, RequestQuery, RequestTiming,
+    RequestTimingHook, ResponseQuery, Router, StorageWitness, Witnessed,
+    DEFAULT_MAX_REQUEST_PATH_LEN,
 };
 use vp::{Vp, VP};
 
@@ -20,6 +27,8 @@ use crate::MaybeSend;
 
 #[macro_use]
 mod router;
+#[cfg(any(test, feature = "async-client"))]
+mod caching;
 mod shell;
 mod types;
 pub mod vp;
@@ -71,6 +80,20 @@ where
     Ok(())
 }
 
+/// For queries whose result must be consistent across multiple requests,
+/// check that a specific block height is given, otherwise return an error.
+pub fn require_pinned_height(
+    request: &RequestQuery,
+) -> namada_storage::Result<BlockHeight> {
+    if request.height.value() == 0 {
+        return Err(namada_storage::Error::new_const(
+            "This query requires a specific block height ('0' for the latest \
+             block height is not supported)",
+        ));
+    }
+    Ok(request.height.into())
+}
+
 /// For queries that do not support proofs, check that proof is not requested,
 /// otherwise return an error.
 pub fn require_no_proof(request: &RequestQuery) -> namada_storage::Result<()> {
@@ -184,6 +207,8 @@ mod testing {
                 vp_wasm_cache: (),
                 tx_wasm_cache: (),
                 storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: None,
             };
             // TODO: this is a hack to propagate errors to the caller, we should
             // really permit error types other than [`std::io::Error`]
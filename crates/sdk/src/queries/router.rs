@@ -13,6 +13,78 @@ use thiserror::Error;
 pub enum Error {
     #[error("Found no matching pattern for the given path {0}")]
     WrongPath(String),
+    #[cfg(feature = "router-diagnostics")]
+    #[error(
+        "Found no matching pattern for the given path {path}, the closest \
+         routes are {closest:?}"
+    )]
+    WrongPathWithClosest { path: String, closest: Vec<String> },
+}
+
+/// A segment of a route flattened from a router's patterns, used for the
+/// diagnostics of unmatched paths.
+#[cfg(feature = "router-diagnostics")]
+#[derive(Clone, Copy, Debug)]
+pub enum RouteSegment {
+    /// A literal string segment
+    Literal(&'static str),
+    /// A typed or untyped argument with its name
+    Arg(&'static str),
+    /// The rest of the path handled by a sub-router
+    SubRouter,
+}
+
+/// Find the routes whose leading segments match the most segments of the
+/// given path after the `start` index. Returns the routes joined with the
+/// already matched part of the path, or nothing when no route matches even
+/// the first segment.
+#[cfg(feature = "router-diagnostics")]
+pub fn closest_routes(
+    path: &str,
+    start: usize,
+    routes: &[Vec<RouteSegment>],
+) -> Vec<String> {
+    let segments: Vec<&str> = path
+        .get(start..)
+        .unwrap_or_default()
+        .trim_start_matches('/')
+        .split('/')
+        .collect();
+    let matched_len = |route: &[RouteSegment]| {
+        route
+            .iter()
+            .zip(&segments)
+            .take_while(|(route_segment, segment)| match route_segment {
+                RouteSegment::Literal(literal) => literal == *segment,
+                RouteSegment::Arg(_) => !segment.is_empty(),
+                RouteSegment::SubRouter => false,
+            })
+            .count()
+    };
+    let best = routes
+        .iter()
+        .map(|route| matched_len(route))
+        .max()
+        .unwrap_or_default();
+    if best == 0 {
+        return vec![];
+    }
+    let matched_prefix = path.get(..start).unwrap_or_default();
+    routes
+        .iter()
+        .filter(|route| matched_len(route) == best)
+        .map(|route| {
+            let route = itertools::join(
+                route.iter().map(|segment| match segment {
+                    RouteSegment::Literal(literal) => literal.to_string(),
+                    RouteSegment::Arg(arg) => format!("[{arg}]"),
+                    RouteSegment::SubRouter => "..".to_string(),
+                }),
+                "/",
+            );
+            format!("{matched_prefix}/{route}")
+        })
+        .collect()
 }
 
 /// Find the index of a next forward slash after the given `start` index in the
@@ -30,9 +102,161 @@ pub fn find_next_slash_index(path: &str, start: usize) -> usize {
         .unwrap_or(path.len())
 }
 
+/// Index the given fully literal paths of a router's patterns by their
+/// position. The patterns that are not fully literal are `None`.
+pub fn index_literal_routes(
+    routes: &[Option<&'static str>],
+) -> std::collections::HashMap<&'static str, usize> {
+    routes
+        .iter()
+        .enumerate()
+        .filter_map(|(index, route)| route.map(|route| (route, index)))
+        .collect()
+}
+
+/// Strip the optional trailing slash of the path left to be matched by a
+/// router's fully literal routes.
+pub fn literal_route_path(path: &str, start: usize) -> &str {
+    let path = path.get(start..).unwrap_or_default();
+    match path.strip_suffix('/') {
+        Some(stripped) if !stripped.is_empty() => stripped,
+        _ => path,
+    }
+}
+
+/// A router bound to a client with the router's `with_client` method. It has
+/// the same query methods as the router, but without the client argument.
+#[cfg(any(test, feature = "async-client"))]
+pub struct TypedClient<'a, R, C> {
+    /// The router that builds the query paths
+    pub router: R,
+    /// The client that the queries are sent with
+    pub client: &'a C,
+}
+
+/// Whether a handler supports requests with `prove` set, which is declared
+/// with the `with_proof` marker of a handler `with_options`.
+macro_rules! handler_supports_proof {
+    ((with_options $handle:ident $(: $body_ty:ty)?,with_proof)) => {
+        true
+    };
+    ($handle:tt) => {
+        false
+    };
+}
+
+/// The label of a route, which is the name of its handler.
+macro_rules! handler_label {
+    ((with_options $handle:ident $(: $body_ty:ty)? $(,with_proof)?)) => {
+        stringify!($handle)
+    };
+    ($handle:ident) => {
+        stringify!($handle)
+    };
+}
+
 /// Invoke the sub-handler or call the handler function with the matched
 /// arguments generated by `try_match_segments`.
+///
+/// When the `$ctx` is `supports_proof`, the handler is not called and instead,
+/// this only returns whether it supports requests with `prove` set. Similarly,
+/// when the `$ctx` is `route_label`, this only returns the label of the
+/// matched route and when the `$ctx` is `is_match`, this only returns `true`
+/// if the pattern matches, without descending into a sub-router.
 macro_rules! handle_match {
+    // Nested router, which takes any path under its pattern
+    (
+        is_match, $request:ident, $start:ident, $end:ident,
+        (sub $router:tt), ( $( $matched_args:ident, )* ),
+    ) => {
+        // not used anymore - silence the warning
+        let _ = ($start, $end);
+        // The args are only matched, the sub router is not invoked
+        $( let _ = $matched_args; )*
+        return true;
+    };
+
+    // Any handler function, checking that the path is fully matched
+    (
+        is_match, $request:ident, $start:ident, $end:ident,
+        $handle:tt, ( $( $matched_args:ident, )* ),
+    ) => {
+        // check that we're at the end of the path - trailing slash is optional
+        if !($end == $request.path.len() ||
+            // ignore trailing slashes
+            $end == $request.path.len() - 1 && &$request.path[$end..] == "/") {
+                // we're not at the end, no match
+                break
+        }
+        // The args are only matched, the handler is not called
+        $( let _ = $matched_args; )*
+        return true;
+    };
+
+    // Nested router, checking whether its matched handler supports proofs
+    (
+        supports_proof, $request:ident, $start:ident, $end:ident,
+        (sub $router:tt), ( $( $matched_args:ident, )* ),
+    ) => {
+        // not used anymore - silence the warning
+        let _ = $end;
+        // Undo last '/' advance, the next pattern has to start with `/`.
+        // This cannot underflow because path cannot be empty and must start
+        // with `/`
+        $start -= 1;
+        // Invoke `internal_supports_proof` on the sub router
+        return $router.internal_supports_proof($request, $start)
+    };
+
+    // Any handler function, checking whether it supports proofs
+    (
+        supports_proof, $request:ident, $start:ident, $end:ident,
+        $handle:tt, ( $( $matched_args:ident, )* ),
+    ) => {
+        // check that we're at the end of the path - trailing slash is optional
+        if !($end == $request.path.len() ||
+            // ignore trailing slashes
+            $end == $request.path.len() - 1 && &$request.path[$end..] == "/") {
+                // we're not at the end, no match
+                break
+        }
+        // The args are only matched, the handler is not called
+        $( let _ = $matched_args; )*
+        return Some(handler_supports_proof!($handle));
+    };
+
+    // Nested router, getting the label of its matched route
+    (
+        route_label, $request:ident, $start:ident, $end:ident,
+        (sub $router:tt), ( $( $matched_args:ident, )* ),
+    ) => {
+        // not used anymore - silence the warning
+        let _ = $end;
+        // Undo last '/' advance, the next pattern has to start with `/`.
+        // This cannot underflow because path cannot be empty and must start
+        // with `/`
+        $start -= 1;
+        // Invoke `internal_route_label` on the sub router
+        return $router.internal_route_label($request, $start)
+    };
+
+    // Any handler function, getting the label of its route
+    (
+        route_label, $request:ident, $start:ident, $end:ident,
+        $handle:tt, ( $( $matched_args:ident, )* ),
+    ) => {
+        // check that we're at the end of the path - trailing slash is optional
+        if !($end == $request.path.len() ||
+            // ignore trailing slashes
+            $end == $request.path.len() - 1 && &$request.path[$end..] == "/") {
+                // we're not at the end, no match
+                break
+        }
+        // The args are only matched, the handler is not called
+        $( let _ = $matched_args; )*
+        return Some(handler_label!($handle));
+    };
+
     // Nested router
     (
         $ctx:ident, $request:ident, $start:ident, $end:ident,
@@ -48,10 +272,36 @@ macro_rules! handle_match {
         return $router.internal_handle($ctx, $request, $start)
     };
 
+    // Handler function that uses a request (`with_options`) with a typed body
+    // decoded from the request data
+    (
+        $ctx:ident, $request:ident, $start:ident, $end:ident,
+        (with_options $handle:tt : $body_ty:ty $(, with_proof)?),
+        ( $( $matched_args:ident, )* ),
+    ) => {
+        // check that we're at the end of the path - trailing slash is optional
+        if !($end == $request.path.len() ||
+            // ignore trailing slashes
+            $end == $request.path.len() - 1 && &$request.path[$end..] == "/") {
+                // we're not at the end, no match
+                // println!("Not fully matched");
+                break
+        }
+        let body: $body_ty = borsh::BorshDeserialize::try_from_slice(&$request.data[..])
+            .wrap_err(concat!(
+                "Malformed request body of `", stringify!($handle),
+                "`, expected `", stringify!($body_ty), "`"
+            ))?;
+        let result = $handle($ctx, $request, body, $( $matched_args ),* )?;
+        // The handle must take care of encoding if needed and return `Vec<u8>`.
+        return Ok(result);
+    };
+
     // Handler function that uses a request (`with_options`)
     (
         $ctx:ident, $request:ident, $start:ident, $end:ident,
-        (with_options $handle:tt), ( $( $matched_args:ident, )* ),
+        (with_options $handle:tt $(, with_proof)?),
+        ( $( $matched_args:ident, )* ),
     ) => {
         // check that we're at the end of the path - trailing slash is optional
         if !($end == $request.path.len() ||
@@ -63,7 +313,8 @@ macro_rules! handle_match {
         }
         let result = $handle($ctx, $request, $( $matched_args ),* )?;
         // The handle must take care of encoding if needed and return `Vec<u8>`.
-        // This is because for `storage_value` the bytes are returned verbatim
+        // This is because for `storage_value` and `raw_value` the bytes are
+        // returned verbatim
         // as read from storage.
         return Ok(result);
     };
@@ -126,6 +377,8 @@ macro_rules! try_match_segments {
                 );
             }
         )*
+        // None of the sub-patterns matched, try to skip to the next pattern
+        break;
     };
 
     // Terminal tail call, invoked after when all the args in the current
@@ -194,6 +447,42 @@ macro_rules! try_match_segments {
             ( $( $matched_args, )* $arg, ), ( $( $( $tail )/ * )? ) );
     };
 
+    // Try to match and parse a typed argument like the case above, but fall
+    // back to the default value instead of `None`.
+    // Declares the expected $arg into type $t, if it can be parsed, or
+    // initializes it with the $default expression.
+    (
+        $ctx:ident, $request:ident, $start:ident, $end:ident, $handle:tt,
+        ( $( $matched_args:ident, )* ),
+        (
+            [$arg:ident : $arg_ty:ty = $default:expr]
+            $( / $( $tail:tt)/ * )?
+        )
+    ) => {
+        let $arg: $arg_ty = match $request.path[$start..$end].parse::<$arg_ty>() {
+            Ok(parsed) => {
+                // Only advance if the argument is present, otherwise stay
+                // in the same position for the next match, if any.
+
+                $start = $end;
+                // advance past next '/', if any
+                if $start + 1 < $request.path.len() {
+                    $start += 1;
+                }
+                $end = find_next_slash_index(&$request.path, $start);
+
+                parsed
+            },
+            Err(_) =>
+            {
+                // If arg cannot be parsed, use the default value
+                $default
+            }
+        };
+        try_match_segments!($ctx, $request, $start, $end, $handle,
+            ( $( $matched_args, )* $arg, ), ( $( $( $tail )/ * )? ) );
+    };
+
     // Special case of the typed argument pattern below. When there are no more
     // args in the tail and the handle isn't a sub-router (its handler is
     // ident), we try to match the rest of the path till the end.
@@ -208,7 +497,7 @@ macro_rules! try_match_segments {
         $handle:ident,
         ( $( $matched_args:ident, )* ),
         (
-            [$arg:ident : $arg_ty:ty]
+            [$arg:ident : $arg_ty:ty $( where $validate:expr )?]
         )
     ) => {
         let $arg: $arg_ty;
@@ -216,6 +505,13 @@ macro_rules! try_match_segments {
         match $request.path[$start..$end].parse::<$arg_ty>() {
             Ok(parsed) => {
                 // println!("Parsed {}", parsed);
+                $(
+                    // If the parsed arg is not valid, try to skip to next
+                    // pattern
+                    if !($validate)(&parsed) {
+                        break
+                    }
+                )?
                 $arg = parsed
             },
             Err(_) =>
@@ -240,10 +536,10 @@ macro_rules! try_match_segments {
     // type $t, if it can be parsed
     (
         $ctx:ident, $request:ident, $start:ident, $end:ident,
-        (with_options $handle:ident),
+        (with_options $handle:ident $( : $body_ty:ty )? $( , $proof:ident )?),
         ( $( $matched_args:ident, )* ),
         (
-            [$arg:ident : $arg_ty:ty]
+            [$arg:ident : $arg_ty:ty $( where $validate:expr )?]
         )
     ) => {
         let $arg: $arg_ty;
@@ -251,6 +547,13 @@ macro_rules! try_match_segments {
         match $request.path[$start..$end].parse::<$arg_ty>() {
             Ok(parsed) => {
                 // println!("Parsed {}", parsed);
+                $(
+                    // If the parsed arg is not valid, try to skip to next
+                    // pattern
+                    if !($validate)(&parsed) {
+                        break
+                    }
+                )?
                 $arg = parsed
             },
             Err(_) =>
@@ -261,23 +564,31 @@ macro_rules! try_match_segments {
             }
         }
         // Invoke the terminal pattern
-        try_match_segments!($ctx, $request, $start, $end, (with_options $handle),
+        try_match_segments!($ctx, $request, $start, $end,
+            (with_options $handle $( : $body_ty )? $( , $proof )?),
             ( $( $matched_args, )* $arg, ), () );
     };
 
     // Try to match and parse a typed argument, declares the expected $arg into
-    // type $t, if it can be parsed
+    // type $t, if it can be parsed and it passes the optional validation
     (
         $ctx:ident, $request:ident, $start:ident, $end:ident, $handle:tt,
         ( $( $matched_args:ident, )* ),
         (
-            [$arg:ident : $arg_ty:ty]
+            [$arg:ident : $arg_ty:ty $( where $validate:expr )?]
             $( / $( $tail:tt)/ * )?
         )
     ) => {
         let $arg: $arg_ty;
         match $request.path[$start..$end].parse::<$arg_ty>() {
             Ok(parsed) => {
+                $(
+                    // If the parsed arg is not valid, try to skip to next
+                    // pattern
+                    if !($validate)(&parsed) {
+                        break
+                    }
+                )?
                 $arg = parsed
             },
             Err(_) =>
@@ -356,6 +667,123 @@ macro_rules! try_match {
     };
 }
 
+/// Flatten the patterns of a router with their sub-patterns into a
+/// `Vec<Vec<RouteSegment>>` of all its routes.
+#[cfg(feature = "router-diagnostics")]
+macro_rules! router_routes {
+    // pattern with sub-patterns
+    (
+        [ $( $prefix:tt )* ] $pattern:tt =
+        { $( $sub_pattern:tt $( -> $_sub_return_ty:path )? = $sub_handle:tt, )* }
+    ) => {{
+        let mut routes = Vec::new();
+        $(
+            routes.extend(router_routes!(
+                [ $( $prefix )* $pattern ] $sub_pattern = $sub_handle
+            ));
+        )*
+        routes
+    }};
+
+    // sub-router, which matches the rest of the path
+    ( [ $( $prefix:tt )* ] $pattern:tt = (sub $router:tt) ) => {{
+        let mut route = Vec::new();
+        $( route.extend_from_slice(route_segments!($prefix)); )*
+        route.extend_from_slice(route_segments!($pattern));
+        route.push($crate::queries::router::RouteSegment::SubRouter);
+        vec![route]
+    }};
+
+    // handler
+    ( [ $( $prefix:tt )* ] $pattern:tt = $handle:tt ) => {{
+        let mut route = Vec::new();
+        $( route.extend_from_slice(route_segments!($prefix)); )*
+        route.extend_from_slice(route_segments!($pattern));
+        vec![route]
+    }};
+}
+
+/// Convert a pattern into a `&[RouteSegment]`
+#[cfg(feature = "router-diagnostics")]
+macro_rules! route_segments {
+    ( ( $( $segment:tt )/ * ) ) => {
+        &[ $( route_segment!($segment) ),* ]
+    };
+}
+
+/// Convert a segment of a pattern into a `RouteSegment`
+#[cfg(feature = "router-diagnostics")]
+macro_rules! route_segment {
+    ($literal:literal) => {
+        $crate::queries::router::RouteSegment::Literal($literal)
+    };
+    ([$arg:ident $($_rest:tt)*]) => {
+        $crate::queries::router::RouteSegment::Arg(stringify!($arg))
+    };
+}
+
+/// For the fully literal patterns with a handler, this generates the full
+/// path joined from their literal segments for the router's fast path. With
+/// the `dispatch` prefix, it generates a call of the pattern's handler when
+/// the pattern's position is the one found with the fast path. The patterns
+/// with any dynamic segment, sub-patterns or a sub-router are skipped.
+macro_rules! literal_route {
+    // the path of a sub-router, which matches the rest of the path
+    ( ( $( $segment:literal )/ * ) = (sub $router:tt) ) => {
+        None
+    };
+
+    // the path of a pattern with sub-patterns
+    ( ( $( $segment:literal )/ * ) = { $( $sub:tt )* } ) => {
+        None
+    };
+
+    // the path of a fully literal pattern with a handler
+    ( ( $( $segment:literal )/ * ) = $handle:tt ) => {
+        Some(concat!( $( "/", $segment ),* ))
+    };
+
+    // the path of a pattern with dynamic segments
+    ( $pattern:tt = $handle:tt ) => {
+        None
+    };
+
+    // dispatch a sub-router, not on the fast path
+    (
+        dispatch $ctx:ident, $request:ident, $start:ident, $index:ident,
+        $route_index:ident, ( $( $segment:literal )/ * ) = (sub $router:tt)
+    ) => {};
+
+    // dispatch sub-patterns, not on the fast path
+    (
+        dispatch $ctx:ident, $request:ident, $start:ident, $index:ident,
+        $route_index:ident, ( $( $segment:literal )/ * ) = { $( $sub:tt )* }
+    ) => {};
+
+    // dispatch a fully literal pattern's handler
+    (
+        dispatch $ctx:ident, $request:ident, $start:ident, $index:ident,
+        $route_index:ident, ( $( $segment:literal )/ * ) = $handle:tt
+    ) => {
+        if $index == $route_index {
+            // This loop never repeats, it's only used for the `break`s of an
+            // unmatched pattern, which falls through to the slow path
+            loop {
+                let mut $start = $start;
+                try_match!(
+                    $ctx, $request, $start, $handle, ( $( $segment )/ * )
+                );
+            }
+        }
+    };
+
+    // dispatch a pattern with dynamic segments, not on the fast path
+    (
+        dispatch $ctx:ident, $request:ident, $start:ident, $index:ident,
+        $route_index:ident, $pattern:tt = $handle:tt
+    ) => {};
+}
+
 /// Convert literal pattern into a `&[&'static str]`
 // TODO sub router pattern is not yet used
 #[allow(unused_macros)]
@@ -371,35 +799,82 @@ macro_rules! pattern_to_prefix {
 /// Turn patterns and their handlers into methods for the router, where each
 /// dynamic pattern is turned into a parameter for the method.
 macro_rules! pattern_and_handler_to_method {
-    // Special terminal rule for `storage_value` handle from
-    // `namada/src/ledger/queries/shell.rs` that returns `Vec<u8>` which should
-    // not be decoded from response.data, but instead return as is
+    // Special terminal rules for the `storage_value` and `raw_value` handles
+    // from `namada/src/ledger/queries/shell.rs` that return `Vec<u8>` which
+    // should not be decoded from response.data, but instead return as is
     (
+        method
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $return_type:path,
-        (with_options storage_value),
+        (with_options storage_value $(, with_proof)?),
         ()
+    ) => {
+        pattern_and_handler_to_method!(
+            verbatim_method
+            ( $( $param: $param_ty ),* )
+            [ $( { $prefix } ),* ]
+            storage_value
+        );
+    };
+    (
+        method
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options raw_value $(, with_proof)?),
+        ()
+    ) => {
+        pattern_and_handler_to_method!(
+            verbatim_method
+            ( $( $param: $param_ty ),* )
+            [ $( { $prefix } ),* ]
+            raw_value
+        );
+    };
+    (
+        verbatim_method
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $handle:ident
     ) => {
         // paste! used to construct the `fn $handle_path`'s name.
         paste::paste! {
             #[allow(dead_code)]
-            #[doc = "Get a path to query `storage_value`."]
-            pub fn storage_value_path(&self, $( $param: &$param_ty ),* ) -> String {
+            #[doc = "Get a path to query `" $handle "`."]
+            pub fn [<$handle _path>](&self, $( $param: &$param_ty ),* ) -> String {
                 itertools::join(
                     [ Some(std::borrow::Cow::from(&self.prefix)), $( $prefix ),* ]
                     .into_iter()
                     .filter_map(|x| x), "/")
             }
 
+            #[allow(dead_code)]
+            #[doc = "Get a URL to query `" $handle "` with the block height \
+                (if any) and whether a proof is requested in its query \
+                string."]
+            pub fn [<$handle _url>](&self,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            ) -> String {
+                let path = self.[<$handle _path>]( $( $param ),* );
+                match height {
+                    Some(height) => {
+                        format!("{path}?height={height}&prove={prove}")
+                    }
+                    None => format!("{path}?prove={prove}"),
+                }
+            }
+
             #[allow(dead_code)]
             #[allow(clippy::too_many_arguments)]
             #[cfg(any(test, feature = "async-client"))]
             #[doc = "Request value with optional data (used for e.g. \
                 `dry_run_tx`), optionally specified height (supported for \
                 `storage_value`) and optional proof (supported for \
-                `storage_value` and `storage_prefix`) from `storage_value`."]
-            pub async fn storage_value<CLIENT>(&self, client: &CLIENT,
+                `storage_value` and `storage_prefix`) from `" $handle "`."]
+            pub async fn $handle<CLIENT>(&self, client: &CLIENT,
                 data: Option<Vec<u8>>,
                 height: Option<namada_core::types::storage::BlockHeight>,
                 prove: bool,
@@ -410,7 +885,7 @@ macro_rules! pattern_and_handler_to_method {
                     <CLIENT as $crate::queries::Client>::Error
                 >
                 where CLIENT: $crate::queries::Client + std::marker::Sync {
-                    let path = self.storage_value_path( $( $param ),* );
+                    let path = self.[<$handle _path>]( $( $param ),* );
 
                     let $crate::queries::ResponseQuery {
                         data, info, proof
@@ -425,12 +900,87 @@ macro_rules! pattern_and_handler_to_method {
         }
     };
 
+    // terminal rule for $handle that uses request (`with_options`) with a
+    // typed body
+    (
+        method
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options $handle:tt : $body_ty:ty $(, with_proof)?),
+        ()
+    ) => {
+        // paste! used to construct the `fn $handle_path`'s name.
+        paste::paste! {
+            #[allow(dead_code)]
+            #[doc = "Get a path to query `" $handle "`."]
+            pub fn [<$handle _path>](&self, $( $param: &$param_ty ),* ) -> String {
+                itertools::join(
+                    [ Some(std::borrow::Cow::from(&self.prefix)), $( $prefix ),* ]
+                    .into_iter()
+                    .filter_map(|x| x), "/")
+            }
+
+            #[allow(dead_code)]
+            #[doc = "Get a URL to query `" $handle "` with the block height \
+                (if any) and whether a proof is requested in its query \
+                string."]
+            pub fn [<$handle _url>](&self,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            ) -> String {
+                let path = self.[<$handle _path>]( $( $param ),* );
+                match height {
+                    Some(height) => {
+                        format!("{path}?height={height}&prove={prove}")
+                    }
+                    None => format!("{path}?prove={prove}"),
+                }
+            }
+
+            #[allow(dead_code)]
+            #[allow(clippy::too_many_arguments)]
+            #[cfg(any(test, feature = "async-client"))]
+            #[doc = "Request value with a borsh-encoded body, optionally \
+                specified height and optional proof from `" $handle "`."]
+            pub async fn $handle<CLIENT>(&self, client: &CLIENT,
+                body: &$body_ty,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            )
+                -> std::result::Result<
+                    $crate::queries::ResponseQuery<$return_type>,
+                    <CLIENT as $crate::queries::Client>::Error
+                >
+                where CLIENT: $crate::queries::Client + std::marker::Sync {
+                    let path = self.[<$handle _path>]( $( $param ),* );
+                    let data = borsh::to_vec(body)?;
+
+                    let $crate::queries::ResponseQuery {
+                        data, info, proof
+                    } = client.request(path, Some(data), height, prove).await?;
+
+                    let decoded: $return_type =
+                        borsh::BorshDeserialize::try_from_slice(&data[..])?;
+
+                    Ok($crate::queries::ResponseQuery {
+                        data: decoded,
+                        info,
+                        proof,
+                    })
+            }
+        }
+    };
+
     // terminal rule for $handle that uses request (`with_options`)
     (
+        method
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $return_type:path,
-        (with_options $handle:tt),
+        (with_options $handle:tt $(, with_proof)?),
         ()
     ) => {
         // paste! used to construct the `fn $handle_path`'s name.
@@ -444,6 +994,24 @@ macro_rules! pattern_and_handler_to_method {
                     .filter_map(|x| x), "/")
             }
 
+            #[allow(dead_code)]
+            #[doc = "Get a URL to query `" $handle "` with the block height \
+                (if any) and whether a proof is requested in its query \
+                string."]
+            pub fn [<$handle _url>](&self,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            ) -> String {
+                let path = self.[<$handle _path>]( $( $param ),* );
+                match height {
+                    Some(height) => {
+                        format!("{path}?height={height}&prove={prove}")
+                    }
+                    None => format!("{path}?prove={prove}"),
+                }
+            }
+
             #[allow(dead_code)]
             #[allow(clippy::too_many_arguments)]
             #[cfg(any(test, feature = "async-client"))]
@@ -482,6 +1050,7 @@ macro_rules! pattern_and_handler_to_method {
 
     // terminal rule that $handle that doesn't use request
     (
+        method
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $return_type:path,
@@ -524,8 +1093,154 @@ macro_rules! pattern_and_handler_to_method {
         }
     };
 
+    // Terminal rules for the methods of a `TypedClient`, which forward to the
+    // router's methods with the bound client.
+
+    // typed client rules for `storage_value` and `raw_value`
+    (
+        typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options storage_value $(, with_proof)?),
+        ()
+    ) => {
+        pattern_and_handler_to_method!(
+            verbatim_typed_client
+            ( $( $param: $param_ty ),* )
+            storage_value
+        );
+    };
+    (
+        typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options raw_value $(, with_proof)?),
+        ()
+    ) => {
+        pattern_and_handler_to_method!(
+            verbatim_typed_client
+            ( $( $param: $param_ty ),* )
+            raw_value
+        );
+    };
+    (
+        verbatim_typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        $handle:ident
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            #[allow(clippy::too_many_arguments)]
+            #[doc = "Request value from `" $handle "` with the bound client."]
+            pub async fn $handle(&self,
+                data: Option<Vec<u8>>,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            )
+                -> std::result::Result<
+                    $crate::queries::ResponseQuery<Vec<u8>>,
+                    <C as $crate::queries::Client>::Error
+                > {
+                    self.router
+                        .$handle(self.client, data, height, prove, $( $param ),*)
+                        .await
+            }
+        }
+    };
+
+    // typed client rule for $handle that uses request (`with_options`) with a
+    // typed body
+    (
+        typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options $handle:tt : $body_ty:ty $(, with_proof)?),
+        ()
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            #[allow(clippy::too_many_arguments)]
+            #[doc = "Request value with a borsh-encoded body from `" $handle "` \
+                with the bound client."]
+            pub async fn $handle(&self,
+                body: &$body_ty,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            )
+                -> std::result::Result<
+                    $crate::queries::ResponseQuery<$return_type>,
+                    <C as $crate::queries::Client>::Error
+                > {
+                    self.router
+                        .$handle(self.client, body, height, prove, $( $param ),*)
+                        .await
+            }
+        }
+    };
+
+    // typed client rule for $handle that uses request (`with_options`)
+    (
+        typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        (with_options $handle:tt $(, with_proof)?),
+        ()
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            #[allow(clippy::too_many_arguments)]
+            #[doc = "Request value with optional data from `" $handle "` with \
+                the bound client."]
+            pub async fn $handle(&self,
+                data: Option<Vec<u8>>,
+                height: Option<namada_core::types::storage::BlockHeight>,
+                prove: bool,
+                $( $param: &$param_ty ),*
+            )
+                -> std::result::Result<
+                    $crate::queries::ResponseQuery<$return_type>,
+                    <C as $crate::queries::Client>::Error
+                > {
+                    self.router
+                        .$handle(self.client, data, height, prove, $( $param ),*)
+                        .await
+            }
+        }
+    };
+
+    // typed client rule for $handle that doesn't use request
+    (
+        typed_client
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $return_type:path,
+        $handle:tt,
+        ()
+    ) => {
+        paste::paste! {
+            #[allow(dead_code)]
+            #[allow(clippy::too_many_arguments)]
+            #[doc = "Request a simple borsh-encoded value from `" $handle "` \
+                with the bound client."]
+            pub async fn $handle(&self, $( $param: &$param_ty ),*)
+                -> std::result::Result<
+                    $return_type,
+                    <C as $crate::queries::Client>::Error
+                > {
+                    self.router.$handle(self.client, $( $param ),*).await
+            }
+        }
+    };
+
     // sub-pattern
     (
+        $mode:ident
         $param:tt
         $prefix:tt
         $( $_return_type:path )?,
@@ -535,6 +1250,7 @@ macro_rules! pattern_and_handler_to_method {
         $(
             // join pattern with each sub-pattern
             pattern_and_handler_to_method!(
+                $mode
                 $param
                 $prefix
                 $( $sub_return_ty )?, $handle, $pattern, $sub_pattern
@@ -544,6 +1260,7 @@ macro_rules! pattern_and_handler_to_method {
 
     // literal string arg
     (
+        $mode:ident
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $( $return_type:path )?,
@@ -551,6 +1268,7 @@ macro_rules! pattern_and_handler_to_method {
         ( $pattern:literal $( / $tail:tt )* )
     ) => {
         pattern_and_handler_to_method!(
+            $mode
             ( $( $param: $param_ty ),* )
             [ $( { $prefix }, )* { std::option::Option::Some(std::borrow::Cow::from($pattern)) } ]
             $( $return_type )?, $handle, ( $( $tail )/ * )
@@ -559,6 +1277,7 @@ macro_rules! pattern_and_handler_to_method {
 
     // untyped arg
     (
+        $mode:ident
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $( $return_type:path )?,
@@ -566,36 +1285,59 @@ macro_rules! pattern_and_handler_to_method {
         ( [$name:tt] $( / $tail:tt )* )
     ) => {
         pattern_and_handler_to_method!(
+            $mode
             ( $( $param: $param_ty, )* $name: str )
             [ $( { $prefix }, )* { std::option::Option::Some(std::borrow::Cow::from($name)) } ]
             $( $return_type )?, $handle, ( $( $tail )/ * )
         );
     };
 
-    // typed arg
+    // typed arg
+    (
+        $mode:ident
+        ( $( $param:tt: $param_ty:ty ),* )
+        [ $( { $prefix:expr } ),* ]
+        $( $return_type:path )?,
+        $handle:tt,
+        ( [$name:tt: $type:ty $( where $_validate:expr )?] $( / $tail:tt )* )
+    ) => {
+        pattern_and_handler_to_method!(
+            $mode
+            ( $( $param: $param_ty, )* $name: $type )
+            [ $( { $prefix }, )* { std::option::Option::Some(std::borrow::Cow::from($name.to_string())) } ]
+            $( $return_type )?, $handle, ( $( $tail )/ * )
+        );
+    };
+
+    // opt typed arg
     (
+        $mode:ident
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $( $return_type:path )?,
         $handle:tt,
-        ( [$name:tt: $type:ty] $( / $tail:tt )* )
+        ( [$name:tt: opt $type:ty] $( / $tail:tt )* )
     ) => {
         pattern_and_handler_to_method!(
-            ( $( $param: $param_ty, )* $name: $type )
-            [ $( { $prefix }, )* { std::option::Option::Some(std::borrow::Cow::from($name.to_string())) } ]
+            $mode
+            ( $( $param: $param_ty, )* $name: std::option::Option<$type> )
+            [ $( { $prefix }, )* { $name.as_ref().map(|arg| std::borrow::Cow::from(arg.to_string())) } ]
             $( $return_type )?, $handle, ( $( $tail )/ * )
         );
     };
 
-    // opt typed arg
+    // typed arg with a default value - skipped from the path when `None`, in
+    // which case the router uses the default
     (
+        $mode:ident
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $( $return_type:path )?,
         $handle:tt,
-        ( [$name:tt: opt $type:ty] $( / $tail:tt )* )
+        ( [$name:tt: $type:ty = $_default:expr] $( / $tail:tt )* )
     ) => {
         pattern_and_handler_to_method!(
+            $mode
             ( $( $param: $param_ty, )* $name: std::option::Option<$type> )
             [ $( { $prefix }, )* { $name.as_ref().map(|arg| std::borrow::Cow::from(arg.to_string())) } ]
             $( $return_type )?, $handle, ( $( $tail )/ * )
@@ -604,6 +1346,7 @@ macro_rules! pattern_and_handler_to_method {
 
     // join pattern with sub-pattern
     (
+        $mode:ident
         ( $( $param:tt: $param_ty:ty ),* )
         [ $( { $prefix:expr } ),* ]
         $( $return_type:path )?,
@@ -611,6 +1354,7 @@ macro_rules! pattern_and_handler_to_method {
         ( $( $pattern:tt )/ * ), ( $( $sub_pattern:tt )/ * )
     ) => {
         pattern_and_handler_to_method!(
+            $mode
             ( $( $param: $param_ty ),* )
             [ $( { $prefix }, )* ]
             $( $return_type )?,
@@ -620,10 +1364,14 @@ macro_rules! pattern_and_handler_to_method {
 }
 
 /// TT muncher macro that generates a `struct $name` with methods for all its
-/// handlers.
+/// handlers and the same methods for a [`TypedClient`] over it.
 macro_rules! router_type {
     // terminal rule
-    ($name:ident { $( $methods:item )* }, ) => {
+    (
+        $name:ident
+        { $( $methods:item )* }
+        { $( $client_methods:item )* },
+    ) => {
         paste::paste! {
             #[doc = "`" $name "`path router type"]
             pub struct $name {
@@ -646,15 +1394,39 @@ macro_rules! router_type {
                     }
                 }
 
+                #[allow(dead_code)]
+                #[cfg(any(test, feature = "async-client"))]
+                #[doc = "Bind this router to the given client"]
+                pub fn with_client<'a, C>(
+                    &self,
+                    client: &'a C,
+                ) -> $crate::queries::router::TypedClient<'a, Self, C> {
+                    $crate::queries::router::TypedClient {
+                        router: Self::sub(self.prefix.clone()),
+                        client,
+                    }
+                }
+
                 // paste the generated methods
                 $( $methods )*
             }
+
+            #[cfg(any(test, feature = "async-client"))]
+            impl<'a, C> $crate::queries::router::TypedClient<'a, $name, C>
+            where
+                C: $crate::queries::Client + std::marker::Sync,
+            {
+                // paste the generated client methods
+                $( $client_methods )*
+            }
         }
     };
 
     // a sub router - recursion
     (
-        $name:ident { $( $methods:item )* },
+        $name:ident
+        { $( $methods:item )* }
+        { $( $client_methods:item )* },
         $pattern:tt = (sub $router:ident)
         $( ,$tail_pattern:tt $( -> $tail_return_type:path )? = $tail:tt )*
     ) => {
@@ -669,6 +1441,13 @@ macro_rules! router_type {
                         [<$router:camel>]::sub(path)
                     }
                     $( $methods )*
+                } {
+                    #[doc = "`" $name "` sub-router with the bound client"]
+                    pub fn [<$router:camel:snake>](&self)
+                        -> $crate::queries::router::TypedClient<'a, [<$router:camel>], C> {
+                        self.router.[<$router:camel:snake>]().with_client(self.client)
+                    }
+                    $( $client_methods )*
                 },
                 $( $tail_pattern $( -> $tail_return_type )? = $tail ),*
             }
@@ -678,7 +1457,8 @@ macro_rules! router_type {
     // a sub-pattern - add a method for each handle inside it
     (
         $name:ident
-        { $( $methods:item )* },
+        { $( $methods:item )* }
+        { $( $client_methods:item )* },
         $pattern:tt = { $( $sub_pattern:tt $( -> $sub_return_ty:path )? = $handle:tt, )* }
         $( ,$tail_pattern:tt $( -> $tail_return_type:path )? = $tail:tt )*
     ) => {
@@ -686,11 +1466,18 @@ macro_rules! router_type {
             $name {
                 $(
                     // join pattern with each sub-pattern
-                    pattern_and_handler_to_method!( () [] $( $sub_return_ty )?, $handle,
-                        $pattern, $sub_pattern
+                    pattern_and_handler_to_method!( method () [] $( $sub_return_ty )?,
+                        $handle, $pattern, $sub_pattern
                     );
                 )*
                 $( $methods )*
+            } {
+                $(
+                    pattern_and_handler_to_method!( typed_client () [] $( $sub_return_ty )?,
+                        $handle, $pattern, $sub_pattern
+                    );
+                )*
+                $( $client_methods )*
             },
             $( $tail_pattern $( -> $tail_return_type )? = $tail ),*
         }
@@ -699,25 +1486,76 @@ macro_rules! router_type {
     // pattern with a handle - add a method for the handle
     (
         $name:ident
-        { $( $methods:item )* },
+        { $( $methods:item )* }
+        { $( $client_methods:item )* },
         $pattern:tt -> $return_type:path = $handle:tt
         $( ,$tail_pattern:tt $( -> $tail_return_type:path )? = $tail:tt )*
     ) => {
         router_type!{
             $name {
-                pattern_and_handler_to_method!( () [] $return_type, $handle, $pattern );
+                pattern_and_handler_to_method!( method () [] $return_type, $handle, $pattern );
                 $( $methods )*
+            } {
+                pattern_and_handler_to_method!( typed_client () [] $return_type, $handle, $pattern );
+                $( $client_methods )*
             },
             $( $tail_pattern $( -> $tail_return_type )? = $tail ),*
         }
     };
 }
 
+/// Handle a request whose path is not matched by any pattern of a router. The
+/// request is passed to the router's fallback handler, if any, or else an
+/// error is returned.
+macro_rules! unmatched_path {
+    // with a fallback handler
+    (
+        $ctx:ident, $request:ident, $start:ident,
+        [ $( $pattern:tt = $handle:tt ),* ] $fallback:ident
+    ) => {
+        $fallback($ctx, $request)
+    };
+
+    // without a fallback handler
+    (
+        $ctx:ident, $request:ident, $start:ident,
+        [ $( $pattern:tt = $handle:tt ),* ]
+    ) => {{
+        // With the diagnostics, find the routes that were the closest
+        // to match the path
+        #[cfg(feature = "router-diagnostics")]
+        let error = {
+            let mut routes = Vec::new();
+            $(
+                routes.extend(router_routes!([] $pattern = $handle));
+            )*
+            let closest = $crate::queries::router::closest_routes(
+                &$request.path, $start, &routes);
+            $crate::queries::router::Error::WrongPathWithClosest {
+                path: $request.path.clone(),
+                closest,
+            }
+        };
+
+        #[cfg(not(feature = "router-diagnostics"))]
+        let error =
+            $crate::queries::router::Error::WrongPath($request.path.clone());
+
+        Err(error).into_storage_result()
+    }};
+}
+
 /// Compile time tree patterns router with type-safe dynamic parameter parsing,
 /// automatic routing, type-safe path constructors and optional client query
-/// methods (enabled with `feature = "async-client"`).
+/// methods (enabled with `feature = "async-client"`). The client query methods
+/// are also available on a [`TypedClient`] obtained from the router's
+/// `with_client` method, which doesn't need the client argument.
 ///
-/// The `router!` macro implements greedy matching algorithm.
+/// The `router!` macro implements greedy matching algorithm, in which the
+/// first pattern that matches the path is used. The patterns made of only
+/// literal segments that have a handler are looked up by their full path,
+/// unless an earlier pattern would also match their path, so that the lookup
+/// doesn't change which pattern is used.
 ///
 /// ## Examples
 ///
@@ -739,6 +1577,12 @@ macro_rules! router_type {
 ///   ( "pattern_c" / [arg1: opt ArgType] / "separator" / [arg2: opt ArgType] ) -> ReturnType =
 ///     handler,
 ///
+///   // Arguments with a default value. The handler receives the default when
+///   // the argument is absent from the path. The path constructor takes an
+///   // `Option` of the argument and skips it when `None`, like with `opt`.
+///   ( "pattern_c_default" / [arg_with_default: ArgType = default_value] ) ->
+///     ReturnType = handler,
+///
 ///   // Untyped dynamic arg is a string slice `&str`
 ///   ( "pattern_d" / [untyped_dynamic_arg] ) -> ReturnType = handler,
 ///
@@ -748,6 +1592,22 @@ macro_rules! router_type {
 ///   // necessary), which can have some `info` string and a proof.
 ///   ( "pattern_e" ) -> ReturnType = (with_options handler),
 ///
+///   // Like above, but the handler also receives the request data decoded
+///   // with borsh into the given type. Malformed data is rejected with an
+///   // error before the handler is called.
+///   ( "pattern_e_body" ) -> ReturnType = (with_options handler: BodyType),
+///
+///   // A handler with options that attaches a proof to its response when the
+///   // request asks for it is marked `with_proof`, which is reported by the
+///   // router's `supports_proof`.
+///   ( "pattern_e_proof" ) -> ReturnType = (with_options handler, with_proof),
+///
+///   // Typed arguments can be validated after parsing. When the validation
+///   // function returns `false`, the pattern is not matched and the router
+///   // tries the next pattern.
+///   ( "pattern_f" / [validated_dynamic_arg: ArgType where is_valid] ) ->
+///     ReturnType = handler,
+///
 ///   ( "another" / "pattern" / "that" / "goes" / "deep" ) -> ReturnType = handler,
 ///
 ///   // Inlined sub-tree
@@ -758,6 +1618,11 @@ macro_rules! router_type {
 ///
 ///   // Imported sub-router - The prefix can only have literal segments
 ///   ( "sub" / "no_dynamic_args" ) = (sub SUB_ROUTER),
+///
+///   // Optional fallback handler, which must come last. It handles the
+///   // requests whose path isn't matched by any of the patterns above,
+///   // instead of returning an error.
+///   _ => fallback_handler,
 /// }
 ///
 /// router! {SUB_ROUTER,
@@ -783,14 +1648,63 @@ macro_rules! router_type {
 ///     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
 ///     H: 'static + StorageHasher + Sync;
 /// ```
+///
+/// With a typed body, defined as `(with_options $handler: BodyType)`, the
+/// decoded body follows the request:
+/// ```rust,ignore
+/// fn handler<D, H>(ctx: RequestCtx<'_, D, H>, request: &RequestQuery, body:
+/// BodyType, args ...)   -> namada_storage::Result<ResponseQuery<ReturnType>>
+/// where
+///     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+///     H: 'static + StorageHasher + Sync;
+/// ```
+///
+/// The fallback handler receives the full request and returns an encoded
+/// response:
+/// ```rust,ignore
+/// fn fallback_handler<D, H>(ctx: RequestCtx<'_, D, H>, request: &RequestQuery)
+///   -> namada_storage::Result<EncodedResponseQuery>
+/// where
+///     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+///     H: 'static + StorageHasher + Sync;
+/// ```
 #[macro_export]
 macro_rules! router {
+    // split the trailing fallback handler from the patterns
+    (@split $name:ident [ $( $patterns:tt )* ] _ => $fallback:ident $(,)? ) => (
+        router!{@router $name [ $fallback ] $( $patterns )* }
+    );
+
+    // move the next pattern in front of the fallback handler
+    (
+        @split $name:ident [ $( $patterns:tt )* ]
+        $pattern:tt $( -> $return_type:path )? = $handle:tt , $( $rest:tt )*
+    ) => (
+        router!{@split $name
+            [ $( $patterns )* $pattern $( -> $return_type )? = $handle , ]
+            $( $rest )*
+        }
+    );
+
+    // patterns without a fallback handler
     { $name:ident, $( $pattern:tt $( -> $return_type:path )? = $handle:tt , )* } => (
+        router!{@router $name [] $( $pattern $( -> $return_type )? = $handle , )* }
+    );
+
+    // patterns with a trailing fallback handler
+    { $name:ident, $( $body:tt )* } => (
+        router!{@split $name [] $( $body )* }
+    );
+
+    {
+        @router $name:ident [ $( $fallback:ident )? ]
+        $( $pattern:tt $( -> $return_type:path )? = $handle:tt , )*
+    } => (
 
 	// `paste!` is used to convert the $name cases for a derived type and function name
 	paste::paste! {
 
-        router_type!{[<$name:camel>] {}, $( $pattern $( -> $return_type )? = $handle ),* }
+        router_type!{[<$name:camel>] {} {}, $( $pattern $( -> $return_type )? = $handle ),* }
 
 		impl $crate::queries::Router for [<$name:camel>] {
             // TODO: for some patterns, there's unused assignment of `$end`
@@ -806,12 +1720,67 @@ macro_rules! router {
                 H: 'static + namada_state::StorageHasher + Sync,
             {
 
-                // Import for `.into_storage_result()`
+                // Import for `.into_storage_result()` and `.wrap_err()`
                 use namada_storage::ResultExt;
 
                 // Import helper from this crate used inside the macros
                 use $crate::queries::router::find_next_slash_index;
 
+                // The fast path for the fully literal routes, which are
+                // looked up by their full path instead of being matched
+                // segment by segment. A route is only indexed if no earlier
+                // pattern matches its path, to keep the patterns' precedence.
+                static LITERAL_ROUTES: std::sync::OnceLock<
+                    std::collections::HashMap<&'static str, usize>
+                > = std::sync::OnceLock::new();
+                let literal_routes = LITERAL_ROUTES.get_or_init(|| {
+                    let mut routes =
+                        $crate::queries::router::index_literal_routes(&[
+                            $( literal_route!($pattern = $handle) ),*
+                        ]);
+                    routes.retain(|path, route_index| {
+                        let request = $crate::queries::RequestQuery {
+                            data: Default::default(),
+                            path: path.to_string(),
+                            height: 0_u32.into(),
+                            prove: false,
+                        };
+                        let mut index = 0_usize;
+                        $(
+                            #[allow(clippy::redundant_closure_call)]
+                            let matched = (|| {
+                                // This loop never repeats, it's only used for
+                                // the `break`s of an unmatched pattern
+                                loop {
+                                    let mut start = 0_usize;
+                                    try_match!(is_match, request, start, $handle, $pattern);
+                                }
+                                false
+                            })();
+                            if matched {
+                                // The first matching pattern is the route
+                                // itself or an earlier one
+                                return index == *route_index;
+                            }
+                            index += 1;
+                        )*
+                        let _ = index;
+                        false
+                    });
+                    routes
+                });
+                let path = $crate::queries::router::literal_route_path(
+                    &request.path, start);
+                if let Some(&route_index) = literal_routes.get(path) {
+                    let mut index = 0_usize;
+                    $(
+                        literal_route!(dispatch ctx, request, start, index,
+                            route_index, $pattern = $handle);
+                        index += 1;
+                    )*
+                    let _ = (index, route_index);
+                }
+
 				$(
                     // This loop never repeats, it's only used for a breaking
                     // mechanism when a $pattern is not matched to skip to the
@@ -824,9 +1793,55 @@ macro_rules! router {
                     }
                 )*
 
-				return Err(
-                    $crate::queries::router::Error::WrongPath(request.path.clone()))
-                    .into_storage_result();
+                unmatched_path!(ctx, request, start, [ $( $pattern = $handle ),* ] $( $fallback )?)
+			}
+
+            #[allow(unused_assignments)]
+            fn internal_supports_proof(
+                &self,
+                request: &$crate::queries::RequestQuery,
+                start: usize
+            ) -> Option<bool> {
+                // Import helper from this crate used inside the macros
+                use $crate::queries::router::find_next_slash_index;
+
+				$(
+                    // This loop never repeats, it's only used for a breaking
+                    // mechanism when a $pattern is not matched to skip to the
+                    // next one, if any
+                    loop {
+                        let mut start = start;
+                        // Try to match and parse args, will break the `loop`
+                        // not matched
+                        try_match!(supports_proof, request, start, $handle, $pattern);
+                    }
+                )*
+
+                None
+			}
+
+            #[allow(unused_assignments)]
+            fn internal_route_label(
+                &self,
+                request: &$crate::queries::RequestQuery,
+                start: usize
+            ) -> Option<&'static str> {
+                // Import helper from this crate used inside the macros
+                use $crate::queries::router::find_next_slash_index;
+
+				$(
+                    // This loop never repeats, it's only used for a breaking
+                    // mechanism when a $pattern is not matched to skip to the
+                    // next one, if any
+                    loop {
+                        let mut start = start;
+                        // Try to match and parse args, will break the `loop`
+                        // not matched
+                        try_match!(route_label, request, start, $handle, $pattern);
+                    }
+                )*
+
+                None
 			}
 		}
 
@@ -843,13 +1858,16 @@ macro_rules! router {
 /// ```
 #[cfg(test)]
 mod test_rpc_handlers {
+    use borsh::BorshDeserialize;
     use borsh_ext::BorshSerializeExt;
-    use namada_core::types::storage::Epoch;
+    use namada_core::types::storage::{self, Epoch};
     use namada_core::types::token;
     use namada_state::{DBIter, StorageHasher, DB};
+    use namada_storage::ResultExt;
 
     use crate::queries::{
         EncodedResponseQuery, RequestCtx, RequestQuery, ResponseQuery,
+        StorageWitness, Witnessed,
     };
 
     /// A little macro to generate boilerplate for RPC handler functions.
@@ -902,11 +1920,23 @@ mod test_rpc_handlers {
             a2: token::DenominatedAmount,
             a3: token::DenominatedAmount
         ),
+        d(amount: token::DenominatedAmount),
+        d_positive(amount: token::DenominatedAmount),
+        e(epoch: Epoch),
+        f(epoch: Epoch),
+        g(untyped_arg: &str),
+        gx,
+        hx,
         x,
         y(untyped_arg: &str),
         z(untyped_arg: &str),
     );
 
+    /// Validation of a typed argument used in the router below.
+    pub fn is_positive(amount: &token::DenominatedAmount) -> bool {
+        !amount.is_zero()
+    }
+
     /// This handler is hand-written, because the test helper macro doesn't
     /// support optional args.
     pub fn b3iii<D, H, V, T>(
@@ -963,6 +1993,87 @@ mod test_rpc_handlers {
             ..ResponseQuery::default()
         })
     }
+
+    /// This handler is hand-written, because the test helper macro doesn't
+    /// support handlers with `with_options`. It sums the values from the
+    /// request body.
+    pub fn total<D, H, V, T>(
+        _ctx: RequestCtx<'_, D, H, V, T>,
+        _request: &RequestQuery,
+        values: Vec<u64>,
+    ) -> namada_storage::Result<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+    {
+        let total: u64 = values.into_iter().sum();
+        Ok(ResponseQuery {
+            data: total.serialize_to_vec(),
+            ..ResponseQuery::default()
+        })
+    }
+
+    /// A fallback handler that returns the unmatched path.
+    pub fn not_found<D, H, V, T>(
+        _ctx: RequestCtx<'_, D, H, V, T>,
+        request: &RequestQuery,
+    ) -> namada_storage::Result<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+    {
+        Ok(ResponseQuery {
+            data: request.path.serialize_to_vec(),
+            info: "not found".to_owned(),
+            ..ResponseQuery::default()
+        })
+    }
+
+    /// The storage keys of the values summed by the [`sum`] handler.
+    pub fn sum_keys() -> [storage::Key; 2] {
+        [
+            storage::Key::parse("sum/a").unwrap(),
+            storage::Key::parse("sum/b").unwrap(),
+        ]
+    }
+
+    /// This handler is hand-written, because the test helper macro doesn't
+    /// support handlers with `with_options`. It computes its data from
+    /// storage values and returns their witnesses when a proof is requested.
+    pub fn sum<D, H, V, T>(
+        ctx: RequestCtx<'_, D, H, V, T>,
+        request: &RequestQuery,
+    ) -> namada_storage::Result<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+    {
+        let height = ctx.wl_storage.storage.get_last_block_height();
+        let mut sum = 0_u64;
+        let mut witnesses = vec![];
+        for key in sum_keys() {
+            let (value, _gas) =
+                ctx.wl_storage.storage.read(&key).into_storage_result()?;
+            let value = value.unwrap_or_default();
+            sum += u64::try_from_slice(&value).into_storage_result()?;
+            if request.prove {
+                let proof = ctx
+                    .wl_storage
+                    .storage
+                    .get_existence_proof(&key, &value, height)
+                    .into_storage_result()?;
+                witnesses.push(StorageWitness { key, value, proof });
+            }
+        }
+        Ok(ResponseQuery {
+            data: Witnessed {
+                data: sum,
+                witnesses,
+            }
+            .serialize_to_vec(),
+            ..ResponseQuery::default()
+        })
+    }
 }
 
 /// You can expand the `router!` macro invocation with e.g.:
@@ -975,6 +2086,7 @@ mod test_rpc {
     use namada_core::types::token;
 
     use super::test_rpc_handlers::*;
+    use crate::queries::Witnessed;
 
     // Setup an RPC router for testing
     router! {TEST_RPC,
@@ -998,6 +2110,15 @@ mod test_rpc {
             },
         },
         ( "c" ) -> String = (with_options c),
+        ( "sum" ) -> Witnessed<u64> = (with_options sum, with_proof),
+        ( "total" ) -> u64 = (with_options total: Vec<u64>),
+        ( "d" / [amount: token::DenominatedAmount where is_positive] ) -> String = d_positive,
+        ( "d" / [amount: token::DenominatedAmount] ) -> String = d,
+        ( "e" / [epoch: Epoch = Epoch(1)] / "x" ) -> String = e,
+        ( "f" / [epoch: Epoch = Epoch::default()] ) -> String = f,
+        ( "g" / [untyped_arg] ) -> String = g,
+        ( "g" / "x" ) -> String = gx,
+        ( "h" / "x" ) -> String = hx,
     }
 
     router! {TEST_SUB_RPC,
@@ -1005,18 +2126,35 @@ mod test_rpc {
         ( "y" / [untyped_arg] ) -> String = y,
         ( "z" / [untyped_arg] ) -> String = z,
     }
+
+    // Setup an RPC router with a fallback handler for testing
+    router! {TEST_FALLBACK_RPC,
+        ( "a" ) -> String = a,
+        ( "sub" ) = (sub TEST_SUB_RPC),
+        _ => not_found,
+    }
 }
 
 #[cfg(test)]
 mod test {
+    use std::sync::{Arc, Mutex};
+
+    use borsh::BorshDeserialize;
+    use borsh_ext::BorshSerializeExt;
     use namada_core::tendermint::block;
-    use namada_core::types::storage::Epoch;
+    use namada_core::types::hash::Sha256Hasher;
+    use namada_core::types::storage::{BlockHeight, Epoch};
     use namada_core::types::token;
     use namada_core::types::token::NATIVE_MAX_DECIMAL_PLACES;
+    use namada_state::merkle_tree::verify_existence_proof_ops;
 
-    use super::test_rpc::TEST_RPC;
+    use super::test_rpc::{TEST_FALLBACK_RPC, TEST_RPC};
+    use super::test_rpc_handlers::sum_keys;
     use crate::queries::testing::TestClient;
-    use crate::queries::{RequestCtx, RequestQuery, Router};
+    use crate::queries::{
+        Client, RequestCtx, RequestQuery, RequestTimingHook, Router,
+        DEFAULT_MAX_REQUEST_PATH_LEN,
+    };
 
     /// Test all the possible paths in `TEST_RPC` router.
     #[tokio::test]
@@ -1036,6 +2174,8 @@ mod test {
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
+            max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+            request_timing_hook: None,
         };
         let result = TEST_RPC.handle(ctx, &request);
         assert!(result.is_err());
@@ -1053,6 +2193,8 @@ mod test {
             vp_wasm_cache: (),
             tx_wasm_cache: (),
             storage_read_past_height_limit: None,
+            max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+            request_timing_hook: None,
         };
         let result = TEST_RPC.handle(ctx, &request);
         assert!(matches!(
@@ -1132,6 +2274,34 @@ mod test {
         let result = TEST_RPC.c(&client, None, None, false).await.unwrap();
         assert_eq!(result.data, format!("c"));
 
+        let amount = token::DenominatedAmount::new(
+            token::Amount::native_whole(5),
+            NATIVE_MAX_DECIMAL_PLACES.into(),
+        );
+        let result = TEST_RPC.d_positive(&client, &amount).await.unwrap();
+        assert_eq!(result, format!("d_positive/{amount}"));
+
+        // A zero amount doesn't pass the validation and falls through to the
+        // next pattern
+        let zero = token::DenominatedAmount::new(
+            token::Amount::zero(),
+            NATIVE_MAX_DECIMAL_PLACES.into(),
+        );
+        let result = TEST_RPC.d_positive(&client, &zero).await.unwrap();
+        assert_eq!(result, format!("d/{zero}"));
+
+        // An omitted arg with a default value uses the default ...
+        let result = TEST_RPC.e(&client, &None).await.unwrap();
+        assert_eq!(result, format!("e/1"));
+        let result = TEST_RPC.f(&client, &None).await.unwrap();
+        assert_eq!(result, format!("f/0"));
+        // ... and a supplied one overrides it
+        let epoch = Epoch(5);
+        let result = TEST_RPC.e(&client, &Some(epoch)).await.unwrap();
+        assert_eq!(result, format!("e/{epoch}"));
+        let result = TEST_RPC.f(&client, &Some(epoch)).await.unwrap();
+        assert_eq!(result, format!("f/{epoch}"));
+
         let result = TEST_RPC.test_sub_rpc().x(&client).await.unwrap();
         assert_eq!(result, format!("x"));
 
@@ -1145,4 +2315,361 @@ mod test {
 
         Ok(())
     }
+
+    /// Test that the typed client sends the requests with its bound client.
+    #[tokio::test]
+    async fn test_router_typed_client() {
+        let client = TestClient::new(TEST_RPC);
+        let typed_client = TEST_RPC.with_client(&client);
+
+        let result = typed_client.a().await.unwrap();
+        assert_eq!(result, "a");
+
+        let result = typed_client.b1().await.unwrap();
+        assert_eq!(result, "b1");
+
+        let balance = token::DenominatedAmount::new(
+            token::Amount::native_whole(123_000_000),
+            NATIVE_MAX_DECIMAL_PLACES.into(),
+        );
+        let result = typed_client.b2i(&balance).await.unwrap();
+        assert_eq!(result, format!("b2i/{balance}"));
+
+        let result = typed_client.c(None, None, false).await.unwrap();
+        assert_eq!(result.data, "c");
+
+        let values = vec![1, 2, 3];
+        let result = typed_client.total(&values, None, false).await.unwrap();
+        assert_eq!(result.data, 6);
+
+        let arg = "test123";
+        let result = typed_client.test_sub_rpc().y(arg).await.unwrap();
+        assert_eq!(result, format!("y/{arg}"));
+    }
+
+    /// Test that the labels of the matched routes are found.
+    #[test]
+    fn test_router_route_label() {
+        assert_eq!(TEST_RPC.route_label("/a"), Some("a"));
+        assert_eq!(TEST_RPC.route_label("/b/0/ii/"), Some("b0ii"));
+        assert_eq!(TEST_RPC.route_label("/sub/y/arg"), Some("y"));
+        assert_eq!(TEST_RPC.route_label("/total"), Some("total"));
+        assert_eq!(TEST_RPC.route_label("/b/0"), None);
+        assert_eq!(TEST_RPC.route_label("/invalid"), None);
+    }
+
+    /// Test that the request timing hook fires once per request with the
+    /// label of the matched route.
+    #[test]
+    fn test_router_request_timing_hook() {
+        let timings = Arc::new(Mutex::new(vec![]));
+        let hook_timings = timings.clone();
+        let hook = RequestTimingHook::new(move |timing| {
+            hook_timings.lock().unwrap().push(timing.clone())
+        });
+
+        let client = TestClient::new(TEST_RPC);
+        let handle = |path: &str, hook: Option<RequestTimingHook>| {
+            let request = RequestQuery {
+                path: path.to_owned(),
+                data: Default::default(),
+                height: block::Height::from(0_u32),
+                prove: Default::default(),
+            };
+            let ctx = RequestCtx {
+                event_log: &client.event_log,
+                wl_storage: &client.wl_storage,
+                vp_wasm_cache: (),
+                tx_wasm_cache: (),
+                storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: hook,
+            };
+            TEST_RPC.handle(ctx, &request)
+        };
+        handle("/sub/y/arg", Some(hook.clone())).unwrap();
+        handle("/sub/z/arg", Some(hook.clone())).unwrap();
+        assert!(handle("/invalid", Some(hook)).is_err());
+        // The requests without a hook in their context are not reported
+        handle("/a", None).unwrap();
+
+        let timings = timings.lock().unwrap();
+        let routes: Vec<_> =
+            timings.iter().map(|timing| timing.route).collect();
+        assert_eq!(routes, vec![Some("y"), Some("z"), None]);
+        for timing in timings.iter() {
+            assert!(timing.end >= timing.start);
+        }
+    }
+
+    /// Test that a near-miss path reports the closest route.
+    #[cfg(feature = "router-diagnostics")]
+    #[test]
+    fn test_router_diagnostics() {
+        let client = TestClient::new(TEST_RPC);
+        let handle = |path: &str| {
+            let request = RequestQuery {
+                path: path.to_owned(),
+                data: Default::default(),
+                height: block::Height::from(0_u32),
+                prove: Default::default(),
+            };
+            let ctx = RequestCtx {
+                event_log: &client.event_log,
+                wl_storage: &client.wl_storage,
+                vp_wasm_cache: (),
+                tx_wasm_cache: (),
+                storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: None,
+            };
+            TEST_RPC.handle(ctx, &request).unwrap_err().to_string()
+        };
+
+        let error = handle("/b/2/x");
+        assert!(error.contains(r#"["/b/2/i/[balance]"]"#), "{error}");
+
+        // Paths unmatched by a sub-router report its closest routes
+        let error = handle("/sub/x/extra");
+        assert!(error.contains(r#"["/sub/x"]"#), "{error}");
+
+        // Paths that don't match any first segment have no closest routes
+        let error = handle("/invalid");
+        assert!(error.contains("[]"), "{error}");
+    }
+
+    /// Test that the paths unmatched by a router reach its fallback handler.
+    #[test]
+    fn test_router_fallback() {
+        let client = TestClient::new(TEST_FALLBACK_RPC);
+        let handle = |path: &str| {
+            let request = RequestQuery {
+                path: path.to_owned(),
+                data: Default::default(),
+                height: block::Height::from(0_u32),
+                prove: Default::default(),
+            };
+            let ctx = RequestCtx {
+                event_log: &client.event_log,
+                wl_storage: &client.wl_storage,
+                vp_wasm_cache: (),
+                tx_wasm_cache: (),
+                storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: None,
+            };
+            TEST_FALLBACK_RPC.handle(ctx, &request)
+        };
+
+        // The matched paths are handled by their handlers
+        let response = handle("/a").unwrap();
+        assert_eq!(String::try_from_slice(&response.data).unwrap(), "a");
+        let response = handle("/sub/x").unwrap();
+        assert_eq!(String::try_from_slice(&response.data).unwrap(), "x");
+
+        // The unmatched paths reach the fallback instead of erroring
+        for path in ["/invalid", "/a/extra", "/"] {
+            let response = handle(path).unwrap();
+            assert_eq!(response.info, "not found");
+            assert_eq!(String::try_from_slice(&response.data).unwrap(), path);
+        }
+
+        // The paths unmatched by a sub-router without a fallback still fail
+        assert!(handle("/sub/invalid").is_err());
+    }
+
+    /// Test that the paths over the maximum length are rejected before they
+    /// are matched.
+    #[test]
+    fn test_router_max_path_len() {
+        let client = TestClient::new(TEST_FALLBACK_RPC);
+        let handle = |path: String, max_request_path_len: usize| {
+            let request = RequestQuery {
+                path,
+                data: Default::default(),
+                height: block::Height::from(0_u32),
+                prove: Default::default(),
+            };
+            let ctx = RequestCtx {
+                event_log: &client.event_log,
+                wl_storage: &client.wl_storage,
+                vp_wasm_cache: (),
+                tx_wasm_cache: (),
+                storage_read_past_height_limit: None,
+                max_request_path_len,
+                request_timing_hook: None,
+            };
+            TEST_FALLBACK_RPC.handle(ctx, &request)
+        };
+        let path = format!("/a{}", "/".repeat(DEFAULT_MAX_REQUEST_PATH_LEN));
+
+        // The over-long path is rejected even with a fallback handler
+        let result = handle(path.clone(), DEFAULT_MAX_REQUEST_PATH_LEN);
+        assert!(matches!(
+            result,
+            Err(namada_storage::Error::SimpleMessage(
+                "Request paths over the maximum length are unsupported",
+            ))
+        ));
+
+        // With a higher limit, the same path reaches the fallback handler
+        let result = handle(path.clone(), path.len());
+        assert_eq!(result.unwrap().info, "not found");
+    }
+
+    /// Test that the fully literal routes are dispatched to the same handlers
+    /// as the patterns matched segment by segment, with an earlier dynamic
+    /// pattern taking precedence over a later literal one.
+    #[test]
+    fn test_router_literal_routes() {
+        let client = TestClient::new(TEST_RPC);
+        let handle = |path: &str| {
+            let request = RequestQuery {
+                path: path.to_owned(),
+                data: Default::default(),
+                height: block::Height::from(0_u32),
+                prove: Default::default(),
+            };
+            let ctx = RequestCtx {
+                event_log: &client.event_log,
+                wl_storage: &client.wl_storage,
+                vp_wasm_cache: (),
+                tx_wasm_cache: (),
+                storage_read_past_height_limit: None,
+                max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+                request_timing_hook: None,
+            };
+            let response = TEST_RPC.handle(ctx, &request).unwrap();
+            String::try_from_slice(&response.data).unwrap()
+        };
+
+        // The fully literal routes, also in a sub-router and with a trailing
+        // slash
+        assert_eq!(handle("/a"), "a");
+        assert_eq!(handle("/a/"), "a");
+        assert_eq!(handle("/c"), "c");
+        assert_eq!(handle("/sub/x"), "x");
+        assert_eq!(handle("/h/x"), "hx");
+
+        // A literal route whose path is also matched by an earlier dynamic
+        // pattern is dispatched to the earlier pattern's handler
+        assert_eq!(handle("/g/x"), "g/x");
+        assert_eq!(handle("/g/x/"), "g/x");
+        assert_eq!(TEST_RPC.route_label("/g/x"), Some("g"));
+        assert_eq!(handle("/g/y"), "g/y");
+    }
+
+    /// Test that a handler with a typed body receives the decoded request data
+    /// and that malformed data is rejected.
+    #[tokio::test]
+    async fn test_router_with_typed_body() -> namada_storage::Result<()> {
+        let client = TestClient::new(TEST_RPC);
+
+        let values = vec![1_u64, 2, 3];
+        let result =
+            TEST_RPC.total(&client, &values, None, false).await.unwrap();
+        assert_eq!(result.data, 6);
+
+        // Data that cannot be decoded into the body's type is an error
+        let request = RequestQuery {
+            path: TEST_RPC.total_path(),
+            data: vec![1_u8, 2].into(),
+            height: block::Height::from(0_u32),
+            prove: Default::default(),
+        };
+        let ctx = RequestCtx {
+            event_log: &client.event_log,
+            wl_storage: &client.wl_storage,
+            vp_wasm_cache: (),
+            tx_wasm_cache: (),
+            storage_read_past_height_limit: None,
+            max_request_path_len: DEFAULT_MAX_REQUEST_PATH_LEN,
+            request_timing_hook: None,
+        };
+        let error = TEST_RPC.handle(ctx, &request).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Malformed request body of `total`"),
+            "Unexpected error {error}"
+        );
+
+        Ok(())
+    }
+
+    /// Test that a handler with options can return storage witnesses of the
+    /// values that its data is computed from.
+    #[tokio::test]
+    async fn test_router_with_witnesses() -> namada_storage::Result<()> {
+        let mut client = TestClient::new(TEST_RPC);
+        let [key_a, key_b] = sum_keys();
+        client.wl_storage.storage.block.height = BlockHeight(1);
+        client
+            .wl_storage
+            .storage
+            .write(&key_a, 2_u64.serialize_to_vec())
+            .unwrap();
+        client
+            .wl_storage
+            .storage
+            .write(&key_b, 3_u64.serialize_to_vec())
+            .unwrap();
+        client.wl_storage.commit_block().unwrap();
+
+        // Without a proof, there are no witnesses
+        let result = TEST_RPC.sum(&client, None, None, false).await.unwrap();
+        assert_eq!(result.data.data, 5);
+        assert!(result.data.witnesses.is_empty());
+
+        // With a proof, the data can be recomputed from the witnesses, each
+        // of which is proven against the Merkle root
+        let result = TEST_RPC.sum(&client, None, None, true).await.unwrap();
+        assert_eq!(result.data.data, 5);
+        let witnesses = result.data.witnesses;
+        assert_eq!(witnesses.len(), 2);
+        assert_eq!(witnesses[0].key, key_a);
+        assert_eq!(witnesses[1].key, key_b);
+        let root = client.wl_storage.storage.merkle_root();
+        let mut sum = 0_u64;
+        for witness in witnesses {
+            assert!(verify_existence_proof_ops::<Sha256Hasher>(
+                &root,
+                &witness.key,
+                &witness.value,
+                &witness.proof,
+            ));
+            sum += u64::try_from_slice(&witness.value).unwrap();
+        }
+        assert_eq!(sum, 5);
+
+        // A witness with a tampered value doesn't verify
+        let witness = TEST_RPC
+            .sum(&client, None, None, true)
+            .await
+            .unwrap()
+            .data
+            .witnesses
+            .remove(0);
+        assert!(!verify_existence_proof_ops::<Sha256Hasher>(
+            &root,
+            &witness.key,
+            &7_u64.serialize_to_vec(),
+            &witness.proof,
+        ));
+
+        Ok(())
+    }
+
+    /// Test that only the handlers marked `with_proof` support proofs.
+    #[test]
+    fn test_router_supports_proof() {
+        assert_eq!(TEST_RPC.supports_proof(&TEST_RPC.sum_path()), Some(true));
+        assert_eq!(TEST_RPC.supports_proof(&TEST_RPC.c_path()), Some(false));
+        assert_eq!(
+            TEST_RPC.supports_proof(&TEST_RPC.total_path()),
+            Some(false)
+        );
+        assert_eq!(TEST_RPC.supports_proof(&TEST_RPC.a_path()), Some(false));
+        assert_eq!(TEST_RPC.supports_proof("/invalid"), None);
+    }
 }
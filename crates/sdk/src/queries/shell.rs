@@ -1,8 +1,8 @@
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 pub(super) mod eth_bridge;
 
-use borsh::BorshDeserialize;
+use borsh::{BorshDeserialize, BorshSerialize};
 use borsh_ext::BorshSerializeExt;
 use masp_primitives::asset_type::AssetType;
 use masp_primitives::merkle_tree::MerklePath;
@@ -12,6 +12,7 @@ use namada_core::hints;
 use namada_core::types::address::Address;
 use namada_core::types::dec::Dec;
 use namada_core::types::hash::Hash;
+use namada_core::types::parameters::FeeParameters;
 use namada_core::types::storage::{
     self, BlockHeight, BlockResults, Epoch, KeySeg, PrefixValue,
 };
@@ -19,7 +20,6 @@ use namada_core::types::token::{Denomination, MaspDigitPos};
 use namada_core::types::uint::Uint;
 use namada_state::{DBIter, LastBlock, StorageHasher, DB};
 use namada_storage::{self, ResultExt, StorageRead};
-#[cfg(any(test, feature = "async-client"))]
 use namada_tx::data::TxResult;
 
 use self::eth_bridge::{EthBridge, ETH_BRIDGE};
@@ -30,9 +30,20 @@ use crate::ibc::core::host::types::identifiers::{
 };
 use crate::masp::MaspTokenRewardData;
 use crate::queries::types::{RequestCtx, RequestQuery};
-use crate::queries::{require_latest_height, EncodedResponseQuery};
+use crate::queries::{
+    require_latest_height, require_no_proof, require_pinned_height,
+    EncodedResponseQuery,
+};
 use crate::tendermint::merkle::proof::ProofOps;
 
+/// The maximum number of the storage entries returned by a single page of the
+/// `account_dump` query
+pub const MAX_ACCOUNT_DUMP_ENTRIES: u64 = 1_000;
+
+/// The version of the protocol run by the node, which is also reported as the
+/// app version to CometBFT
+pub const PROTOCOL_VERSION: u64 = 0;
+
 type ConversionWithoutPath = (
     Address,
     Denomination,
@@ -50,6 +61,70 @@ type Conversion = (
     MerklePath<Node>,
 );
 
+/// A chunk of a storage value that's too large to be read in a single query
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct StorageValueChunk {
+    /// The bytes of the value in this chunk
+    pub data: Vec<u8>,
+    /// The offset of the next chunk, if there are any bytes left
+    pub next_offset: Option<u64>,
+}
+
+/// The outcome of a transaction dry-run, including the storage modifications
+/// that it would make if it was committed
+#[derive(Debug, Clone, BorshSerialize, BorshDeserialize)]
+pub struct TxSimulation {
+    /// The result of the dry-run, with the gas used, the results of the
+    /// triggered VPs and the emitted events
+    pub result: TxResult,
+    /// The values written by the transaction, where `None` is a deletion
+    pub write_set: BTreeMap<storage::Key, Option<Vec<u8>>>,
+}
+
+/// The versions of the node's software and protocol
+#[derive(Debug, Clone, PartialEq, Eq, BorshSerialize, BorshDeserialize)]
+pub struct VersionInfo {
+    /// The version of the node's crate
+    pub crate_version: String,
+    /// The version of the protocol run by the node
+    pub protocol_version: u64,
+    /// The build profile, followed by the git commit that the node was built
+    /// from if it was set in the `NAMADA_GIT_COMMIT` env var at compile time
+    pub build_metadata: String,
+}
+
+impl VersionInfo {
+    /// The versions of this build
+    pub fn current() -> Self {
+        let profile = if cfg!(debug_assertions) {
+            "debug"
+        } else {
+            "release"
+        };
+        let build_metadata = match option_env!("NAMADA_GIT_COMMIT") {
+            Some(commit) => format!("{profile}.{commit}"),
+            None => profile.to_string(),
+        };
+        Self {
+            crate_version: env!("CARGO_PKG_VERSION").to_string(),
+            protocol_version: PROTOCOL_VERSION,
+            build_metadata,
+        }
+    }
+}
+
+impl TxSimulation {
+    /// The addresses whose VPs have been triggered by the transaction
+    pub fn verifiers(&self) -> BTreeSet<Address> {
+        let vps_result = &self.result.vps_result;
+        vps_result
+            .accepted_vps
+            .union(&vps_result.rejected_vps)
+            .cloned()
+            .collect()
+    }
+}
+
 router! {SHELL,
     // Shell provides storage read access, block metadata and can dry-run a tx
 
@@ -68,16 +143,41 @@ router! {SHELL,
     // Query the last committed block
     ( "last_block" ) -> Option<LastBlock> = last_block,
 
+    // The versions of the node's software and protocol
+    ( "node" / "version" ) -> VersionInfo = node_version,
+
+    // The current fee parameters
+    ( "parameters" / "fees" ) -> FeeParameters = fee_parameters,
+
     // Raw storage access - read value
     ( "value" / [storage_key: storage::Key] )
-        -> Vec<u8> = (with_options storage_value),
+        -> Vec<u8> = (with_options storage_value, with_proof),
+
+    // Raw storage access - read the bytes of a value verbatim, without any
+    // decoding
+    ( "raw" / [storage_key: storage::Key] )
+        -> Vec<u8> = (with_options raw_value, with_proof),
+
+    // Raw storage access - read a chunk of a value at the given byte offset
+    ( "value_chunk" / [offset: u64] / [chunk_size: u64] / [storage_key: storage::Key] )
+        -> Option<StorageValueChunk> = (with_options storage_value_chunk),
 
     // Dry run a transaction
     ( "dry_run_tx" ) -> TxResult = (with_options dry_run_tx),
 
+    // Dry run a transaction and return the storage modifications it would make
+    ( "tx" / "simulate" ) -> TxSimulation = (with_options simulate_tx),
+
+    // Estimate the gas of a transaction by dry running only its code, without
+    // the VPs
+    ( "tx" / "gas_estimate" ) -> u64 = (with_options gas_estimate),
+
+    // The keys changed by an applied transaction
+    ( "tx" / [hash: Hash] / "changed_keys" ) -> Vec<storage::Key> = tx_changed_keys,
+
     // Raw storage access - prefix iterator
     ( "prefix" / [storage_key: storage::Key] )
-        -> Vec<PrefixValue> = (with_options storage_prefix),
+        -> Vec<PrefixValue> = (with_options storage_prefix, with_proof),
 
     // Raw storage access - is given storage key present?
     ( "has_key" / [storage_key: storage::Key] )
@@ -101,6 +201,17 @@ router! {SHELL,
     // was the transaction applied?
     ( "applied" / [tx_hash: Hash] ) -> Option<Event> = applied,
 
+    // The logged events of a block, optionally only those of a single tx
+    ( "events" / [height: BlockHeight] / [tx_index: opt u64] )
+        -> Vec<Event> = events_at,
+
+    // Query the code hash of the VP of an account
+    ( "account" / [owner: Address] / "vp_hash" ) -> Hash = account_vp_hash,
+
+    // Dump a page of the raw storage sub-space of an account
+    ( "account" / [owner: Address] / "dump" / [offset: u64] / [limit: u64] )
+        -> BTreeMap<storage::Key, Vec<u8>> = account_dump,
+
     // Query account subspace
     ( "account" / [owner: Address] ) -> Option<Account> = account,
 
@@ -127,6 +238,28 @@ where
     unimplemented!("Dry running tx requires \"wasm-runtime\" feature.")
 }
 
+fn simulate_tx<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+    _request: &RequestQuery,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Simulating tx requires \"wasm-runtime\" feature.")
+}
+
+fn gas_estimate<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+    _request: &RequestQuery,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    unimplemented!("Estimating tx gas requires \"wasm-runtime\" feature.")
+}
+
 /// Query to read block results from storage
 pub fn read_results<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -347,6 +480,9 @@ where
 /// borsh-encoded types, it is safe to check `data.is_empty()` to see if the
 /// value was found, except for unit - see `fn query_storage_value` in
 /// `apps/src/lib/client/rpc.rs` for unit type handling via `storage_has_key`.
+/// When a proof is requested for a key that is not found, the response
+/// carries a proof of its non-existence instead. Such proofs are only
+/// supported for the keys of the IBC sub-tree.
 fn storage_value<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     request: &RequestQuery,
@@ -356,30 +492,7 @@ where
     D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
     H: 'static + StorageHasher + Sync,
 {
-    let last_committed_height = ctx.wl_storage.storage.get_last_block_height();
-    let queried_height = {
-        let height: BlockHeight = request.height.into();
-        let is_last_height_query = height.0 == 0;
-
-        if hints::likely(is_last_height_query) {
-            last_committed_height
-        } else {
-            height
-        }
-    };
-
-    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
-        if queried_height + past_height_limit < last_committed_height {
-            return Err(namada_storage::Error::new(std::io::Error::new(
-                std::io::ErrorKind::InvalidInput,
-                format!(
-                    "Cannot query more than {past_height_limit} blocks in the \
-                     past (configured via \
-                     `shell.storage_read_past_height_limit`)."
-                ),
-            )));
-        }
-    }
+    let queried_height = checked_queried_height(&ctx, request)?;
 
     match ctx
         .wl_storage
@@ -424,6 +537,120 @@ where
     }
 }
 
+/// Read the raw bytes of a storage value, regardless of their type, e.g. for
+/// re-encoding or debugging. The bytes are returned verbatim and they are
+/// never decoded, neither by the handler nor by the client. Like with
+/// [`storage_value`], a key that is not found returns empty data and the
+/// existence or non-existence proof of the key can be requested.
+fn raw_value<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    storage_key: storage::Key,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    storage_value(ctx, request, storage_key)
+}
+
+/// Read a chunk of a storage value, starting at the given byte offset. This
+/// allows to read values that are too large to fit in a single response. A
+/// specific block height is required so that the value cannot change between
+/// the chunks. Returns `None` when the storage key is not found.
+fn storage_value_chunk<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+    offset: u64,
+    chunk_size: u64,
+    storage_key: storage::Key,
+) -> namada_storage::Result<EncodedResponseQuery>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    require_pinned_height(request)?;
+    require_no_proof(request)?;
+    if chunk_size == 0 {
+        return Err(namada_storage::Error::new_const(
+            "The chunk size must be greater than 0",
+        ));
+    }
+    let queried_height = checked_queried_height(&ctx, request)?;
+
+    match ctx
+        .wl_storage
+        .storage
+        .read_with_height(&storage_key, queried_height)
+        .into_storage_result()?
+    {
+        (Some(value), _gas) => {
+            let value_len = value.len() as u64;
+            if offset > value_len {
+                return Err(namada_storage::Error::new(std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!(
+                        "The offset {offset} is out of range of the value of \
+                         length {value_len}"
+                    ),
+                )));
+            }
+            let end = offset.saturating_add(chunk_size).min(value_len);
+            let chunk = StorageValueChunk {
+                data: value[offset as usize..end as usize].to_vec(),
+                next_offset: (end < value_len).then_some(end),
+            };
+            Ok(EncodedResponseQuery {
+                data: Some(chunk).serialize_to_vec(),
+                proof: None,
+                info: Default::default(),
+            })
+        }
+        (None, _gas) => Ok(EncodedResponseQuery {
+            data: None::<StorageValueChunk>.serialize_to_vec(),
+            proof: None,
+            info: format!("No value found for key: {}", storage_key),
+        }),
+    }
+}
+
+/// Get the height for a storage read query, checking that it's within the
+/// configured limit of past heights.
+fn checked_queried_height<D, H, V, T>(
+    ctx: &RequestCtx<'_, D, H, V, T>,
+    request: &RequestQuery,
+) -> namada_storage::Result<BlockHeight>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let last_committed_height = ctx.wl_storage.storage.get_last_block_height();
+    let queried_height = {
+        let height: BlockHeight = request.height.into();
+        let is_last_height_query = height.0 == 0;
+
+        if hints::likely(is_last_height_query) {
+            last_committed_height
+        } else {
+            height
+        }
+    };
+
+    if let Some(past_height_limit) = ctx.storage_read_past_height_limit {
+        if queried_height + past_height_limit < last_committed_height {
+            return Err(namada_storage::Error::new(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "Cannot query more than {past_height_limit} blocks in the \
+                     past (configured via \
+                     `shell.storage_read_past_height_limit`)."
+                ),
+            )));
+        }
+    }
+    Ok(queried_height)
+}
+
 fn storage_prefix<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     request: &RequestQuery,
@@ -526,6 +753,70 @@ where
         .cloned())
 }
 
+fn node_version<D, H, V, T>(
+    _ctx: RequestCtx<'_, D, H, V, T>,
+) -> namada_storage::Result<VersionInfo>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(VersionInfo::current())
+}
+
+fn fee_parameters<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+) -> namada_storage::Result<FeeParameters>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    namada_parameters::read_fee_parameters(ctx.wl_storage)
+}
+
+/// Find the events of the block at the given height that are still held in the
+/// event log. With a `tx_index`, only the events of the tx at that index in the
+/// block are returned.
+fn events_at<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    height: BlockHeight,
+    tx_index: Option<u64>,
+) -> namada_storage::Result<Vec<Event>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let height = height.to_string();
+    let tx_index = tx_index.map(|tx_index| tx_index.to_string());
+    Ok(ctx
+        .event_log
+        .iter()
+        .filter(|event| {
+            event.get("height") == Some(&height)
+                && (tx_index.is_none()
+                    || event.get("tx_index") == tx_index.as_ref())
+        })
+        .cloned()
+        .collect())
+}
+
+/// Find the keys changed by the applied tx with the given hash. Like the
+/// events, the changed keys are only held in the event log for the recent txs,
+/// so the result is empty for a tx that has been pruned from the log.
+fn tx_changed_keys<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    hash: Hash,
+) -> namada_storage::Result<Vec<storage::Key>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    Ok(ctx
+        .event_log
+        .tx_changed_keys(&hash)
+        .map(|changed_keys| changed_keys.iter().cloned().collect())
+        .unwrap_or_default())
+}
+
 fn ibc_client_update<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     client_id: ClientId,
@@ -600,6 +891,56 @@ where
     }
 }
 
+/// Get the code hash of the VP of an account. For an implicit account, this is
+/// the hash of the implicit VP. Fails for an account that has no VP.
+fn account_vp_hash<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    owner: Address,
+) -> namada_storage::Result<Hash>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let vp_key = if let Address::Implicit(_) = &owner {
+        namada_parameters::storage::get_implicit_vp_key()
+    } else {
+        storage::Key::validity_predicate(&owner)
+    };
+    ctx.wl_storage.read(&vp_key)?.ok_or_else(|| {
+        namada_storage::Error::new(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("No VP found for the account {owner}"),
+        ))
+    })
+}
+
+/// Dump the raw storage sub-space of an account, ordered by the storage keys.
+/// The entries are paginated - the `offset` is the number of the entries to
+/// skip and at most `limit` entries, capped at [`MAX_ACCOUNT_DUMP_ENTRIES`],
+/// are returned. A page with less entries than requested is the last one.
+fn account_dump<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    owner: Address,
+    offset: u64,
+    limit: u64,
+) -> namada_storage::Result<BTreeMap<storage::Key, Vec<u8>>>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    if limit == 0 {
+        return Err(namada_storage::Error::new_const(
+            "The limit must be greater than 0",
+        ));
+    }
+    let limit = limit.min(MAX_ACCOUNT_DUMP_ENTRIES);
+    let prefix = storage::Key::from(owner.to_db_key());
+    namada_storage::iter_prefix_bytes(ctx.wl_storage, &prefix)?
+        .skip(offset as usize)
+        .take(limit as usize)
+        .collect()
+}
+
 fn revealed<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
     owner: Address,
@@ -615,10 +956,21 @@ where
 
 #[cfg(test)]
 mod test {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use borsh_ext::BorshSerializeExt;
     use namada_core::types::address;
+    use namada_core::types::hash::Hash;
+    use namada_core::types::ibc::IbcEvent;
+    use namada_core::types::storage::{self, BlockHeight, KeySeg};
+    use namada_core::types::token::Amount;
+    use namada_storage::StorageWrite;
     use namada_token::storage_key::balance_key;
 
-    use crate::queries::RPC;
+    use super::{FeeParameters, VersionInfo, PROTOCOL_VERSION};
+    use crate::events::Event;
+    use crate::queries::testing::TestClient;
+    use crate::queries::{Router, RPC};
 
     #[test]
     fn test_shell_queries_router_paths() {
@@ -631,6 +983,12 @@ mod test {
         let path = RPC.shell().storage_value_path(&key);
         assert_eq!(format!("/shell/value/{}", key), path);
 
+        let path = RPC.shell().raw_value_path(&key);
+        assert_eq!(format!("/shell/raw/{}", key), path);
+
+        let path = RPC.shell().storage_value_chunk_path(&16, &8, &key);
+        assert_eq!(format!("/shell/value_chunk/16/8/{}", key), path);
+
         let path = RPC.shell().dry_run_tx_path();
         assert_eq!("/shell/dry_run_tx", path);
 
@@ -640,4 +998,283 @@ mod test {
         let path = RPC.shell().storage_has_key_path(&key);
         assert_eq!(format!("/shell/has_key/{}", key), path);
     }
+
+    #[test]
+    fn test_shell_queries_router_urls() {
+        let token_addr = address::testing::established_address_1();
+        let owner = address::testing::established_address_2();
+        let key = balance_key(&token_addr, &owner);
+
+        let height = Some(BlockHeight(5));
+        let url = RPC.shell().storage_value_url(height, true, &key);
+        assert_eq!(format!("/shell/value/{}?height=5&prove=true", key), url);
+
+        // Without a height, only the proof option is in the query string
+        let url = RPC.shell().storage_value_url(None, false, &key);
+        assert_eq!(format!("/shell/value/{}?prove=false", key), url);
+    }
+    #[test]
+    fn test_shell_queries_router_supports_proof() {
+        let token_addr = address::testing::established_address_1();
+        let owner = address::testing::established_address_2();
+        let key = balance_key(&token_addr, &owner);
+
+        // Storage reads support proofs
+        let path = RPC.shell().storage_value_path(&key);
+        assert_eq!(RPC.supports_proof(&path), Some(true));
+        let path = RPC.shell().raw_value_path(&key);
+        assert_eq!(RPC.supports_proof(&path), Some(true));
+        let path = RPC.shell().storage_prefix_path(&key);
+        assert_eq!(RPC.supports_proof(&path), Some(true));
+
+        // Other handlers don't
+        let path = RPC.shell().epoch_path();
+        assert_eq!(RPC.supports_proof(&path), Some(false));
+        let path = RPC.shell().storage_has_key_path(&key);
+        assert_eq!(RPC.supports_proof(&path), Some(false));
+        let path = RPC.shell().storage_value_chunk_path(&16, &8, &key);
+        assert_eq!(RPC.supports_proof(&path), Some(false));
+
+        // Unknown paths are not matched
+        assert_eq!(RPC.supports_proof("/shell/invalid"), None);
+        assert_eq!(RPC.supports_proof("/invalid"), None);
+    }
+
+    #[tokio::test]
+    async fn test_account_vp_hash_query() {
+        let mut client = TestClient::new(RPC);
+        let owner = address::testing::established_address_1();
+        let vp_hash = Hash::sha256(b"vp code");
+        client
+            .wl_storage
+            .write(&storage::Key::validity_predicate(&owner), vp_hash)
+            .unwrap();
+
+        let path = RPC.shell().account_vp_hash_path(&owner);
+        assert_eq!(format!("/shell/account/{owner}/vp_hash"), path);
+
+        // The seeded VP hash is returned
+        let read_hash =
+            RPC.shell().account_vp_hash(&client, &owner).await.unwrap();
+        assert_eq!(read_hash, vp_hash);
+
+        // Implicit accounts are governed by the implicit VP
+        let implicit_vp_hash = Hash::sha256(b"implicit vp code");
+        client
+            .wl_storage
+            .write(
+                &namada_parameters::storage::get_implicit_vp_key(),
+                implicit_vp_hash,
+            )
+            .unwrap();
+        let implicit = address::testing::gen_implicit_address();
+        let read_hash = RPC
+            .shell()
+            .account_vp_hash(&client, &implicit)
+            .await
+            .unwrap();
+        assert_eq!(read_hash, implicit_vp_hash);
+
+        // An account without a VP is not found
+        let unknown = address::testing::established_address_2();
+        let err = RPC
+            .shell()
+            .account_vp_hash(&client, &unknown)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No VP found"));
+    }
+
+    #[tokio::test]
+    async fn test_account_dump_query() {
+        let mut client = TestClient::new(RPC);
+        let owner = address::testing::established_address_1();
+        let other = address::testing::established_address_2();
+        let owner_prefix = storage::Key::from(owner.to_db_key());
+        let mut seeded = BTreeMap::new();
+        for i in 0_u64..5 {
+            let key = owner_prefix.push(&format!("key{i}")).unwrap();
+            client.wl_storage.write(&key, i).unwrap();
+            seeded.insert(key, i.serialize_to_vec());
+        }
+        // A key of another account is not dumped
+        let other_key = storage::Key::from(other.to_db_key())
+            .push(&"key0".to_string())
+            .unwrap();
+        client.wl_storage.write(&other_key, 0_u64).unwrap();
+
+        let path = RPC.shell().account_dump_path(&owner, &0, &10);
+        assert_eq!(format!("/shell/account/{owner}/dump/0/10"), path);
+
+        // All the seeded keys appear in a single page
+        let dump = RPC
+            .shell()
+            .account_dump(&client, &owner, &0, &10)
+            .await
+            .unwrap();
+        assert_eq!(dump, seeded);
+
+        // The same keys are dumped page by page
+        let mut paged = BTreeMap::new();
+        let mut offset = 0;
+        loop {
+            let page = RPC
+                .shell()
+                .account_dump(&client, &owner, &offset, &2)
+                .await
+                .unwrap();
+            offset += page.len() as u64;
+            let is_last = page.len() < 2;
+            paged.extend(page);
+            if is_last {
+                break;
+            }
+        }
+        assert_eq!(paged, seeded);
+
+        // An empty page is rejected
+        let err = RPC
+            .shell()
+            .account_dump(&client, &owner, &0, &0)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("The limit must be greater than 0"));
+    }
+
+    #[tokio::test]
+    async fn test_node_version_query() {
+        let client = TestClient::new(RPC);
+
+        let path = RPC.shell().node_version_path();
+        assert_eq!("/shell/node/version", path);
+
+        let version = RPC.shell().node_version(&client).await.unwrap();
+        assert_eq!(version.crate_version, env!("CARGO_PKG_VERSION"));
+        assert_eq!(version.protocol_version, PROTOCOL_VERSION);
+        assert_eq!(version, VersionInfo::current());
+    }
+
+    #[tokio::test]
+    async fn test_fee_parameters_query() {
+        let mut client = TestClient::new(RPC);
+
+        // Seed the fee parameters, the max block gas is set by the client
+        let minimum_gas_price =
+            BTreeMap::from([(address::nam(), Amount::from_u64(100))]);
+        client
+            .wl_storage
+            .write(
+                &namada_parameters::storage::get_gas_cost_key(),
+                &minimum_gas_price,
+            )
+            .unwrap();
+        client
+            .wl_storage
+            .write(
+                &namada_parameters::storage::get_fee_unshielding_gas_limit_key(
+                ),
+                20_000_u64,
+            )
+            .unwrap();
+        client
+            .wl_storage
+            .write(
+                &namada_parameters::storage::get_fee_unshielding_descriptions_limit_key(),
+                15_u64,
+            )
+            .unwrap();
+        client.wl_storage.commit_tx();
+
+        let path = RPC.shell().fee_parameters_path();
+        assert_eq!("/shell/parameters/fees", path);
+
+        let fee_parameters = RPC.shell().fee_parameters(&client).await.unwrap();
+        assert_eq!(
+            fee_parameters,
+            FeeParameters {
+                max_block_gas: 20_000_000,
+                fee_unshielding_gas_limit: 20_000,
+                fee_unshielding_descriptions_limit: 15,
+                minimum_gas_price,
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_events_at_query() {
+        let mut client = TestClient::new(RPC);
+
+        // Index the events emitted by two txs in a block and one in another
+        let event = |height: u64, tx_index: usize| {
+            let mut event = Event::from(IbcEvent {
+                event_type: "send_packet".to_string(),
+                attributes: Default::default(),
+            });
+            event["height"] = height.to_string();
+            event["tx_index"] = tx_index.to_string();
+            event
+        };
+        let block_events = vec![event(5, 0), event(5, 1), event(5, 1)];
+        client.event_log.log_events(block_events.clone());
+        client.event_log.log_events([event(6, 0)]);
+
+        let path = RPC.shell().events_at_path(&BlockHeight(5), &Some(1));
+        assert_eq!("/shell/events/5/1", path);
+
+        let events = RPC
+            .shell()
+            .events_at(&client, &BlockHeight(5), &None)
+            .await
+            .unwrap();
+        assert_eq!(events, block_events);
+
+        let events = RPC
+            .shell()
+            .events_at(&client, &BlockHeight(5), &Some(1))
+            .await
+            .unwrap();
+        assert_eq!(events, block_events[1..]);
+
+        // A block without any logged events
+        let events = RPC
+            .shell()
+            .events_at(&client, &BlockHeight(7), &None)
+            .await
+            .unwrap();
+        assert!(events.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_tx_changed_keys_query() {
+        let mut client = TestClient::new(RPC);
+
+        // Index the keys changed by two txs
+        let hash = Hash::sha256(b"tx");
+        let other_hash = Hash::sha256(b"other tx");
+        let changed_keys = BTreeSet::from([
+            storage::Key::parse("a").unwrap(),
+            storage::Key::parse("b/c").unwrap(),
+        ]);
+        client.event_log.log_tx_changed_keys([
+            (hash, changed_keys.clone()),
+            (
+                other_hash,
+                BTreeSet::from([storage::Key::parse("d").unwrap()]),
+            ),
+        ]);
+
+        let path = RPC.shell().tx_changed_keys_path(&hash);
+        assert_eq!(format!("/shell/tx/{hash}/changed_keys"), path);
+
+        let keys = RPC.shell().tx_changed_keys(&client, &hash).await.unwrap();
+        assert_eq!(keys, changed_keys.into_iter().collect::<Vec<_>>());
+
+        // A tx that is not indexed
+        let keys = RPC
+            .shell()
+            .tx_changed_keys(&client, &Hash::sha256(b"unknown tx"))
+            .await
+            .unwrap();
+        assert!(keys.is_empty());
+    }
 }
@@ -1,11 +1,14 @@
 use std::fmt::Debug;
+use std::sync::Arc;
 
-use namada_core::types::storage::BlockHeight;
+use borsh::{BorshDeserialize, BorshSerialize};
+use namada_core::types::storage::{self, BlockHeight};
 use namada_state::{DBIter, StorageHasher, WlStorage, DB};
 use thiserror::Error;
 
+use crate::control_flow::time::Instant;
 use crate::events::log::EventLog;
-use crate::tendermint::merkle::proof::ProofOps;
+use crate::tendermint::merkle::proof::{ProofOp, ProofOps};
 pub use crate::tendermint::v0_37::abci::request::Query as RequestQuery;
 /// A request context provides read-only access to storage and WASM compilation
 /// caches to request handlers.
@@ -27,6 +30,12 @@ where
     /// limit the how many block heights in the past can the storage be
     /// queried for reading values.
     pub storage_read_past_height_limit: Option<u64>,
+    /// Taken from config `max_request_path_len`. The request paths longer
+    /// than this number of bytes are rejected before they are matched
+    /// against any pattern.
+    pub max_request_path_len: usize,
+    /// When set, the hook is invoked with the timing of the request.
+    pub request_timing_hook: Option<RequestTimingHook>,
 }
 
 /// A `Router` handles parsing read-only query requests and dispatching them to
@@ -43,6 +52,47 @@ pub trait Router {
         D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
         H: 'static + StorageHasher + Sync,
     {
+        let Some(hook) = ctx.request_timing_hook.clone() else {
+            return self.handle_path(ctx, request);
+        };
+        let max_request_path_len = ctx.max_request_path_len;
+        let start = Instant::now();
+        let result = self.handle_path(ctx, request);
+        // The over-long paths are not matched against any pattern
+        let route = if request.path.len() > max_request_path_len {
+            None
+        } else {
+            self.route_label(&request.path)
+        };
+        (hook.0)(&RequestTiming {
+            route,
+            path: request.path.clone(),
+            start,
+            end: Instant::now(),
+        });
+        result
+    }
+
+    /// Internal method which shouldn't be invoked directly. Instead, you may
+    /// want to call `self.handle()`.
+    ///
+    /// Handle a given request using the provided context, without reporting
+    /// its timing.
+    fn handle_path<D, H, V, T>(
+        &self,
+        ctx: RequestCtx<'_, D, H, V, T>,
+        request: &RequestQuery,
+    ) -> namada_storage::Result<EncodedResponseQuery>
+    where
+        D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+        H: 'static + StorageHasher + Sync,
+    {
+        // Reject the over-long paths before any matching
+        if request.path.len() > ctx.max_request_path_len {
+            return Err(namada_storage::Error::SimpleMessage(
+                "Request paths over the maximum length are unsupported",
+            ));
+        }
         if !request.path.is_ascii() {
             return Err(namada_storage::Error::SimpleMessage(
                 "Non-ascii request paths are unsupported",
@@ -66,8 +116,102 @@ pub trait Router {
     where
         D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
         H: 'static + StorageHasher + Sync;
+
+    /// Check whether the handler matching the given path supports requests
+    /// with `prove` set, so that a client can decide up front whether to ask
+    /// for a proof. Returns `None` when no pattern matches the path. This must
+    /// be invoked on the root `Router` to be able to match the `path` fully.
+    fn supports_proof(&self, path: &str) -> Option<bool> {
+        if !path.is_ascii() {
+            return None;
+        }
+        let request = RequestQuery {
+            data: Default::default(),
+            path: path.to_owned(),
+            height: 0_u32.into(),
+            prove: true,
+        };
+        self.internal_supports_proof(&request, 0)
+    }
+
+    /// Internal method which shouldn't be invoked directly. Instead, you may
+    /// want to call `self.supports_proof()`.
+    ///
+    /// Check whether the handler matching `request.path`, starting at the
+    /// given `start` offset, supports requests with `prove` set.
+    fn internal_supports_proof(
+        &self,
+        request: &RequestQuery,
+        start: usize,
+    ) -> Option<bool>;
+
+    /// Get the label of the route matching the given path, which is the name
+    /// of its handler. Returns `None` when no pattern matches the path. This
+    /// must be invoked on the root `Router` to be able to match the `path`
+    /// fully.
+    fn route_label(&self, path: &str) -> Option<&'static str> {
+        if !path.is_ascii() {
+            return None;
+        }
+        let request = RequestQuery {
+            data: Default::default(),
+            path: path.to_owned(),
+            height: 0_u32.into(),
+            prove: false,
+        };
+        self.internal_route_label(&request, 0)
+    }
+
+    /// Internal method which shouldn't be invoked directly. Instead, you may
+    /// want to call `self.route_label()`.
+    ///
+    /// Get the label of the route matching `request.path`, starting at the
+    /// given `start` offset.
+    fn internal_route_label(
+        &self,
+        request: &RequestQuery,
+        start: usize,
+    ) -> Option<&'static str>;
+}
+
+/// The timing of a request handled by a [`Router`], reported to the
+/// [`RequestTimingHook`] of the request's context.
+#[derive(Clone, Debug)]
+pub struct RequestTiming {
+    /// The label of the matched route, which is the name of its handler, if
+    /// any route matched the request
+    pub route: Option<&'static str>,
+    /// The path of the request
+    pub path: String,
+    /// When the handling of the request started
+    pub start: Instant,
+    /// When the handling of the request ended
+    pub end: Instant,
+}
+
+/// A hook invoked with the timing of the requests handled by a [`Router`]
+/// with the hook in their [`RequestCtx`], regardless of which route they
+/// match. The requests' routes are only matched for their labels when a hook
+/// is set.
+#[derive(Clone)]
+pub struct RequestTimingHook(Arc<dyn Fn(&RequestTiming) + Send + Sync>);
+
+impl RequestTimingHook {
+    /// Create a hook from the given function
+    pub fn new(hook: impl Fn(&RequestTiming) + Send + Sync + 'static) -> Self {
+        Self(Arc::new(hook))
+    }
+}
+
+impl Debug for RequestTimingHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("RequestTimingHook").finish()
+    }
 }
 
+/// The default maximum length of a request path in bytes
+pub const DEFAULT_MAX_REQUEST_PATH_LEN: usize = 2048;
+
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
 pub enum Error {
@@ -92,5 +236,70 @@ pub struct ResponseQuery<T> {
     pub proof: Option<ProofOps>,
 }
 
+/// A storage value read by a query handler together with the proof of its
+/// existence. A handler that computes its data from storage values can return
+/// the witnesses of these values to allow a client to independently recompute
+/// and verify the data.
+#[derive(Clone, Debug)]
+pub struct StorageWitness {
+    /// The storage key
+    pub key: storage::Key,
+    /// The value read from storage
+    pub value: Vec<u8>,
+    /// The proof of the existence of the value
+    pub proof: ProofOps,
+}
+
+impl BorshSerialize for StorageWitness {
+    fn serialize<W: std::io::Write>(
+        &self,
+        writer: &mut W,
+    ) -> std::io::Result<()> {
+        let ops: Vec<(&String, &Vec<u8>, &Vec<u8>)> = self
+            .proof
+            .ops
+            .iter()
+            .map(|op| (&op.field_type, &op.key, &op.data))
+            .collect();
+        BorshSerialize::serialize(&(&self.key, &self.value, ops), writer)
+    }
+}
+
+impl BorshDeserialize for StorageWitness {
+    fn deserialize_reader<R: std::io::Read>(
+        reader: &mut R,
+    ) -> std::io::Result<Self> {
+        let (key, value, ops): (
+            storage::Key,
+            Vec<u8>,
+            Vec<(String, Vec<u8>, Vec<u8>)>,
+        ) = BorshDeserialize::deserialize_reader(reader)?;
+        let ops = ops
+            .into_iter()
+            .map(|(field_type, key, data)| ProofOp {
+                field_type,
+                key,
+                data,
+            })
+            .collect();
+        Ok(Self {
+            key,
+            value,
+            proof: ProofOps { ops },
+        })
+    }
+}
+
+/// The data of a response computed by a query handler from storage values,
+/// together with the witnesses of these values. The witnesses are only
+/// included when a proof is requested.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct Witnessed<T> {
+    /// The data computed from the storage values
+    pub data: T,
+    /// The witnesses of the storage values
+    pub witnesses: Vec<StorageWitness>,
+}
+
 /// [`ResponseQuery`] with borsh-encoded `data` field
 pub type EncodedResponseQuery = ResponseQuery<Vec<u8>>;
@@ -78,6 +78,9 @@ router! {POS,
         // TODO: add "below_threshold"
     },
 
+    ( "validators" / [epoch: opt Epoch] )
+        -> ValidatorSet = validator_set,
+
     ( "pos_params") -> PosParams = pos_params,
 
     ( "total_stake" / [epoch: opt Epoch] )
@@ -174,6 +177,20 @@ impl<T> Enriched<T> {
     }
 }
 
+/// The active validator set at some epoch, consisting of the consensus and
+/// the below-capacity validators with their bonded stake.
+#[derive(
+    Debug, Clone, PartialEq, Eq, BorshDeserialize, BorshSerialize, BorshSchema,
+)]
+pub struct ValidatorSet {
+    /// The epoch of the validator set
+    pub epoch: Epoch,
+    /// The validators in the consensus set
+    pub consensus: BTreeSet<WeightedValidator>,
+    /// The validators in the below-capacity set
+    pub below_capacity: BTreeSet<WeightedValidator>,
+}
+
 // Handlers that implement the functions via `trait StorageRead`:
 
 /// Get the PoS parameters
@@ -404,6 +421,32 @@ where
     )
 }
 
+/// Get the active validator set at the given epoch or current when `None`.
+fn validator_set<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    epoch: Option<Epoch>,
+) -> namada_storage::Result<ValidatorSet>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    let epoch = epoch.unwrap_or(ctx.wl_storage.storage.last_epoch);
+    let consensus = read_consensus_validator_set_addresses_with_stake(
+        ctx.wl_storage,
+        epoch,
+    )?;
+    let below_capacity =
+        read_below_capacity_validator_set_addresses_with_stake(
+            ctx.wl_storage,
+            epoch,
+        )?;
+    Ok(ValidatorSet {
+        epoch,
+        consensus,
+        below_capacity,
+    })
+}
+
 /// Get the total stake in PoS system at the given epoch or current when `None`.
 fn total_stake<D, H, V, T>(
     ctx: RequestCtx<'_, D, H, V, T>,
@@ -777,3 +820,97 @@ fn enrich_bonds_and_unbonds(
         total_withdrawable,
     }
 }
+
+#[cfg(test)]
+mod test {
+    use namada_core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada_core::types::dec::Dec;
+    use namada_core::types::key::testing::common_sk_from_simple_seed;
+    use namada_core::types::key::{self, RefTo};
+    use namada_proof_of_stake::test_utils::test_init_genesis;
+    use namada_proof_of_stake::types::GenesisValidator;
+    use namada_proof_of_stake::OwnedPosParams;
+
+    use super::*;
+    use crate::queries::testing::TestClient;
+    use crate::queries::RPC;
+
+    fn genesis_validator(
+        address: Address,
+        tokens: token::Amount,
+        seed: u64,
+    ) -> GenesisValidator {
+        let eth_key = || {
+            key::common::SecretKey::Secp256k1(key::testing::gen_keypair::<
+                key::secp256k1::SigScheme,
+            >())
+            .ref_to()
+        };
+        GenesisValidator {
+            address,
+            tokens,
+            consensus_key: common_sk_from_simple_seed(seed).ref_to(),
+            protocol_key: common_sk_from_simple_seed(seed + 1).ref_to(),
+            eth_cold_key: eth_key(),
+            eth_hot_key: eth_key(),
+            commission_rate: Dec::new(1, 1).unwrap(),
+            max_commission_rate_change: Dec::new(1, 1).unwrap(),
+            metadata: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_validator_set_query() {
+        let mut client = TestClient::new(RPC);
+        let validator_1 = established_address_1();
+        let validator_2 = established_address_2();
+        let stake_1 = token::Amount::native_whole(20);
+        let stake_2 = token::Amount::native_whole(10);
+        // Allow only one consensus validator, so that the other one is in
+        // the below-capacity set
+        let owned = OwnedPosParams {
+            max_validator_slots: 1,
+            ..Default::default()
+        };
+        test_init_genesis(
+            &mut client.wl_storage,
+            owned,
+            [
+                genesis_validator(validator_1.clone(), stake_1, 0),
+                genesis_validator(validator_2.clone(), stake_2, 2),
+            ]
+            .into_iter(),
+            Epoch::default(),
+        )
+        .unwrap();
+
+        let expected = ValidatorSet {
+            epoch: Epoch::default(),
+            consensus: BTreeSet::from([WeightedValidator {
+                bonded_stake: stake_1,
+                address: validator_1,
+            }]),
+            below_capacity: BTreeSet::from([WeightedValidator {
+                bonded_stake: stake_2,
+                address: validator_2,
+            }]),
+        };
+
+        // Without an epoch, the current epoch's set is returned
+        let validator_set =
+            RPC.vp().pos().validator_set(&client, &None).await.unwrap();
+        assert_eq!(validator_set, expected);
+
+        // With an explicit epoch
+        let epoch = Epoch(1);
+        let validator_set = RPC
+            .vp()
+            .pos()
+            .validator_set(&client, &Some(epoch))
+            .await
+            .unwrap();
+        assert_eq!(validator_set, ValidatorSet { epoch, ..expected });
+    }
+}
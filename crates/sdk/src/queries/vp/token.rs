@@ -2,7 +2,8 @@
 
 use namada_core::types::address::Address;
 use namada_core::types::token;
-use namada_state::{DBIter, StorageHasher, DB};
+use namada_state::{DBIter, StorageHasher, StorageRead, DB};
+use namada_token::storage_key::minted_balance_key;
 use namada_token::{read_denom, read_total_supply};
 
 use crate::queries::RequestCtx;
@@ -10,6 +11,7 @@ use crate::queries::RequestCtx;
 router! {TOKEN,
     ( "denomination" / [addr: Address] ) -> Option<token::Denomination> = denomination,
     ( "total_supply" / [addr: Address] ) -> token::Amount = total_supply,
+    ( [token: Address] / "supply" ) -> token::Amount = token_supply,
 }
 
 /// Get the number of decimal places (in base 10) for a
@@ -37,6 +39,26 @@ where
     read_total_supply(ctx.wl_storage, &addr)
 }
 
+/// Get the total supply of a token from its maintained counter. Unlike
+/// `total_supply`, this fails for a token that has no supply in storage.
+fn token_supply<D, H, V, T>(
+    ctx: RequestCtx<'_, D, H, V, T>,
+    token: Address,
+) -> namada_storage::Result<token::Amount>
+where
+    D: 'static + DB + for<'iter> DBIter<'iter> + Sync,
+    H: 'static + StorageHasher + Sync,
+{
+    ctx.wl_storage
+        .read(&minted_balance_key(&token))?
+        .ok_or_else(|| {
+            namada_storage::Error::new(std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("No total supply found for the token {token}"),
+            ))
+        })
+}
+
 #[cfg(any(test, feature = "async-client"))]
 pub mod client_only_methods {
     use borsh::BorshDeserialize;
@@ -74,3 +96,48 @@ pub mod client_only_methods {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use namada_core::types::address::testing::{
+        established_address_1, established_address_2,
+    };
+    use namada_state::StorageWrite;
+
+    use super::*;
+    use crate::queries::testing::TestClient;
+    use crate::queries::RPC;
+
+    #[tokio::test]
+    async fn test_token_supply_query() {
+        let mut client = TestClient::new(RPC);
+        let token = established_address_1();
+        let supply = token::Amount::native_whole(1_000);
+        client
+            .wl_storage
+            .write(&minted_balance_key(&token), supply)
+            .unwrap();
+
+        let path = RPC.vp().token().token_supply_path(&token);
+        assert_eq!(format!("/vp/token/{token}/supply"), path);
+
+        // The seeded supply is returned
+        let read_supply = RPC
+            .vp()
+            .token()
+            .token_supply(&client, &token)
+            .await
+            .unwrap();
+        assert_eq!(read_supply, supply);
+
+        // An unknown token is not found
+        let unknown = established_address_2();
+        let err = RPC
+            .vp()
+            .token()
+            .token_supply(&client, &unknown)
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("No total supply found"));
+    }
+}
@@ -46,7 +46,7 @@ use crate::internal_macros::echo_error;
 use crate::io::Io;
 use crate::masp::MaspTokenRewardData;
 use crate::queries::vp::pos::EnrichedBondsAndUnbondsDetails;
-use crate::queries::{Client, RPC};
+use crate::queries::{Client, StorageValueChunk, RPC};
 use crate::tendermint::block::Height;
 use crate::tendermint::merkle::proof::ProofOps;
 use crate::tendermint_rpc::error::Error as TError;
@@ -411,6 +411,44 @@ pub async fn query_storage_value_bytes<C: crate::queries::Client + Sync>(
     })
 }
 
+/// Query a storage value that may be too large to fit in a single response
+/// without decoding it. The value is read in chunks of at most `chunk_size`
+/// bytes from the given block height, which must be specific so that the value
+/// cannot change between the chunks, and reassembled.
+pub async fn query_storage_value_chunked<C: crate::queries::Client + Sync>(
+    client: &C,
+    key: &storage::Key,
+    height: BlockHeight,
+    chunk_size: u64,
+) -> Result<Option<Vec<u8>>, error::Error> {
+    let mut value = vec![];
+    let mut offset = 0_u64;
+    loop {
+        let response = convert_response::<C, _>(
+            RPC.shell()
+                .storage_value_chunk(
+                    client,
+                    None,
+                    Some(height),
+                    false,
+                    &offset,
+                    &chunk_size,
+                    key,
+                )
+                .await,
+        )?;
+        let StorageValueChunk { data, next_offset } = match response.data {
+            Some(chunk) => chunk,
+            None => return Ok(None),
+        };
+        value.extend(data);
+        match next_offset {
+            Some(next_offset) => offset = next_offset,
+            None => return Ok(Some(value)),
+        }
+    }
+}
+
 /// Query a range of storage values with a matching prefix and decode them with
 /// [`BorshDeserialize`]. Returns an iterator of the storage keys paired with
 /// their associated values.
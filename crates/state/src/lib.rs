@@ -38,7 +38,8 @@ pub use namada_storage::{Error as StorageError, Result as StorageResult, *};
 use thiserror::Error;
 use tx_queue::{ExpiredTxsQueue, TxQueue};
 pub use wl_storage::{
-    iter_prefix_post, iter_prefix_pre, PrefixIter, TempWlStorage, WlStorage,
+    iter_prefix_post, iter_prefix_post_after, iter_prefix_pre,
+    iter_prefix_pre_after, PrefixIter, TempWlStorage, WlStorage,
 };
 
 /// A result of a function that may fail
@@ -1261,6 +1262,11 @@ mod tests {
                 implicit_vp_code_hash: Some(Hash::zero()),
                 epochs_per_year: 100,
                 max_signatures_per_transaction: 15,
+                max_value_bytes: 1024 * 1024,
+                max_writes: 10_000,
+                max_events: 1_000,
+                max_verifiers: 128,
+                max_vp_previews: 16,
                 staked_ratio: Dec::new(1,1).expect("Cannot fail"),
                 pos_inflation_amount: token::Amount::zero(),
                 fee_unshielding_gas_limit: 20_000,
@@ -345,6 +345,75 @@ where
     )
 }
 
+/// Iterate write-log storage items prior to a tx execution, matching the
+/// given prefix, after the given key. Returns the iterator and gas cost.
+pub fn iter_prefix_pre_after<'iter, D, H>(
+    write_log: &'iter WriteLog,
+    storage: &'iter State<D, H>,
+    prefix: &storage::Key,
+    last_key: &str,
+) -> (PrefixIter<'iter, D>, u64)
+where
+    D: DB + for<'iter_> DBIter<'iter_>,
+    H: StorageHasher,
+{
+    let storage_iter = storage
+        .db
+        .iter_prefix_after(Some(prefix), last_key)
+        .peekable();
+    let write_log_iter = write_log.iter_prefix_pre(prefix).peekable();
+    (
+        PrefixIter::after(storage_iter, write_log_iter, last_key),
+        prefix.len() as u64 * namada_gas::STORAGE_ACCESS_GAS_PER_BYTE,
+    )
+}
+
+/// Iterate write-log storage items posterior to a tx execution, matching the
+/// given prefix, after the given key. Returns the iterator and gas cost.
+pub fn iter_prefix_post_after<'iter, D, H>(
+    write_log: &'iter WriteLog,
+    storage: &'iter State<D, H>,
+    prefix: &storage::Key,
+    last_key: &str,
+) -> (PrefixIter<'iter, D>, u64)
+where
+    D: DB + for<'iter_> DBIter<'iter_>,
+    H: StorageHasher,
+{
+    let storage_iter = storage
+        .db
+        .iter_prefix_after(Some(prefix), last_key)
+        .peekable();
+    let write_log_iter = write_log.iter_prefix_post(prefix).peekable();
+    (
+        PrefixIter::after(storage_iter, write_log_iter, last_key),
+        prefix.len() as u64 * namada_gas::STORAGE_ACCESS_GAS_PER_BYTE,
+    )
+}
+
+impl<'iter, D> PrefixIter<'iter, D>
+where
+    D: DB + DBIter<'iter>,
+{
+    /// Build an iterator from a storage iterator that already starts after the
+    /// given key. The write log modifications are held in memory, so the ones
+    /// up to and including the key are dropped from their iterator.
+    fn after(
+        storage_iter: Peekable<<D as DBIter<'iter>>::PrefixIter>,
+        mut write_log_iter: Peekable<write_log::PrefixIter>,
+        last_key: &str,
+    ) -> Self {
+        while write_log_iter
+            .next_if(|(key, _)| key.as_str() <= last_key)
+            .is_some()
+        {}
+        Self {
+            storage_iter,
+            write_log_iter,
+        }
+    }
+}
+
 impl<'iter, D> Iterator for PrefixIter<'iter, D>
 where
     D: DB + DBIter<'iter>,
@@ -234,6 +234,57 @@ impl WriteLog {
         Ok((gas as u64 * STORAGE_WRITE_GAS_PER_BYTE, size_diff))
     }
 
+    /// Get the number of keys modified by the current transaction
+    pub fn tx_modified_keys_count(&self) -> usize {
+        self.tx_write_log.len()
+    }
+
+    /// Apply the given changes on top of the current transaction's write log,
+    /// call the given function with the resulting write log and the gas cost
+    /// of the changes, and then revert the changes. Only the previous
+    /// modifications of the changed keys are kept aside, the rest of the write
+    /// log is not copied.
+    pub fn with_tx_changes<T>(
+        &mut self,
+        changes: Vec<(storage::Key, Option<Vec<u8>>)>,
+        f: impl FnOnce(&Self, u64) -> T,
+    ) -> Result<T> {
+        let mut previous = Vec::with_capacity(changes.len());
+        let mut gas: u64 = 0;
+        let mut applied = Ok(());
+        for (key, value) in changes {
+            previous.push((key.clone(), self.tx_write_log.get(&key).cloned()));
+            let change = match value {
+                Some(value) => self.write(&key, value),
+                None => self.delete(&key),
+            };
+            match change {
+                Ok((change_gas, _size_diff)) => {
+                    gas = gas.saturating_add(change_gas)
+                }
+                Err(err) => {
+                    applied = Err(err);
+                    break;
+                }
+            }
+        }
+        let result = applied.map(|()| f(self, gas));
+
+        // Revert in the reverse order, so that a key changed more than once
+        // gets back the modification it had before the first change
+        for (key, modification) in previous.into_iter().rev() {
+            match modification {
+                Some(modification) => {
+                    self.tx_write_log.insert(key, modification);
+                }
+                None => {
+                    self.tx_write_log.remove(&key);
+                }
+            }
+        }
+        result
+    }
+
     /// Write a key and a value.
     /// Fails with [`Error::UpdateVpOfNewAccount`] when attempting to update a
     /// validity predicate of a new account that's not yet committed to storage.
@@ -478,11 +529,13 @@ impl WriteLog {
     }
 
     /// Drop the current transaction's write log and precommit when it's
-    /// declined by any of the triggered validity predicates. Starts a new
-    /// transaction write log.
+    /// declined by any of the triggered validity predicates. All the
+    /// modifications accumulated since the transaction started and its IBC
+    /// events are discarded. Starts a new transaction write log.
     pub fn drop_tx(&mut self) {
         self.tx_precommit_write_log.clear();
         self.tx_write_log.clear();
+        self.ibc_events.clear();
     }
 
     /// Drop the current transaction's write log but keep the precommit one.
@@ -955,6 +1008,54 @@ mod tests {
         assert_eq!(value, None);
     }
 
+    #[test]
+    fn test_drop_tx() {
+        let mut storage = crate::testing::TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let mut batch = crate::testing::TestStorage::batch();
+        let address_gen = EstablishedAddressGen::new("test");
+
+        let key1 =
+            storage::Key::parse("key1").expect("cannot parse the key string");
+        let key2 =
+            storage::Key::parse("key2").expect("cannot parse the key string");
+        let key3 =
+            storage::Key::parse("key3").expect("cannot parse the key string");
+
+        // a part of the tx is precommitted
+        let val = "val".as_bytes().to_vec();
+        write_log.write(&key1, val.clone()).unwrap();
+        write_log.precommit_tx();
+
+        // the rest of the tx modifications
+        let vp = Hash::sha256("vp".as_bytes());
+        let (addr, _) = write_log.init_account(&address_gen, vp);
+        write_log.write(&key2, val.clone()).unwrap();
+        write_log.write_temp(&key3, val).unwrap();
+        write_log.emit_ibc_event(IbcEvent {
+            event_type: "test".to_string(),
+            attributes: Default::default(),
+        });
+
+        // a VP rejects the tx
+        write_log.drop_tx();
+        assert!(write_log.get_keys_with_precommit().is_empty());
+        assert!(write_log.get_ibc_events().is_empty());
+
+        // commit a block
+        write_log
+            .commit_block(&mut storage, &mut batch)
+            .expect("commit failed");
+
+        let (vp_code_hash, _gas) =
+            storage.validity_predicate(&addr).expect("vp read failed");
+        assert!(vp_code_hash.is_none());
+        for key in [key1, key2, key3] {
+            let (value, _) = storage.read(&key).expect("read failed");
+            assert!(value.is_none());
+        }
+    }
+
     #[test]
     fn test_replay_protection_commit() {
         let mut storage = crate::testing::TestStorage::default();
@@ -275,6 +275,15 @@ pub trait DBIter<'iter> {
     /// ordered by the storage keys.
     fn iter_prefix(&'iter self, prefix: Option<&Key>) -> Self::PrefixIter;
 
+    /// Like [`DBIter::iter_prefix`], but the iterator starts after the given
+    /// key. The DB iterator is positioned with a seek, so the key value pairs
+    /// up to and including the given key are not read.
+    fn iter_prefix_after(
+        &'iter self,
+        prefix: Option<&Key>,
+        last_key: &str,
+    ) -> Self::PrefixIter;
+
     /// Read results subspace key value pairs from the DB
     fn iter_results(&'iter self) -> Self::PrefixIter;
 
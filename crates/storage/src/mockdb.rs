@@ -714,23 +714,15 @@ impl<'iter> DBIter<'iter> for MockDB {
     type PrefixIter = MockPrefixIterator;
 
     fn iter_prefix(&'iter self, prefix: Option<&Key>) -> MockPrefixIterator {
-        let stripped_prefix = "subspace/".to_owned();
-        let prefix = format!(
-            "{}{}",
-            stripped_prefix,
-            match prefix {
-                Some(prefix) => {
-                    if prefix == &Key::default() {
-                        prefix.to_string()
-                    } else {
-                        format!("{prefix}/")
-                    }
-                }
-                None => "".to_string(),
-            }
-        );
-        let iter = self.0.borrow().clone().into_iter();
-        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+        self.iter_subspace_prefix(prefix, None)
+    }
+
+    fn iter_prefix_after(
+        &'iter self,
+        prefix: Option<&Key>,
+        last_key: &str,
+    ) -> MockPrefixIterator {
+        self.iter_subspace_prefix(prefix, Some(last_key))
     }
 
     fn iter_results(&'iter self) -> MockPrefixIterator {
@@ -791,6 +783,40 @@ impl<'iter> DBIter<'iter> for MockDB {
     }
 }
 
+impl MockDB {
+    /// Iterate the subspace keys matching the given prefix, after the given
+    /// key, if any.
+    fn iter_subspace_prefix(
+        &self,
+        prefix: Option<&Key>,
+        last_key: Option<&str>,
+    ) -> MockPrefixIterator {
+        let stripped_prefix = "subspace/".to_owned();
+        let prefix = format!(
+            "{}{}",
+            stripped_prefix,
+            match prefix {
+                Some(prefix) => {
+                    if prefix == &Key::default() {
+                        prefix.to_string()
+                    } else {
+                        format!("{prefix}/")
+                    }
+                }
+                None => "".to_string(),
+            }
+        );
+        let mut db = self.0.borrow().clone();
+        if let Some(last_key) = last_key {
+            // Keep only the keys after the `last_key`, of which the smallest
+            // one is the `last_key` followed by a nul char
+            db = db.split_off(&format!("{stripped_prefix}{last_key}\0"));
+        }
+        let iter = db.into_iter();
+        MockPrefixIterator::new(MockIterator { prefix, iter }, stripped_prefix)
+    }
+}
+
 /// A prefix iterator base for the [`MockPrefixIterator`].
 #[derive(Debug)]
 pub struct MockIterator {
@@ -27,6 +27,7 @@ mod tests {
     use namada::ibc::context::transfer_mod::testing::DummyTransferModule;
     use namada::ibc::primitives::Msg;
     use namada::ibc::Error as IbcActionError;
+    use namada::ledger::gas::{GasMetering, TxGasMeter};
     use namada::ledger::ibc::storage as ibc_storage;
     use namada::ledger::native_vp::ibc::{
         get_dummy_header as tm_dummy_header, Error as IbcError,
@@ -36,13 +37,19 @@ mod tests {
     use namada::tx::Tx;
     use namada::types::hash::Hash;
     use namada::types::key::*;
-    use namada::types::storage::{self, BlockHash, BlockHeight, Key, KeySeg};
-    use namada::types::time::DateTimeUtc;
+    use namada::types::storage::{
+        self, BlockHash, BlockHeight, DbKeySeg, Key, KeySeg,
+    };
+    use namada::types::time::{DateTimeUtc, DurationSecs};
     use namada::types::{address, key};
+    use namada::vm::host_env;
+    use namada::vm::prefix_iter::PrefixIterators;
     use namada_test_utils::TestWasms;
     use namada_tx_prelude::address::InternalAddress;
     use namada_tx_prelude::chain::ChainId;
-    use namada_tx_prelude::{Address, StorageRead, StorageWrite};
+    use namada_tx_prelude::{
+        Address, BorshDeserialize, ResultExt, StorageRead, StorageWrite,
+    };
     use namada_vp_prelude::account::AccountPublicKeysMap;
     use namada_vp_prelude::{sha256, VpEnv};
     use prost::Message;
@@ -185,6 +192,475 @@ mod tests {
         itertools::assert_equal(iter, expected);
     }
 
+    #[test]
+    fn test_tx_too_many_open_iterators() {
+        // The environment must be initialized first
+        tx_host_env::init();
+        let max_open = 2;
+        tx_host_env::with(|env| {
+            env.iterators = PrefixIterators::new(max_open);
+        });
+
+        let prefix = storage::Key::parse("prefix").unwrap();
+        // Exhausted iterators don't count towards the limit
+        for _ in 0..=max_open {
+            let mut iter =
+                namada_tx_prelude::iter_prefix_bytes(tx::ctx(), &prefix)
+                    .unwrap();
+            assert!(iter.next().is_none());
+        }
+
+        // Open as many iterators as allowed without exhausting them
+        let _iters: Vec<_> = (0..max_open)
+            .map(|_| tx::ctx().iter_prefix(&prefix).unwrap())
+            .collect();
+
+        // One more iterator is over the limit
+        assert!(
+            panic::catch_unwind(|| {
+                tx::ctx().iter_prefix(&prefix).unwrap();
+            })
+            .err()
+            .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+            .unwrap()
+            .contains("TooManyOpenIterators")
+        );
+    }
+
+    #[test]
+    fn test_tx_read_chain_param() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Seed a protocol parameter
+        let key = namada::ledger::parameters::storage::get_max_block_gas_key();
+        let max_block_gas = 12_345_u64;
+        tx_host_env::with(|env| {
+            env.wl_storage.write(&key, max_block_gas).unwrap();
+        });
+
+        let read_value: Option<u64> =
+            tx::ctx().read_chain_param("max_block_gas").unwrap();
+        assert_eq!(read_value, Some(max_block_gas));
+
+        // A known parameter without a value is not found
+        let read_value = tx::ctx()
+            .read_chain_param_bytes("fee_unshielding_gas_limit")
+            .unwrap();
+        assert_eq!(read_value, None);
+
+        // An unknown parameter is an error
+        assert!(
+            panic::catch_unwind(|| {
+                tx::ctx().read_chain_param_bytes("unknown").unwrap();
+            })
+            .err()
+            .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+            .unwrap()
+            .contains("UnknownChainParameter")
+        );
+    }
+
+    #[test]
+    fn test_tx_make_key() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let addr = address::testing::established_address_1();
+        let expected_key = storage::Key::from(addr.to_db_key())
+            .push(&"balance".to_owned())
+            .unwrap();
+
+        // Make the key from its segments and write to it
+        let key = tx::ctx()
+            .make_key(&[
+                DbKeySeg::AddressSeg(addr),
+                DbKeySeg::StringSeg("balance".to_owned()),
+            ])
+            .unwrap();
+        assert_eq!(key, expected_key);
+        tx::ctx().write(&key, 100_u64).unwrap();
+
+        // The host sees the write under the same key
+        let read_value: Option<u64> = tx_host_env::with(|env| {
+            env.wl_storage.read(&expected_key).unwrap()
+        });
+        assert_eq!(read_value, Some(100));
+
+        // Segments that don't form a valid key are rejected
+        assert!(tx::ctx().make_key(&[]).is_err());
+        let invalid_seg = DbKeySeg::StringSeg("a/b".to_owned());
+        assert!(tx::ctx().make_key(&[invalid_seg]).is_err());
+    }
+
+    #[test]
+    fn test_tx_and_vp_read_epoch() {
+        let mut tx_env = TestTxEnv::default();
+        let epoch = storage::Epoch(5);
+        tx_env.wl_storage.storage.block.epoch = epoch;
+
+        let addr = address::testing::established_address_1();
+        let key = storage::Key::from(addr.to_db_key())
+            .push(&"epoch".to_owned())
+            .unwrap();
+
+        // Initialize the VP environment via a transaction that reads the
+        // epoch and writes it to storage
+        vp_host_env::init_from_tx(addr, tx_env, |_addr| {
+            let gas_before =
+                tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+            let tx_epoch = tx::ctx().get_block_epoch().unwrap();
+            let gas_after =
+                tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+            assert!(gas_after > gas_before, "Reading the epoch must use gas");
+            tx::ctx().write(&key, tx_epoch).unwrap();
+        });
+
+        // The epoch written by the tx matches the seeded epoch
+        let written: Option<storage::Epoch> = vp::CTX.read_post(&key).unwrap();
+        assert_eq!(written, Some(epoch));
+
+        // The VP sees the same epoch
+        assert_eq!(vp::CTX.get_block_epoch().unwrap(), epoch);
+    }
+
+    #[test]
+    fn test_tx_and_vp_get_block_time() {
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let token = address::nam();
+        let amount = Amount::native_whole(10);
+        let now = DateTimeUtc::now();
+        let deadline = now + DurationSecs(10);
+        let header = |time| storage::Header {
+            hash: Hash::default(),
+            time,
+            next_validators_hash: Hash::default(),
+        };
+
+        // A tx that transfers tokens only until the deadline encoded in its
+        // data
+        let apply_tx = |tx_data: &[u8]| -> namada_tx_prelude::TxResult {
+            let deadline =
+                DateTimeUtc::try_from_slice(tx_data).into_storage_result()?;
+            if tx::ctx().get_block_time()? > deadline {
+                return Err(namada_tx_prelude::Error::new_const(
+                    "The transfer deadline has passed",
+                ));
+            }
+            namada_tx_prelude::token::undenominated_transfer(
+                tx::ctx(),
+                &src,
+                &dest,
+                &token,
+                amount,
+            )
+        };
+
+        for (block_time, is_accepted) in
+            [(now, true), (deadline + DurationSecs(10), false)]
+        {
+            let mut env = TestTxEnv::default();
+            env.spawn_accounts([&src, &dest, &token]);
+            env.credit_tokens(&src, &token, amount);
+            env.wl_storage.storage.header = Some(header(block_time));
+            tx_host_env::set(env);
+
+            // The tx sees the time of the current block
+            assert_eq!(tx::ctx().get_block_time().unwrap(), block_time);
+
+            let result = apply_tx(&deadline.serialize_to_vec());
+            assert_eq!(result.is_ok(), is_accepted);
+            let dest_balance: Option<Amount> = tx::ctx()
+                .read(&token::storage_key::balance_key(&token, &dest))
+                .unwrap();
+            assert_eq!(dest_balance.is_some(), is_accepted);
+        }
+
+        // Without a block header, the block time is not available
+        tx_host_env::init();
+        assert!(tx::ctx().get_block_time().is_err());
+
+        // The VP sees the time of the current block too
+        vp_host_env::init();
+        assert!(vp::CTX.get_block_time().is_err());
+        vp_host_env::with(|env| {
+            env.wl_storage.storage.header = Some(header(now));
+        });
+        assert_eq!(vp::CTX.get_block_time().unwrap(), now);
+    }
+
+    #[test]
+    fn test_vp_has_verifier() {
+        let co_signer = address::testing::established_address_2();
+        // A VP that only accepts the tx if the co-signer's VP is triggered too
+        let vp_requiring_co_signer =
+            || vp::CTX.has_verifier(&co_signer).unwrap();
+
+        for with_co_signer in [true, false] {
+            let mut tx_env = TestTxEnv::default();
+            tx_env.spawn_accounts([&co_signer]);
+            let addr = address::testing::established_address_1();
+            vp_host_env::init_from_tx(addr, tx_env, |addr| {
+                let key = storage::Key::from(addr.to_db_key())
+                    .push(&"counter".to_owned())
+                    .unwrap();
+                tx::ctx().write(&key, 1_u64).unwrap();
+                if with_co_signer {
+                    tx::ctx().insert_verifier(&co_signer).unwrap();
+                }
+            });
+
+            assert_eq!(vp_requiring_co_signer(), with_co_signer);
+            // The VP's own address is always a verifier
+            let addr = address::testing::established_address_1();
+            assert!(vp::CTX.has_verifier(&addr).unwrap());
+        }
+    }
+
+    #[test]
+    fn test_vp_read_with_proof() {
+        use ics23::HostFunctionsManager;
+        use namada::state::ics23_specs::ibc_proof_specs;
+        use namada::state::{Sha256Hasher, StoreType};
+        use namada::tendermint_proto::v0_37::crypto::ProofOps;
+
+        let mut tx_env = TestTxEnv::default();
+
+        // Commit an IBC value in a block
+        let key = storage::Key::from(
+            Address::Internal(InternalAddress::Ibc).to_db_key(),
+        )
+        .push(&"proven".to_string())
+        .unwrap();
+        let committed = vec![1_u8; 8];
+        tx_env.wl_storage.storage.block.height = BlockHeight(1);
+        tx_env.wl_storage.write_bytes(&key, &committed).unwrap();
+        tx_env.wl_storage.commit_tx();
+        tx_env.wl_storage.commit_block().unwrap();
+        let root = tx_env.wl_storage.storage.merkle_root().0.to_vec();
+
+        // The tx changes the committed value
+        let addr = address::testing::established_address_1();
+        vp_host_env::init_from_tx(addr, tx_env, |addr| {
+            tx::ctx().write_bytes(&key, [2_u8; 8]).unwrap();
+            let counter = storage::Key::from(addr.to_db_key())
+                .push(&"counter".to_owned())
+                .unwrap();
+            tx::ctx().write(&counter, 1_u64).unwrap();
+        });
+
+        // The VP reads the committed value, not the changed one
+        let read = vp::CTX.read_with_proof(&key).unwrap().unwrap();
+        assert_eq!(read.value, committed);
+        assert_eq!(vp::CTX.read_bytes_post(&key).unwrap(), Some(vec![2; 8]));
+
+        // The proof verifies the value against the committed root
+        let proof = ProofOps::decode(read.proof.as_slice()).unwrap();
+        assert_eq!(proof.ops.len(), 2);
+        let (store_type, sub_key) = StoreType::sub_key(&key).unwrap();
+        let paths = [sub_key.to_string(), store_type.to_string()];
+        let specs = ibc_proof_specs::<Sha256Hasher>();
+        let mut value = read.value;
+        for ((op, spec), path) in proof.ops.iter().zip(&specs).zip(&paths) {
+            let commitment_proof =
+                ics23::CommitmentProof::decode(op.data.as_slice()).unwrap();
+            let existence_proof = match commitment_proof.proof.clone() {
+                Some(ics23::commitment_proof::Proof::Exist(ep)) => ep,
+                _ => panic!("Expected an existence proof"),
+            };
+            let sub_root = ics23::calculate_existence_root::<
+                HostFunctionsManager,
+            >(&existence_proof)
+            .unwrap();
+            assert!(ics23::verify_membership::<HostFunctionsManager>(
+                &commitment_proof,
+                spec,
+                &sub_root,
+                path.as_bytes(),
+                &value,
+            ));
+            value = sub_root;
+        }
+        assert_eq!(value, root);
+
+        // A missing key has no value nor proof
+        let missing = key.push(&"missing".to_string()).unwrap();
+        assert!(vp::CTX.read_with_proof(&missing).unwrap().is_none());
+    }
+
+    #[test]
+    fn test_vp_verify_merkle_proof() {
+        let mut tx_env = TestTxEnv::default();
+
+        // Commit an IBC value in a block
+        let key = storage::Key::from(
+            Address::Internal(InternalAddress::Ibc).to_db_key(),
+        )
+        .push(&"proven".to_string())
+        .unwrap();
+        let committed = vec![1_u8; 8];
+        tx_env.wl_storage.storage.block.height = BlockHeight(1);
+        tx_env.wl_storage.write_bytes(&key, &committed).unwrap();
+        tx_env.wl_storage.commit_tx();
+        tx_env.wl_storage.commit_block().unwrap();
+        let root = Hash::from(tx_env.wl_storage.storage.merkle_root());
+
+        // The tx stores the trusted root in the account's storage
+        let addr = address::testing::established_address_1();
+        let root_key = storage::Key::from(addr.to_db_key())
+            .push(&"trusted_root".to_owned())
+            .unwrap();
+        vp_host_env::init_from_tx(addr, tx_env, |_addr| {
+            tx::ctx().write(&root_key, root).unwrap();
+        });
+        let proof = vp::CTX.read_with_proof(&key).unwrap().unwrap().proof;
+
+        // A VP that accepts only when the proof verifies against the root
+        // stored in the changed keys
+        let validate = |value: &[u8], proof: &[u8]| -> bool {
+            let root: Hash = vp::CTX.read_post(&root_key).unwrap().unwrap();
+            vp::CTX
+                .verify_merkle_proof(&root, &key, value, proof)
+                .unwrap()
+        };
+
+        // The proof verifies the committed value
+        assert!(validate(&committed, &proof));
+
+        // A different value, a tampered proof or a malformed proof don't
+        let mut tampered = proof.clone();
+        let last = tampered.len() - 1;
+        tampered[last] ^= 1;
+        assert!(!validate(&[2_u8; 8], &proof));
+        assert!(!validate(&committed, &tampered));
+        assert!(!validate(&committed, &[]));
+
+        // Nor does the proof verify against a different root
+        assert!(!vp::CTX
+            .verify_merkle_proof(&Hash::zero(), &key, &committed, &proof)
+            .unwrap());
+    }
+
+    #[test]
+    fn test_tx_get_fee_payer() {
+        // A tx that records its fee payer in storage
+        let key = storage::Key::parse("fee_payer").unwrap();
+        let apply_tx = || -> namada_tx_prelude::TxResult {
+            let fee_payer = tx::ctx().get_fee_payer()?;
+            tx::ctx().write(&key, fee_payer)
+        };
+
+        let payer = address::testing::established_address_1();
+        for fee_payer in [Some(payer), None] {
+            // The environment must be initialized first
+            tx_host_env::init();
+            // Seed the fee payer established by the node
+            tx_host_env::with(|env| env.fee_payer = fee_payer.clone());
+
+            apply_tx().unwrap();
+            let written: Option<Address> =
+                tx::ctx().read(&key).unwrap().unwrap();
+            assert_eq!(written, fee_payer);
+        }
+    }
+
+    #[test]
+    fn test_tx_delete_prefix() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let other = storage::Key::parse("other").unwrap();
+
+        // Write some values directly into the storage and some into the
+        // write log
+        tx_host_env::with(|env| {
+            for i in [1_i32, 2, 3] {
+                let key = prefix.push(&i).unwrap();
+                env.wl_storage
+                    .storage
+                    .write(&key, i.serialize_to_vec())
+                    .unwrap();
+            }
+        });
+        for i in [4_i32, 5] {
+            let key = prefix.push(&i).unwrap();
+            tx::ctx().write(&key, i).unwrap();
+        }
+        tx::ctx().write(&other, 0_i32).unwrap();
+
+        tx::ctx().delete_prefix(&prefix).unwrap();
+
+        let mut iter =
+            namada_tx_prelude::iter_prefix_bytes(tx::ctx(), &prefix).unwrap();
+        assert!(
+            iter.next().is_none(),
+            "After the prefix has been deleted, no keys should be found"
+        );
+        for i in [1_i32, 2, 3, 4, 5] {
+            let key = prefix.push(&i).unwrap();
+            let read_value: Option<i32> = tx::ctx().read(&key).unwrap();
+            assert_eq!(None, read_value);
+        }
+        assert!(
+            tx::ctx().has_key(&other).unwrap(),
+            "Keys outside of the prefix must not be deleted"
+        );
+    }
+
+    #[test]
+    fn test_host_call_gas_schedule() {
+        let schedule = host_env::host_call_gas_schedule();
+        for host_call in [
+            "tx_read",
+            "tx_has_key",
+            "tx_write",
+            "tx_delete",
+            "tx_iter_prefix",
+            "tx_iter_next",
+            "vp_read_pre",
+            "vp_read_post",
+            "vp_has_key_pre",
+            "vp_has_key_post",
+            "vp_iter_prefix_pre",
+            "vp_iter_prefix_post",
+            "vp_iter_next",
+        ] {
+            assert!(
+                schedule.contains_key(host_call),
+                "Missing gas cost for {host_call}"
+            );
+        }
+
+        // The environment must be initialized first
+        tx_host_env::init();
+        let consumed_gas =
+            || tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+
+        // NOTE: the native memory used in tests doesn't charge gas for memory
+        // accesses from the guest, only the host side accesses are charged
+        let key = storage::Key::parse("key").unwrap();
+        let before = consumed_gas();
+        tx::ctx().has_key(&key).unwrap();
+        let cost = schedule["tx_has_key"];
+        let expected = key.len() as u64
+            * (cost.memory_access_per_byte + cost.storage_access_per_byte);
+        assert_eq!(consumed_gas().checked_sub(before), Some(expected.into()));
+
+        // The first write of a tx also reads the tx limits parameters
+        tx::ctx()
+            .write_bytes(&storage::Key::parse("other").unwrap(), [0])
+            .unwrap();
+        let value = vec![1_u8; 100];
+        let before = consumed_gas();
+        tx::ctx().write_bytes(&key, &value).unwrap();
+        let cost = schedule["tx_write"];
+        let expected =
+            (key.len() + value.len()) as u64 * cost.storage_write_per_byte;
+        assert_eq!(consumed_gas().checked_sub(before), Some(expected.into()));
+    }
+
     #[test]
     fn test_tx_insert_verifier() {
         // The environment must be initialized first
@@ -207,6 +683,574 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_tx_insert_verifier_of_unmodified_account() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let modified = address::testing::established_address_1();
+        let co_signer = address::testing::established_address_2();
+        tx_host_env::with(|env| {
+            env.spawn_accounts([&modified, &co_signer]);
+        });
+
+        // Modify only one of the accounts, but require the other's VP too
+        let key = storage::Key::from(modified.to_db_key())
+            .push(&"counter".to_owned())
+            .unwrap();
+        tx::ctx().write(&key, 1_u64).unwrap();
+        tx::ctx().insert_verifier(&co_signer).unwrap();
+
+        // Both of the accounts' VPs are scheduled to validate the tx, even
+        // though no storage key of the co-signer was changed
+        let verifiers = tx_host_env::with(|env| env.get_verifiers());
+        assert!(verifiers.contains(&modified));
+        assert!(verifiers.contains(&co_signer));
+        let changed_keys =
+            tx_host_env::with(|env| env.all_touched_storage_keys());
+        let is_co_signer_changed = changed_keys
+            .iter()
+            .any(|key| key.find_addresses().contains(&co_signer));
+        assert!(!is_co_signer_changed);
+    }
+
+    #[test]
+    fn test_tx_write_value_over_limit() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Lower the limit on the size of written values
+        let max_value_bytes = 32_u64;
+        tx_host_env::with(|env| {
+            let key =
+                namada::ledger::parameters::storage::get_max_value_bytes_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_value_bytes.serialize_to_vec())
+                .unwrap();
+        });
+
+        // A value within the limit can be written
+        let key = storage::Key::parse("key").unwrap();
+        let value = vec![1_u8; max_value_bytes as usize];
+        tx::ctx().write_bytes(&key, &value).unwrap();
+        assert_eq!(tx::ctx().read_bytes(&key).unwrap(), Some(value));
+
+        // A value over the limit is rejected
+        let value = vec![2_u8; max_value_bytes as usize + 1];
+        let error = panic::catch_unwind(|| {
+            tx::ctx().write_bytes(&key, &value).unwrap()
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("ValueTooLarge"));
+    }
+
+    #[test]
+    fn test_tx_too_many_writes() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Lower the limit on the number of written keys
+        let max_writes = 3_u64;
+        tx_host_env::with(|env| {
+            let key = namada::ledger::parameters::storage::get_max_writes_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_writes.serialize_to_vec())
+                .unwrap();
+        });
+
+        // Writing up to the limit of keys is fine and so is rewriting them
+        let keys = (0..=max_writes)
+            .map(|i| storage::Key::parse(format!("key{i}")).unwrap())
+            .collect::<Vec<_>>();
+        for key in &keys[..max_writes as usize] {
+            tx::ctx().write(key, 1_u64).unwrap();
+        }
+        tx::ctx().write(&keys[0], 2_u64).unwrap();
+
+        // Writing one more key is over the limit
+        let error = panic::catch_unwind(|| {
+            tx::ctx().write(&keys[max_writes as usize], 1_u64).unwrap()
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("TooManyWrites"));
+    }
+
+    #[test]
+    fn test_tx_too_many_deletes() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Lower the limit on the number of written keys
+        let max_writes = 2_u64;
+        let keys = (0..=max_writes)
+            .map(|i| storage::Key::parse(format!("key{i}")).unwrap())
+            .collect::<Vec<_>>();
+        tx_host_env::with(|env| {
+            let key = namada::ledger::parameters::storage::get_max_writes_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_writes.serialize_to_vec())
+                .unwrap();
+            for key in &keys {
+                env.wl_storage
+                    .storage
+                    .write(key, 1_u64.serialize_to_vec())
+                    .unwrap();
+            }
+        });
+
+        // Deleting up to the limit of keys is fine
+        for key in &keys[..max_writes as usize] {
+            tx::ctx().delete(key).unwrap();
+        }
+
+        // Deleting one more key is over the limit
+        let error = panic::catch_unwind(|| {
+            tx::ctx().delete(&keys[max_writes as usize]).unwrap()
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("TooManyWrites"));
+
+        // The same applies to deleting the keys by their prefix
+        tx_host_env::init();
+        tx_host_env::with(|env| {
+            let key = namada::ledger::parameters::storage::get_max_writes_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_writes.serialize_to_vec())
+                .unwrap();
+            for key in &keys {
+                let key = storage::Key::parse("prefix").unwrap().join(key);
+                env.wl_storage
+                    .storage
+                    .write(&key, 1_u64.serialize_to_vec())
+                    .unwrap();
+            }
+        });
+        let prefix = storage::Key::parse("prefix").unwrap();
+        let error =
+            panic::catch_unwind(|| tx::ctx().delete_prefix(&prefix).unwrap())
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+                .unwrap();
+        assert!(error.contains("TooManyWrites"));
+    }
+
+    #[test]
+    fn test_tx_limits_read_once() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let max_writes = 1_u64;
+        let key = namada::ledger::parameters::storage::get_max_writes_key();
+        tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .write(&key, max_writes.serialize_to_vec())
+                .unwrap();
+        });
+
+        // The limits are read and charged on the first write only
+        let consumed_gas =
+            || tx_host_env::with(|env| env.gas_meter.get_tx_consumed_gas());
+        let before = consumed_gas();
+        tx::ctx()
+            .write(&storage::Key::parse("key0").unwrap(), 1_u64)
+            .unwrap();
+        let first_write_gas = consumed_gas().checked_sub(before).unwrap();
+        let before = consumed_gas();
+        tx::ctx()
+            .write(&storage::Key::parse("key0").unwrap(), 2_u64)
+            .unwrap();
+        let second_write_gas = consumed_gas().checked_sub(before).unwrap();
+        assert!(first_write_gas > second_write_gas);
+
+        // A change of the limit within the tx has no effect on the tx
+        tx_host_env::with(|env| {
+            env.wl_storage
+                .storage
+                .write(&key, 10_u64.serialize_to_vec())
+                .unwrap();
+        });
+        let error = panic::catch_unwind(|| {
+            tx::ctx()
+                .write(&storage::Key::parse("key1").unwrap(), 1_u64)
+                .unwrap()
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("TooManyWrites"));
+    }
+
+    #[test]
+    fn test_tx_increment() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // A key without a value is incremented from zero
+        let key = storage::Key::parse("counter").unwrap();
+        assert_eq!(tx::ctx().increment(&key, 5).unwrap(), 5);
+        assert_eq!(tx::ctx().increment(&key, 3).unwrap(), 8);
+        let value: Option<u64> = tx::ctx().read(&key).unwrap();
+        assert_eq!(value, Some(8));
+
+        // Incrementing over the max value is rejected
+        tx::ctx().write(&key, u64::MAX - 1).unwrap();
+        let error = panic::catch_unwind(|| {
+            tx::ctx().increment(&key, 2).unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("IncrementOverflow"));
+    }
+
+    #[test]
+    fn test_tx_transfer() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        let src = address::testing::established_address_1();
+        let dest = address::testing::established_address_2();
+        let token = address::nam();
+        tx_host_env::with(|env| {
+            env.spawn_accounts([&src, &dest, &token]);
+            env.credit_tokens(&src, &token, Amount::native_whole(10));
+        });
+
+        tx::ctx()
+            .transfer(&src, &dest, &token, Amount::native_whole(3))
+            .unwrap();
+
+        let balance = |owner: &address::Address| -> Option<Amount> {
+            tx::ctx()
+                .read(&token::storage_key::balance_key(&token, owner))
+                .unwrap()
+        };
+        assert_eq!(balance(&src), Some(Amount::native_whole(7)));
+        assert_eq!(balance(&dest), Some(Amount::native_whole(3)));
+
+        // Both of the accounts' VPs are scheduled to validate the tx
+        let verifiers = tx_host_env::with(|env| env.verifiers.clone());
+        assert_eq!(verifiers, BTreeSet::from([src.clone(), dest.clone()]));
+
+        // Transferring more than the balance is rejected
+        let error = panic::catch_unwind(|| {
+            tx::ctx()
+                .transfer(&src, &dest, &token, Amount::native_whole(8))
+                .unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("InsufficientBalance"));
+
+        // Transferring to a balance that cannot hold the amount is rejected
+        tx::ctx()
+            .write(
+                &token::storage_key::balance_key(&token, &dest),
+                Amount::max(),
+            )
+            .unwrap();
+        let error = panic::catch_unwind(|| {
+            tx::ctx()
+                .transfer(&src, &dest, &token, Amount::native_whole(1))
+                .unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("BalanceOverflow"));
+        assert_eq!(balance(&src), Some(Amount::native_whole(7)));
+    }
+
+    #[test]
+    fn test_tx_too_many_events() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Lower the limit on the number of emitted events
+        let max_events = 2_u64;
+        tx_host_env::with(|env| {
+            let key = namada::ledger::parameters::storage::get_max_events_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_events.serialize_to_vec())
+                .unwrap();
+        });
+
+        // Emitting up to the limit of events is fine and so is re-emitting
+        // the same event
+        let events = (0..=max_events)
+            .map(|i| namada::types::ibc::IbcEvent {
+                event_type: format!("event{i}"),
+                attributes: Default::default(),
+            })
+            .collect::<Vec<_>>();
+        for event in &events[..max_events as usize] {
+            tx::ctx().emit_ibc_event(event).unwrap();
+        }
+        tx::ctx().emit_ibc_event(&events[0]).unwrap();
+
+        // Emitting one more event is over the limit
+        let error = panic::catch_unwind(|| {
+            tx::ctx()
+                .emit_ibc_event(&events[max_events as usize])
+                .unwrap()
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("TooManyEvents"));
+    }
+
+    #[test]
+    fn test_tx_ibc_send_packet() {
+        use namada::ibc::primitives::proto::Protobuf;
+
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // A well-formed packet is recorded as an event
+        let receiver = address::testing::established_address_1();
+        let packet = ibc::received_packet(
+            ibc::PortId::transfer(),
+            ibc::ChannelId::new(0),
+            ibc::Sequence::from(1),
+            address::nam().to_string(),
+            &receiver,
+        );
+        tx::ctx().ibc_send_packet(&packet.encode_vec()).unwrap();
+        let events = tx_host_env::with(|env| {
+            env.wl_storage.write_log.get_ibc_events().clone()
+        });
+        assert_eq!(
+            events,
+            BTreeSet::from([namada::types::ibc::IbcEvent::send_packet(
+                &packet
+            )])
+        );
+        let event = events.first().unwrap();
+        assert_eq!(event.attributes["packet_sequence"], "1");
+        assert_eq!(event.attributes["packet_dst_channel"], "channel-0");
+
+        // A malformed packet is rejected
+        let error = panic::catch_unwind(|| {
+            tx::ctx().ibc_send_packet(&[1, 2, 3]).unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("InvalidIbcPacket"));
+
+        // And so is a packet without any data
+        let empty_packet = ibc::Packet {
+            data: vec![],
+            ..packet
+        };
+        let error = panic::catch_unwind(|| {
+            tx::ctx()
+                .ibc_send_packet(&empty_packet.encode_vec())
+                .unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("InvalidIbcPacket"));
+    }
+
+    #[test]
+    fn test_tx_preview_vp() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Setup an account that accepts and an account that rejects any tx
+        let accepting = address::testing::established_address_1();
+        let rejecting = address::testing::established_address_2();
+        tx_host_env::with(|env| {
+            for (addr, code) in [
+                (&accepting, TestWasms::VpAlwaysTrue.read_bytes()),
+                (&rejecting, TestWasms::VpAlwaysFalse.read_bytes()),
+            ] {
+                let code_hash = Hash::sha256(&code);
+                let code_len = (code.len() as u64).serialize_to_vec();
+                env.wl_storage
+                    .storage
+                    .write(&Key::wasm_code_len(&code_hash), code_len)
+                    .unwrap();
+                env.store_wasm_code(code);
+                env.wl_storage
+                    .storage
+                    .write(&Key::validity_predicate(addr), code_hash.0)
+                    .unwrap();
+            }
+        });
+
+        // A tx that only writes to an account if its VP would accept it, or
+        // else records the rejection
+        let key = |addr: &Address| {
+            storage::Key::from(addr.to_db_key())
+                .join(&Key::parse("counter").unwrap())
+        };
+        let rejected_key = Key::parse("rejected").unwrap();
+        let apply_tx = |addr: &Address| {
+            let change = (key(addr), Some(1_u64.serialize_to_vec()));
+            if tx::ctx().preview_vp(addr, &[change]).unwrap() {
+                tx::ctx().write(&key(addr), 1_u64).unwrap();
+            } else {
+                tx::ctx().write(&rejected_key, addr.clone()).unwrap();
+            }
+        };
+
+        apply_tx(&accepting);
+        apply_tx(&rejecting);
+        let read = |key: &Key| -> Option<u64> { tx::ctx().read(key).unwrap() };
+        assert_eq!(read(&key(&accepting)), Some(1));
+        assert_eq!(read(&key(&rejecting)), None);
+        let rejected: Option<Address> = tx::ctx().read(&rejected_key).unwrap();
+        assert_eq!(rejected, Some(rejecting.clone()));
+
+        // The proposed changes are not persisted and the VPs' gas is charged
+        // to the tx
+        let gas_before = tx_host_env::with(|env| {
+            u64::from(env.gas_meter.get_tx_consumed_gas())
+        });
+        let change = (key(&rejecting), Some(2_u64.serialize_to_vec()));
+        assert!(!tx::ctx().preview_vp(&rejecting, &[change]).unwrap());
+        assert_eq!(read(&key(&rejecting)), None);
+        let gas_after = tx_host_env::with(|env| {
+            u64::from(env.gas_meter.get_tx_consumed_gas())
+        });
+        assert!(gas_after > gas_before);
+
+        // Previewing an account without a VP aborts the tx
+        let unknown = address::testing::established_address_3();
+        let error = panic::catch_unwind(|| {
+            tx::ctx().preview_vp(&unknown, &[]).unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("MissingVp"));
+    }
+
+    #[test]
+    fn test_tx_preview_vp_limits() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Setup an account that accepts any tx
+        let accepting = address::testing::established_address_1();
+        let max_vp_previews = 3_u64;
+        tx_host_env::with(|env| {
+            let code = TestWasms::VpAlwaysTrue.read_bytes();
+            let code_hash = Hash::sha256(&code);
+            let code_len = (code.len() as u64).serialize_to_vec();
+            env.wl_storage
+                .storage
+                .write(&Key::wasm_code_len(&code_hash), code_len)
+                .unwrap();
+            env.store_wasm_code(code);
+            env.wl_storage
+                .storage
+                .write(&Key::validity_predicate(&accepting), code_hash.0)
+                .unwrap();
+            let key =
+                namada::ledger::parameters::storage::get_max_vp_previews_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_vp_previews.serialize_to_vec())
+                .unwrap();
+        });
+        let consumed_gas = || {
+            tx_host_env::with(|env| {
+                u64::from(env.gas_meter.get_tx_consumed_gas())
+            })
+        };
+
+        // Requesting more previews than the limit aborts the tx
+        let mut preview_gas = 0;
+        for _ in 0..max_vp_previews {
+            let before = consumed_gas();
+            assert!(tx::ctx().preview_vp(&accepting, &[]).unwrap());
+            preview_gas = consumed_gas() - before;
+        }
+        let error = panic::catch_unwind(|| {
+            tx::ctx().preview_vp(&accepting, &[]).unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("TooManyVpPreviews"));
+
+        // Repeated previews run out of gas before reaching the limit
+        tx_host_env::with(|env| {
+            env.host_state = Default::default();
+            env.gas_meter =
+                TxGasMeter::new_from_sub_limit((preview_gas * 3 / 2).into());
+        });
+        assert!(tx::ctx().preview_vp(&accepting, &[]).unwrap());
+        let error = panic::catch_unwind(|| {
+            tx::ctx().preview_vp(&accepting, &[]).unwrap();
+        })
+        .err()
+        .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+        .unwrap();
+        assert!(error.contains("OutOfGas"));
+    }
+
+    #[test]
+    fn test_tx_insert_too_many_verifiers() {
+        // The environment must be initialized first
+        tx_host_env::init();
+
+        // Lower the limit on the number of verifiers
+        let max_verifiers = 4_u64;
+        tx_host_env::with(|env| {
+            let key =
+                namada::ledger::parameters::storage::get_max_verifiers_key();
+            env.wl_storage
+                .storage
+                .write(&key, max_verifiers.serialize_to_vec())
+                .unwrap();
+        });
+
+        for i in 0..max_verifiers {
+            let verifier =
+                address::gen_deterministic_established_address(i.to_string());
+            tx::ctx().insert_verifier(&verifier).unwrap();
+        }
+        // Inserting an already present verifier is fine
+        let verifier =
+            address::gen_deterministic_established_address(0.to_string());
+        tx::ctx().insert_verifier(&verifier).unwrap();
+        assert_eq!(
+            tx_host_env::with(|env| env.verifiers.len()) as u64,
+            max_verifiers,
+        );
+
+        // One more verifier is over the limit
+        let verifier = address::testing::established_address_1();
+        assert!(
+            panic::catch_unwind(|| {
+                tx::ctx().insert_verifier(&verifier).unwrap()
+            })
+            .err()
+            .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+            .unwrap()
+            .contains("TooManyVerifiers")
+        );
+    }
+
     #[test]
     #[should_panic]
     fn test_tx_init_account_with_invalid_vp() {
@@ -483,6 +1527,150 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vp_read_amount_and_address() {
+        let mut tx_env = TestTxEnv::default();
+
+        let addr = address::testing::established_address_1();
+        let native_token = address::nam();
+        let balance_key = token::storage_key::balance_key(&native_token, &addr);
+        let addr_key = storage::Key::from(addr.to_db_key());
+        let owner_key = addr_key.join(&Key::parse("owner").unwrap());
+
+        // Write a balance and an address to storage ...
+        let pre_amount = Amount::native_whole(100);
+        let owner = address::testing::established_address_2();
+        tx_env.wl_storage.write(&balance_key, pre_amount).unwrap();
+        tx_env.wl_storage.write(&owner_key, &owner).unwrap();
+        // ... and commit them
+        tx_env.wl_storage.commit_tx();
+
+        // In a transaction, update the balance and delete the owner
+        let post_amount = Amount::native_whole(42);
+        vp_host_env::init_from_tx(addr, tx_env, |_addr| {
+            tx::ctx().write(&balance_key, post_amount).unwrap();
+            tx::ctx().delete(&owner_key).unwrap();
+        });
+
+        // The values decoded on the host must match the manually decoded ones
+        let amount = vp::CTX.read_amount_pre(&balance_key).unwrap();
+        assert_eq!(amount, Some(pre_amount));
+        assert_eq!(amount, vp::CTX.read_pre(&balance_key).unwrap());
+        let amount = vp::CTX.read_amount_post(&balance_key).unwrap();
+        assert_eq!(amount, Some(post_amount));
+        assert_eq!(amount, vp::CTX.read_post(&balance_key).unwrap());
+
+        let read_owner = vp::CTX.read_address_pre(&owner_key).unwrap();
+        assert_eq!(read_owner, Some(owner));
+        assert_eq!(read_owner, vp::CTX.read_pre(&owner_key).unwrap());
+        assert_eq!(vp::CTX.read_address_post(&owner_key).unwrap(), None);
+
+        // A value that's not an address fails to decode on the host
+        let result =
+            panic::catch_unwind(|| vp::CTX.read_address_pre(&balance_key))
+                .err()
+                .map(|a| a.downcast_ref::<String>().cloned().unwrap());
+        assert!(result.unwrap().contains("EncodingError"));
+    }
+
+    #[test]
+    fn test_vp_get_changes() {
+        let mut tx_env = TestTxEnv::default();
+
+        let addr = address::testing::established_address_1();
+        let other = address::testing::established_address_2();
+        let receiver = address::testing::established_address_3();
+        let token = address::nam();
+        let balance_key =
+            |owner: &Address| token::storage_key::balance_key(&token, owner);
+        let unrelated_key = storage::Key::from(addr.to_db_key())
+            .join(&Key::parse("unrelated").unwrap());
+
+        // Write some balances to storage ...
+        tx_env
+            .wl_storage
+            .write(&balance_key(&addr), Amount::native_whole(100))
+            .unwrap();
+        tx_env
+            .wl_storage
+            .write(&balance_key(&other), Amount::native_whole(50))
+            .unwrap();
+        // ... and commit them
+        tx_env.wl_storage.commit_tx();
+
+        // In a transaction, move some tokens to a new receiver and write an
+        // unrelated key
+        vp_host_env::init_from_tx(addr.clone(), tx_env, |_addr| {
+            tx::ctx()
+                .write(&balance_key(&addr), Amount::native_whole(70))
+                .unwrap();
+            tx::ctx()
+                .write(&balance_key(&other), Amount::native_whole(40))
+                .unwrap();
+            tx::ctx()
+                .write(&balance_key(&receiver), Amount::native_whole(40))
+                .unwrap();
+            tx::ctx().write(&unrelated_key, 1_u64).unwrap();
+        });
+
+        // Only the changes with the balance prefix are returned
+        let changes = vp::CTX
+            .get_changes(&token::storage_key::balance_prefix(&token))
+            .unwrap();
+        let changed_keys: BTreeSet<_> =
+            changes.iter().map(|change| change.key.clone()).collect();
+        assert_eq!(
+            changed_keys,
+            BTreeSet::from([
+                balance_key(&addr),
+                balance_key(&other),
+                balance_key(&receiver)
+            ])
+        );
+
+        // A VP validating each change's delta from the combined diff
+        let validate = |changes: &[namada::types::internal::KeyChange]| {
+            let mut total_change = token::Change::zero();
+            for change in changes {
+                let decode = |value: &Option<Vec<u8>>| {
+                    value
+                        .as_ref()
+                        .map(|value| Amount::try_from_slice(value).unwrap())
+                        .unwrap_or_default()
+                };
+                let (pre, post) = (decode(&change.pre), decode(&change.post));
+                // The combined diff matches the separate reads
+                assert_eq!(
+                    change.pre,
+                    vp::CTX.read_bytes_pre(&change.key).unwrap()
+                );
+                assert_eq!(
+                    change.post,
+                    vp::CTX.read_bytes_post(&change.key).unwrap()
+                );
+                total_change += post.change() - pre.change();
+            }
+            // The tokens are neither minted nor burned
+            total_change.is_zero()
+        };
+        assert!(validate(&changes));
+
+        // A new balance has no prior value
+        let received = changes
+            .iter()
+            .find(|change| change.key == balance_key(&receiver))
+            .unwrap();
+        assert_eq!(received.pre, None);
+        assert_eq!(
+            received.post,
+            Some(Amount::native_whole(40).serialize_to_vec())
+        );
+
+        // A prefix without any changes gives an empty diff
+        let prefix = storage::Key::from(other.to_db_key());
+        assert!(vp::CTX.get_changes(&prefix).unwrap().is_empty());
+    }
+
     #[test]
     fn test_vp_iter_prefix() {
         let mut tx_env = TestTxEnv::default();
@@ -544,6 +1732,177 @@ mod tests {
         itertools::assert_equal(iter_post, expected_post);
     }
 
+    #[test]
+    fn test_vp_iter_resume() {
+        let mut tx_env = TestTxEnv::default();
+
+        let addr = address::testing::established_address_1();
+        let prefix = storage::Key::from(addr.to_db_key())
+            .join(&Key::parse("prefix").unwrap());
+
+        // Write some values to storage ...
+        for i in 0_i32..5 {
+            tx_env
+                .wl_storage
+                .write(&prefix.push(&i).unwrap(), i)
+                .unwrap();
+        }
+        // ... and commit them
+        tx_env.wl_storage.commit_tx();
+
+        // In a transaction, add another key-value under the same prefix
+        let new_key = prefix.push(&5_i32).unwrap();
+        vp_host_env::init_from_tx(addr, tx_env, |_addr| {
+            tx::ctx().write(&new_key, 5_i32).unwrap();
+        });
+
+        let ctx_post = vp::CTX.post();
+        let mut iter = ctx_post.iter_prefix(&prefix).unwrap();
+        // No token is given before any item is returned
+        assert!(vp::CTX.iter_continuation_token(&iter).is_none());
+
+        // Iterate a few entries and take a token
+        let mut keys = vec![];
+        for _ in 0..3 {
+            let (key, _val) = ctx_post.iter_next(&mut iter).unwrap().unwrap();
+            keys.push(key);
+        }
+        let token = vp::CTX.iter_continuation_token(&iter).unwrap();
+
+        // The resumed iterator returns the rest of the entries, including the
+        // one from the write log
+        let mut resumed = vp::CTX.iter_resume(&token).unwrap();
+        while let Some((key, _val)) = ctx_post.iter_next(&mut resumed).unwrap()
+        {
+            keys.push(key);
+        }
+        let expected = (0_i32..6).map(|i| prefix.push(&i).unwrap().to_string());
+        itertools::assert_equal(keys, expected);
+
+        // An exhausted iterator doesn't give a token
+        assert!(vp::CTX.iter_continuation_token(&resumed).is_none());
+    }
+
+    #[test]
+    fn test_vp_iter_prefix_self() {
+        let mut tx_env = TestTxEnv::default();
+
+        let addr = address::testing::established_address_1();
+        let other_addr = address::testing::established_address_2();
+        let sub_prefix = Key::parse("prefix").unwrap();
+        let prefix = storage::Key::from(addr.to_db_key()).join(&sub_prefix);
+        let other_prefix =
+            storage::Key::from(other_addr.to_db_key()).join(&sub_prefix);
+
+        // Write some values under the same sub-prefix of both addresses ...
+        for i in 0_i32..3 {
+            tx_env
+                .wl_storage
+                .write(&prefix.push(&i).unwrap(), i)
+                .unwrap();
+            tx_env
+                .wl_storage
+                .write(&other_prefix.push(&i).unwrap(), -i)
+                .unwrap();
+        }
+        // ... and a value outside of the sub-prefix of the VP's address
+        let outside_key = storage::Key::from(addr.to_db_key())
+            .push(&"other".to_owned())
+            .unwrap();
+        tx_env.wl_storage.write(&outside_key, 100_i32).unwrap();
+        tx_env.wl_storage.commit_tx();
+
+        // Initialize the VP environment via a transaction that adds another
+        // key under the sub-prefix
+        let new_key = prefix.push(&3_i32).unwrap();
+        vp_host_env::init_from_tx(addr, tx_env, |_addr| {
+            tx::ctx().write(&new_key, 3_i32).unwrap();
+        });
+
+        // The VP only sees its own keys in the posterior state
+        let iter = vp::CTX
+            .iter_prefix_self::<i32>(&sub_prefix)
+            .unwrap()
+            .map(|item| item.unwrap());
+        let expected = (0_i32..4).map(|i| (prefix.push(&i).unwrap(), i));
+        itertools::assert_equal(iter, expected);
+
+        // With an empty sub-prefix, the VP iterates its whole sub-space
+        let iter = vp::CTX
+            .iter_prefix_self::<i32>(&Key::default())
+            .unwrap()
+            .map(|item| item.unwrap().0);
+        let expected = (0_i32..4)
+            .map(|i| prefix.push(&i).unwrap())
+            .chain([outside_key]);
+        itertools::assert_equal(iter.sorted(), expected.sorted());
+    }
+
+    #[test]
+    fn test_vp_get_tx_hash() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        let code = vec![4, 3, 2, 1, 0];
+        let mut hashes = vec![];
+        for data in [vec![1, 2, 3, 4], vec![4, 3, 2, 1]] {
+            let expected = vp_host_env::with(|env| {
+                let chain_id = env.wl_storage.storage.chain_id.clone();
+                let mut tx = Tx::new(chain_id, None);
+                tx.add_code(code.clone(), None).add_serialized_data(data);
+                env.tx = tx;
+                env.tx.raw_header_hash()
+            });
+            // The VP sees the hash of the tx that is being validated
+            let hash = vp::CTX.get_tx_hash().unwrap();
+            assert_eq!(hash, expected);
+            hashes.push(hash);
+        }
+        // The same code with different data gives a different hash
+        assert_ne!(hashes[0], hashes[1]);
+    }
+
+    #[test]
+    fn test_vp_get_tx_code_hash() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // A VP that only accepts txs with a whitelisted code
+        let allowed_code = vec![1, 2, 3, 4];
+        let whitelist = BTreeSet::from([Hash::sha256(&allowed_code)]);
+        let validate_tx = || match vp::CTX.get_tx_code_hash().unwrap() {
+            Some(code_hash) => whitelist.contains(&code_hash),
+            None => false,
+        };
+        let set_tx_code = |code: Option<Vec<u8>>| {
+            vp_host_env::with(|env| {
+                let chain_id = env.wl_storage.storage.chain_id.clone();
+                let mut tx = Tx::new(chain_id, None);
+                if let Some(code) = code {
+                    tx.add_code(code, None);
+                }
+                tx.add_serialized_data(vec![]);
+                env.tx = tx;
+            })
+        };
+
+        set_tx_code(Some(allowed_code.clone()));
+        assert_eq!(
+            vp::CTX.get_tx_code_hash().unwrap(),
+            Some(Hash::sha256(&allowed_code))
+        );
+        assert!(validate_tx());
+
+        // Any other tx code is rejected
+        set_tx_code(Some(vec![4, 3, 2, 1]));
+        assert!(!validate_tx());
+
+        // And so is a tx without any code
+        set_tx_code(None);
+        assert_eq!(vp::CTX.get_tx_code_hash().unwrap(), None);
+        assert!(!validate_tx());
+    }
+
     #[test]
     fn test_vp_verify_tx_signature() {
         let mut env = TestVpEnv::default();
@@ -651,6 +2010,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_vp_reject_with_reason() {
+        // The environment must be initialized first
+        vp_host_env::init();
+
+        // Rejecting aborts the VP execution
+        assert!(
+            panic::catch_unwind(|| {
+                namada_vp_prelude::reject_with_reason("not authorized")
+            })
+            .err()
+            .map(|a| a.downcast_ref::<String>().cloned().unwrap())
+            .unwrap()
+            .contains("VpRejected")
+        );
+
+        // The reason is recorded in the sentinel
+        vp_host_env::with(|env| {
+            assert!(env.sentinel.is_rejected());
+            assert_eq!(env.sentinel.rejection_reason(), Some("not authorized"));
+        });
+    }
+
     #[test]
     fn test_vp_eval() {
         // The environment must be initialized first
@@ -13,6 +13,7 @@ use namada::types::address::Address;
 use namada::types::hash::Hash;
 use namada::types::storage::{Key, TxIndex};
 use namada::types::time::DurationSecs;
+use namada::vm::host_env::TxHostState;
 use namada::vm::prefix_iter::PrefixIterators;
 use namada::vm::wasm::run::Error;
 use namada::vm::wasm::{self, TxCache, VpCache};
@@ -53,7 +54,9 @@ pub struct TestTxEnv {
     pub gas_meter: TxGasMeter,
     pub sentinel: TxSentinel,
     pub tx_index: TxIndex,
+    pub fee_payer: Option<Address>,
     pub result_buffer: Option<Vec<u8>>,
+    pub host_state: TxHostState,
     pub vp_wasm_cache: VpCache<WasmCacheRwAccess>,
     pub vp_cache_dir: TempDir,
     pub tx_wasm_cache: TxCache<WasmCacheRwAccess>,
@@ -78,8 +81,10 @@ impl Default for TestTxEnv {
             gas_meter: TxGasMeter::new_from_sub_limit(100_000_000.into()),
             sentinel: TxSentinel::default(),
             tx_index: TxIndex::default(),
+            fee_payer: None,
             verifiers: BTreeSet::default(),
             result_buffer: None,
+            host_state: TxHostState::default(),
             vp_wasm_cache,
             vp_cache_dir,
             tx_wasm_cache,
@@ -231,6 +236,7 @@ impl TestTxEnv {
             &mut self.gas_meter,
             &self.tx_index,
             &self.tx,
+            self.fee_payer.as_ref(),
             &mut self.vp_wasm_cache,
             &mut self.tx_wasm_cache,
         )
@@ -348,7 +354,9 @@ mod native_tx_host_env {
                                 gas_meter,
                                 sentinel,
                                 result_buffer,
+                                host_state,
                                 tx_index,
+                                fee_payer,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                                 tx_wasm_cache,
@@ -365,7 +373,9 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                fee_payer,
                                 result_buffer,
+                                host_state,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
                             );
@@ -385,12 +395,14 @@ mod native_tx_host_env {
                     extern "C" fn extern_fn_name( $($arg: $type),* ) -> $ret {
                         with(|TestTxEnv {
                             tx_index,
+                            fee_payer,
                                 wl_storage,
                                 iterators,
                                 verifiers,
                                 gas_meter,
                                 sentinel,
                                 result_buffer,
+                                host_state,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                                 tx_wasm_cache,
@@ -407,7 +419,9 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                fee_payer,
                                 result_buffer,
+                                host_state,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
                             );
@@ -432,7 +446,9 @@ mod native_tx_host_env {
                                 gas_meter,
                                 sentinel,
                                 result_buffer,
+                                host_state,
                                 tx_index,
+                                fee_payer,
                                 vp_wasm_cache,
                                 vp_cache_dir: _,
                                 tx_wasm_cache,
@@ -449,7 +465,9 @@ mod native_tx_host_env {
                                 sentinel,
                                 tx,
                                 tx_index,
+                                fee_payer,
                                 result_buffer,
+                                host_state,
                                 vp_wasm_cache,
                                 tx_wasm_cache,
                             );
@@ -479,7 +497,10 @@ mod native_tx_host_env {
         val_ptr: u64,
         val_len: u64
     ));
+    native_host_fn!(tx_increment(key_ptr: u64, key_len: u64, delta: u64) -> u64);
+    native_host_fn!(tx_transfer(transfer_ptr: u64, transfer_len: u64));
     native_host_fn!(tx_delete(key_ptr: u64, key_len: u64));
+    native_host_fn!(tx_delete_prefix(prefix_ptr: u64, prefix_len: u64));
     native_host_fn!(tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64);
     native_host_fn!(tx_iter_next(iter_id: u64) -> i64);
     native_host_fn!(tx_insert_verifier(addr_ptr: u64, addr_len: u64));
@@ -500,14 +521,25 @@ mod native_tx_host_env {
     ));
     native_host_fn!(tx_emit_ibc_event(event_ptr: u64, event_len: u64));
     native_host_fn!(tx_get_ibc_events(event_type_ptr: u64, event_type_len: u64) -> i64);
+    native_host_fn!(tx_ibc_send_packet(packet_ptr: u64, packet_len: u64));
+    native_host_fn!(tx_preview_vp(
+        addr_ptr: u64,
+        addr_len: u64,
+        changes_ptr: u64,
+        changes_len: u64
+    ) -> i64);
     native_host_fn!(tx_get_chain_id(result_ptr: u64));
     native_host_fn!(tx_get_block_height() -> u64);
     native_host_fn!(tx_get_tx_index() -> u32);
     native_host_fn!(tx_get_block_header(height: u64) -> i64);
+    native_host_fn!(tx_get_block_time() -> i64);
     native_host_fn!(tx_get_block_hash(result_ptr: u64));
     native_host_fn!(tx_get_block_epoch() -> u64);
     native_host_fn!(tx_get_pred_epochs() -> i64);
     native_host_fn!(tx_get_native_token(result_ptr: u64));
+    native_host_fn!(tx_get_fee_payer() -> i64);
+    native_host_fn!(tx_get_chain_param(name_ptr: u64, name_len: u64) -> i64);
+    native_host_fn!(tx_make_key(segments_ptr: u64, segments_len: u64) -> i64);
     native_host_fn!(tx_log_string(str_ptr: u64, str_len: u64));
     native_host_fn!(tx_charge_gas(used_gas: u64));
     native_host_fn!("non-result", tx_set_commitment_sentinel());
@@ -734,7 +766,9 @@ mod tests {
             gas_meter,
             sentinel,
             result_buffer,
+            host_state,
             tx_index,
+            fee_payer,
             vp_wasm_cache,
             vp_cache_dir: _,
             tx_wasm_cache,
@@ -751,7 +785,9 @@ mod tests {
             sentinel,
             tx,
             tx_index,
+            fee_payer,
             result_buffer,
+            host_state,
             vp_wasm_cache,
             tx_wasm_cache,
         );
@@ -345,20 +345,42 @@ mod native_vp_host_env {
     // Implement all the exported functions from
     // [`namada_vm_env::imports::vp`] `extern "C"` section.
     native_host_fn!(vp_read_pre(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_read_with_proof(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_verify_merkle_proof(
+        root_ptr: u64,
+        root_len: u64,
+        key_ptr: u64,
+        key_len: u64,
+        value_ptr: u64,
+        value_len: u64,
+        proof_ptr: u64,
+        proof_len: u64,
+    ) -> i64);
+    native_host_fn!(vp_get_changes(prefix_ptr: u64, prefix_len: u64) -> i64);
     native_host_fn!(vp_read_post(key_ptr: u64, key_len: u64) -> i64);
     native_host_fn!(vp_read_temp(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_read_amount_pre(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_read_amount_post(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_read_address_pre(key_ptr: u64, key_len: u64) -> i64);
+    native_host_fn!(vp_read_address_post(key_ptr: u64, key_len: u64) -> i64);
     native_host_fn!(vp_result_buffer(result_ptr: u64));
     native_host_fn!(vp_has_key_pre(key_ptr: u64, key_len: u64) -> i64);
     native_host_fn!(vp_has_key_post(key_ptr: u64, key_len: u64) -> i64);
     native_host_fn!(vp_iter_prefix_pre(prefix_ptr: u64, prefix_len: u64) -> u64);
     native_host_fn!(vp_iter_prefix_post(prefix_ptr: u64, prefix_len: u64) -> u64);
+    native_host_fn!(vp_iter_prefix_self(sub_prefix_ptr: u64, sub_prefix_len: u64) -> u64);
     native_host_fn!(vp_iter_next(iter_id: u64) -> i64);
+    native_host_fn!(vp_iter_continuation_token(iter_id: u64) -> i64);
+    native_host_fn!(vp_iter_resume(token_ptr: u64, token_len: u64) -> u64);
     native_host_fn!(vp_get_chain_id(result_ptr: u64));
     native_host_fn!(vp_get_block_height() -> u64);
     native_host_fn!(vp_get_tx_index() -> u32);
     native_host_fn!(vp_get_block_header(height: u64) -> i64);
+    native_host_fn!(vp_get_block_time() -> i64);
+    native_host_fn!(vp_has_verifier(addr_ptr: u64, addr_len: u64) -> i64);
     native_host_fn!(vp_get_block_hash(result_ptr: u64));
     native_host_fn!(vp_get_tx_code_hash(result_ptr: u64));
+    native_host_fn!(vp_get_tx_hash(result_ptr: u64));
     native_host_fn!(vp_get_block_epoch() -> u64);
     native_host_fn!(vp_get_pred_epochs() -> i64);
     native_host_fn!(vp_get_native_token(result_ptr: u64));
@@ -369,6 +391,7 @@ mod native_vp_host_env {
             input_data_len: u64,
         ) -> i64);
     native_host_fn!(vp_log_string(str_ptr: u64, str_len: u64));
+    native_host_fn!(vp_reject(reason_ptr: u64, reason_len: u64));
     native_host_fn!(vp_verify_tx_section_signature(
         hash_list_ptr: u64,
         hash_list_len: u64,
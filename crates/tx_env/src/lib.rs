@@ -1,11 +1,12 @@
 //! Transaction environment contains functions that can be called from
 //! inside a tx.
 
-use namada_core::borsh::BorshSerialize;
+use namada_core::borsh::{BorshDeserialize, BorshSerialize};
 use namada_core::types::address::Address;
 use namada_core::types::ibc::IbcEvent;
-use namada_core::types::storage;
-use namada_storage::{Result, StorageRead, StorageWrite};
+use namada_core::types::time::DateTimeUtc;
+use namada_core::types::{storage, token};
+use namada_storage::{Result, ResultExt, StorageRead, StorageWrite};
 
 /// Transaction host functions
 pub trait TxEnv: StorageRead + StorageWrite {
@@ -24,6 +25,27 @@ pub trait TxEnv: StorageRead + StorageWrite {
         val: impl AsRef<[u8]>,
     ) -> Result<()>;
 
+    /// Increment the Borsh-encoded `u64` value at the given key by the given
+    /// delta in a single host call, starting from `0` if the key has no
+    /// value. Returns the new value. The tx is aborted if the value isn't a
+    /// `u64` or the addition overflows.
+    fn increment(&mut self, key: &storage::Key, delta: u64) -> Result<u64>;
+
+    /// Transfer the given amount of a token from the `src` to the `dest`
+    /// account in a single host call. Both accounts are inserted as verifiers.
+    /// The tx is aborted if the `src` balance is insufficient or the `dest`
+    /// balance overflows.
+    fn transfer(
+        &mut self,
+        src: &Address,
+        dest: &Address,
+        token: &Address,
+        amount: token::Amount,
+    ) -> Result<()>;
+
+    /// Delete all the keys matching the given prefix and their values.
+    fn delete_prefix(&mut self, prefix: &storage::Key) -> Result<()>;
+
     /// Insert a verifier address. This address must exist on chain, otherwise
     /// the transaction will be rejected.
     ///
@@ -51,9 +73,55 @@ pub trait TxEnv: StorageRead + StorageWrite {
     /// Emit an IBC event. On multiple calls, these emitted event will be added.
     fn emit_ibc_event(&mut self, event: &IbcEvent) -> Result<()>;
 
+    /// Send an IBC packet encoded with Protobuf to be relayed. The packet is
+    /// validated and recorded as a `send_packet` IBC event. The tx is aborted
+    /// if the packet is malformed.
+    fn ibc_send_packet(&mut self, packet: &[u8]) -> Result<()>;
+
+    /// Preview whether the validity predicate of the given account would
+    /// accept the tx's changes so far together with the proposed writes
+    /// (`Some`) and deletions (`None`) of the given keys. Nothing is persisted
+    /// and the gas used by the validity predicate is charged to the tx.
+    fn preview_vp(
+        &mut self,
+        addr: &Address,
+        changes: &[(storage::Key, Option<Vec<u8>>)],
+    ) -> Result<bool>;
+
     /// Request to charge the provided amount of gas for the current transaction
     fn charge_gas(&mut self, used_gas: u64) -> Result<()>;
 
+    /// Read the encoded value of the protocol parameter with the given name,
+    /// if it has a value.
+    fn read_chain_param_bytes(&self, name: &str) -> Result<Option<Vec<u8>>>;
+
+    /// Read the value of the protocol parameter with the given name and
+    /// decode it with Borsh.
+    fn read_chain_param<T: BorshDeserialize>(
+        &self,
+        name: &str,
+    ) -> Result<Option<T>> {
+        match self.read_chain_param_bytes(name)? {
+            Some(bytes) => {
+                T::try_from_slice(&bytes).into_storage_result().map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Make a storage key from the given segments with the same encoding as
+    /// the host uses. Fails when the segments don't form a valid key.
+    fn make_key(&self, segments: &[storage::DbKeySeg]) -> Result<storage::Key>;
+
+    /// Get the time of the block to which the current transaction is being
+    /// applied. The time is the same for all the transactions of a block.
+    fn get_block_time(&self) -> Result<DateTimeUtc>;
+
+    /// Get the address paying the fees of the current transaction, as
+    /// established by the node from the wrapper. Returns `None` if the
+    /// transaction has no fee payer, e.g. a governance proposal code.
+    fn get_fee_payer(&self) -> Result<Option<Address>>;
+
     /// Get IBC events with a event type
     fn get_ibc_events(
         &self,
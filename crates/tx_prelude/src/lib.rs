@@ -286,6 +286,39 @@ impl TxEnv for Ctx {
         Ok(())
     }
 
+    fn increment(
+        &mut self,
+        key: &storage::Key,
+        delta: u64,
+    ) -> Result<u64, Error> {
+        let key = key.to_string();
+        Ok(unsafe {
+            namada_tx_increment(key.as_ptr() as _, key.len() as _, delta)
+        })
+    }
+
+    fn transfer(
+        &mut self,
+        src: &Address,
+        dest: &Address,
+        token: &Address,
+        amount: token::Amount,
+    ) -> Result<(), Error> {
+        let transfer = (src, dest, token, amount).serialize_to_vec();
+        unsafe {
+            namada_tx_transfer(transfer.as_ptr() as _, transfer.len() as _)
+        };
+        Ok(())
+    }
+
+    fn delete_prefix(&mut self, prefix: &storage::Key) -> Result<(), Error> {
+        let prefix = prefix.to_string();
+        unsafe {
+            namada_tx_delete_prefix(prefix.as_ptr() as _, prefix.len() as _)
+        };
+        Ok(())
+    }
+
     fn insert_verifier(&mut self, addr: &Address) -> Result<(), Error> {
         let addr = addr.encode();
         unsafe {
@@ -351,11 +384,75 @@ impl TxEnv for Ctx {
         Ok(())
     }
 
+    fn ibc_send_packet(&mut self, packet: &[u8]) -> Result<(), Error> {
+        unsafe {
+            namada_tx_ibc_send_packet(packet.as_ptr() as _, packet.len() as _)
+        };
+        Ok(())
+    }
+
+    fn preview_vp(
+        &mut self,
+        addr: &Address,
+        changes: &[(storage::Key, Option<Vec<u8>>)],
+    ) -> Result<bool, Error> {
+        let addr = addr.encode();
+        let changes = changes.serialize_to_vec();
+        let result = unsafe {
+            namada_tx_preview_vp(
+                addr.as_ptr() as _,
+                addr.len() as _,
+                changes.as_ptr() as _,
+                changes.len() as _,
+            )
+        };
+        Ok(HostEnvResult::is_success(result))
+    }
+
     fn charge_gas(&mut self, used_gas: u64) -> Result<(), Error> {
         unsafe { namada_tx_charge_gas(used_gas) };
         Ok(())
     }
 
+    fn read_chain_param_bytes(
+        &self,
+        name: &str,
+    ) -> Result<Option<Vec<u8>>, Error> {
+        let read_result = unsafe {
+            namada_tx_get_chain_param(name.as_ptr() as _, name.len() as _)
+        };
+        Ok(read_from_buffer(read_result, namada_tx_result_buffer))
+    }
+
+    fn make_key(
+        &self,
+        segments: &[storage::DbKeySeg],
+    ) -> Result<storage::Key, Error> {
+        let segments = segments.serialize_to_vec();
+        let read_result = unsafe {
+            namada_tx_make_key(segments.as_ptr() as _, segments.len() as _)
+        };
+        let bytes = read_from_buffer(read_result, namada_tx_result_buffer)
+            .ok_or(Error::SimpleMessage("Invalid storage key segments"))?;
+        let key = String::from_utf8(bytes).into_storage_result()?;
+        storage::Key::parse(key).into_storage_result()
+    }
+
+    fn get_block_time(&self) -> Result<time::DateTimeUtc, Error> {
+        let read_result = unsafe { namada_tx_get_block_time() };
+        let bytes = read_from_buffer(read_result, namada_tx_result_buffer)
+            .ok_or(Error::SimpleMessage("The block header is not available"))?;
+        time::DateTimeUtc::try_from_slice(&bytes[..]).into_storage_result()
+    }
+
+    fn get_fee_payer(&self) -> Result<Option<Address>, Error> {
+        let read_result = unsafe { namada_tx_get_fee_payer() };
+        read_from_buffer(read_result, namada_tx_result_buffer)
+            .map(|bytes| Address::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
     fn get_ibc_events(
         &self,
         event_type: impl AsRef<str>,
@@ -44,9 +44,24 @@ pub mod tx {
             val_len: u64,
         );
 
+        // Increment the `u64` value at the given key by the given delta,
+        // returns the new value
+        pub fn namada_tx_increment(
+            key_ptr: u64,
+            key_len: u64,
+            delta: u64,
+        ) -> u64;
+
+        // Transfer a token amount between two accounts, given as Borsh-encoded
+        // source, target, token and amount
+        pub fn namada_tx_transfer(transfer_ptr: u64, transfer_len: u64);
+
         // Delete the given key and its value
         pub fn namada_tx_delete(key_ptr: u64, key_len: u64);
 
+        // Delete all the keys matching the given prefix and their values
+        pub fn namada_tx_delete_prefix(prefix_ptr: u64, prefix_len: u64);
+
         // Get an ID of a data iterator with key prefix, ordered by storage
         // keys.
         pub fn namada_tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64;
@@ -88,6 +103,18 @@ pub mod tx {
             event_type_len: u64,
         ) -> i64;
 
+        // Send an IBC packet
+        pub fn namada_tx_ibc_send_packet(packet_ptr: u64, packet_len: u64);
+
+        // Preview whether the VP of the given address would accept the
+        // proposed changes, returns 1 if accepted or -1 otherwise
+        pub fn namada_tx_preview_vp(
+            addr_ptr: u64,
+            addr_len: u64,
+            changes_ptr: u64,
+            changes_len: u64,
+        ) -> i64;
+
         // Get the chain ID
         pub fn namada_tx_get_chain_id(result_ptr: u64);
 
@@ -97,6 +124,9 @@ pub mod tx {
         // Get the current block header
         pub fn namada_tx_get_block_header(height: u64) -> i64;
 
+        // Get the current block time
+        pub fn namada_tx_get_block_time() -> i64;
+
         // Get the current block hash
         pub fn namada_tx_get_block_hash(result_ptr: u64);
 
@@ -112,6 +142,17 @@ pub mod tx {
         // Get the native token address
         pub fn namada_tx_get_native_token(result_ptr: u64);
 
+        // Get the fee payer address of the current tx, returns the size of
+        // the encoded address placed in the result buffer, or -1 if the tx
+        // has no fee payer
+        pub fn namada_tx_get_fee_payer() -> i64;
+
+        // Get the value of a protocol parameter by its name
+        pub fn namada_tx_get_chain_param(name_ptr: u64, name_len: u64) -> i64;
+
+        // Make a storage key from its segments
+        pub fn namada_tx_make_key(segments_ptr: u64, segments_len: u64) -> i64;
+
         // Requires a node running with "Info" log level
         pub fn namada_tx_log_string(str_ptr: u64, str_len: u64);
 
@@ -156,6 +197,30 @@ pub mod vp {
         // we know its size.
         pub fn namada_vp_read_pre(key_ptr: u64, key_len: u64) -> i64;
 
+        // Read variable-length last committed state together with the value's
+        // Merkle proof, returns the size of the Borsh encoded result, or -1 if
+        // the key is not present. If a value is found, the result will be
+        // placed in the result buffer.
+        pub fn namada_vp_read_with_proof(key_ptr: u64, key_len: u64) -> i64;
+
+        // Verify that a Merkle proof proves the existence of a key with the
+        // given value against a root, returns 1 if it does or -1 otherwise.
+        pub fn namada_vp_verify_merkle_proof(
+            root_ptr: u64,
+            root_len: u64,
+            key_ptr: u64,
+            key_len: u64,
+            value_ptr: u64,
+            value_len: u64,
+            proof_ptr: u64,
+            proof_len: u64,
+        ) -> i64;
+
+        // Get the changed keys with the given prefix with their prior and
+        // posterior values, returns the size of the Borsh encoded result,
+        // which will be placed in the result buffer.
+        pub fn namada_vp_get_changes(prefix_ptr: u64, prefix_len: u64) -> i64;
+
         // Read variable-length posterior state when we don't know the size
         // up-front, returns the size of the value (can be 0), or -1 if
         // the key is not present. If a value is found, it will be placed in the
@@ -170,6 +235,26 @@ pub mod vp {
         // we know its size.
         pub fn namada_vp_read_temp(key_ptr: u64, key_len: u64) -> i64;
 
+        // Read and decode a token amount in prior state, returns the size of
+        // the Borsh encoded amount, or -1 if the key is not present. If a
+        // value is found, it will be placed in the result buffer.
+        pub fn namada_vp_read_amount_pre(key_ptr: u64, key_len: u64) -> i64;
+
+        // Read and decode a token amount in posterior state, returns the size
+        // of the Borsh encoded amount, or -1 if the key is not present. If a
+        // value is found, it will be placed in the result buffer.
+        pub fn namada_vp_read_amount_post(key_ptr: u64, key_len: u64) -> i64;
+
+        // Read and decode an address in prior state, returns the size of the
+        // Borsh encoded address, or -1 if the key is not present. If a value
+        // is found, it will be placed in the result buffer.
+        pub fn namada_vp_read_address_pre(key_ptr: u64, key_len: u64) -> i64;
+
+        // Read and decode an address in posterior state, returns the size of
+        // the Borsh encoded address, or -1 if the key is not present. If a
+        // value is found, it will be placed in the result buffer.
+        pub fn namada_vp_read_address_post(key_ptr: u64, key_len: u64) -> i64;
+
         // Read a value from result buffer.
         pub fn namada_vp_result_buffer(result_ptr: u64);
 
@@ -193,6 +278,13 @@ pub mod vp {
             prefix_len: u64,
         ) -> u64;
 
+        // Get an ID of a data iterator with key prefix under the VP's own
+        // address in posterior state, ordered by storage keys.
+        pub fn namada_vp_iter_prefix_self(
+            sub_prefix_ptr: u64,
+            sub_prefix_len: u64,
+        ) -> u64;
+
         // Read variable-length iterator's next value when we don't know the
         // size up-front, returns the size of the value (can be 0), or
         // -1 if the key is not present. If a value is found, it will be
@@ -200,6 +292,16 @@ pub mod vp {
         // buffer for it before we know its size.
         pub fn namada_vp_iter_next(iter_id: u64) -> i64;
 
+        // Get a token to resume the iteration of the given iterator, returns
+        // the size of the token, or -1 if there's no token because no value
+        // has been returned from the iterator yet or it's exhausted. If a
+        // token is found, it will be placed in the result buffer.
+        pub fn namada_vp_iter_continuation_token(iter_id: u64) -> i64;
+
+        // Get an ID of a data iterator resumed from a continuation token,
+        // ordered by storage keys.
+        pub fn namada_vp_iter_resume(token_ptr: u64, token_len: u64) -> u64;
+
         // Get the chain ID
         pub fn namada_vp_get_chain_id(result_ptr: u64);
 
@@ -209,12 +311,21 @@ pub mod vp {
         // Get the current block header
         pub fn namada_vp_get_block_header(height: u64) -> i64;
 
+        // Get the current block time
+        pub fn namada_vp_get_block_time() -> i64;
+
+        // Check if the given address is a verifier of the tx
+        pub fn namada_vp_has_verifier(addr_ptr: u64, addr_len: u64) -> i64;
+
         // Get the current block hash
         pub fn namada_vp_get_block_hash(result_ptr: u64);
 
         // Get the current tx hash
         pub fn namada_vp_get_tx_code_hash(result_ptr: u64);
 
+        // Get the hash of the tx being validated
+        pub fn namada_vp_get_tx_hash(result_ptr: u64);
+
         // Get the current block epoch
         pub fn namada_vp_get_block_epoch() -> u64;
 
@@ -236,6 +347,9 @@ pub mod vp {
         // Requires a node running with "Info" log level
         pub fn namada_vp_log_string(str_ptr: u64, str_len: u64);
 
+        // Reject the tx with the given reason and abort the VP execution
+        pub fn namada_vp_reject(reason_ptr: u64, reason_len: u64);
+
         // Verify the signatures of a tx
         pub fn namada_vp_verify_tx_section_signature(
             hash_list_ptr: u64,
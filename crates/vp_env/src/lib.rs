@@ -11,10 +11,12 @@ use namada_core::types::hash::Hash;
 use namada_core::types::ibc::{
     get_shielded_transfer, IbcEvent, MsgShieldedTransfer, EVENT_TYPE_PACKET,
 };
+use namada_core::types::internal::{KeyChange, ValueWithProof};
 use namada_core::types::storage::{
     BlockHash, BlockHeight, Epoch, Epochs, Header, Key, TxIndex,
 };
-use namada_core::types::token::Transfer;
+use namada_core::types::time::DateTimeUtc;
+use namada_core::types::token::{Amount, Transfer};
 use namada_storage::{OptionExt, ResultExt, StorageRead};
 use namada_tx::Tx;
 
@@ -72,6 +74,10 @@ where
     /// current transaction is being applied.
     fn get_block_hash(&self) -> Result<BlockHash, namada_storage::Error>;
 
+    /// Getting the block time. The time is that of the block to which the
+    /// current transaction is being applied.
+    fn get_block_time(&self) -> Result<DateTimeUtc, namada_storage::Error>;
+
     /// Getting the block epoch. The epoch is that of the block to which the
     /// current transaction is being applied.
     fn get_block_epoch(&self) -> Result<Epoch, namada_storage::Error>;
@@ -110,9 +116,47 @@ where
         input_data: Tx,
     ) -> Result<bool, namada_storage::Error>;
 
-    /// Get a tx hash
+    /// Check if the given address is one of the verifiers of the transaction,
+    /// i.e. if its validity predicate is triggered by the transaction.
+    fn has_verifier(
+        &self,
+        addr: &Address,
+    ) -> Result<bool, namada_storage::Error>;
+
+    /// Storage read of the last committed block state together with the
+    /// Merkle proof of the value's existence against the committed root. The
+    /// changes from the current block are not visible.
+    fn read_with_proof(
+        &self,
+        key: &Key,
+    ) -> Result<Option<ValueWithProof>, namada_storage::Error>;
+
+    /// Verify that the Merkle proof, encoded like the proofs of
+    /// [`VpEnv::read_with_proof`], proves that the key has the given value in
+    /// a Merkle tree with the given root.
+    fn verify_merkle_proof(
+        &self,
+        root: &Hash,
+        key: &Key,
+        value: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, namada_storage::Error>;
+
+    /// Get the changed keys with the given prefix together with their values
+    /// in the prior and the posterior state in a single call. The keys with a
+    /// temporary value are not included.
+    fn get_changes(
+        &self,
+        prefix: &Key,
+    ) -> Result<Vec<KeyChange>, namada_storage::Error>;
+
+    /// Get the hash of the code of the tx being validated, if any
     fn get_tx_code_hash(&self) -> Result<Option<Hash>, namada_storage::Error>;
 
+    /// Get the hash of the transaction being validated, which commits to all
+    /// of its sections, including the code and the data
+    fn get_tx_hash(&self) -> Result<Hash, namada_storage::Error>;
+
     /// Get the masp tx part of the shielded action
     fn get_shielded_action(
         &self,
@@ -196,6 +240,42 @@ where
         self.post().read_bytes(key)
     }
 
+    /// Storage read prior state token amount (before tx execution). The wasm
+    /// environment decodes the amount on the host side.
+    fn read_amount_pre(
+        &'view self,
+        key: &Key,
+    ) -> Result<Option<Amount>, namada_storage::Error> {
+        self.read_pre(key)
+    }
+
+    /// Storage read posterior state token amount (after tx execution). The
+    /// wasm environment decodes the amount on the host side.
+    fn read_amount_post(
+        &'view self,
+        key: &Key,
+    ) -> Result<Option<Amount>, namada_storage::Error> {
+        self.read_post(key)
+    }
+
+    /// Storage read prior state address (before tx execution). The wasm
+    /// environment decodes the address on the host side.
+    fn read_address_pre(
+        &'view self,
+        key: &Key,
+    ) -> Result<Option<Address>, namada_storage::Error> {
+        self.read_pre(key)
+    }
+
+    /// Storage read posterior state address (after tx execution). The wasm
+    /// environment decodes the address on the host side.
+    fn read_address_post(
+        &'view self,
+        key: &Key,
+    ) -> Result<Option<Address>, namada_storage::Error> {
+        self.read_post(key)
+    }
+
     /// Storage `has_key` in prior state (before tx execution). It will try to
     /// read from the storage.
     fn has_key_pre(
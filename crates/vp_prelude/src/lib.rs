@@ -24,7 +24,7 @@ pub use namada_core::borsh::{
 pub use namada_core::types::address::Address;
 use namada_core::types::chain::CHAIN_ID_LENGTH;
 use namada_core::types::hash::{Hash, HASH_LENGTH};
-use namada_core::types::internal::HostEnvResult;
+use namada_core::types::internal::{HostEnvResult, KeyChange, ValueWithProof};
 use namada_core::types::storage::{
     BlockHash, BlockHeight, Epoch, Epochs, Header, TxIndex, BLOCK_HASH_LENGTH,
 };
@@ -159,6 +159,84 @@ impl Ctx {
     pub fn post(&self) -> CtxPostStorageRead<'_> {
         CtxPostStorageRead { _ctx: self }
     }
+
+    /// Iterate Borsh encoded items in the posterior storage (state after tx
+    /// execution) under the VP's own address, ordered by the storage keys. The
+    /// given `sub_prefix` is appended to the VP's address to form the prefix
+    /// and it may be empty to iterate the whole sub-space of the address.
+    pub fn iter_prefix_self<T>(
+        &self,
+        sub_prefix: &storage::Key,
+    ) -> EnvResult<impl Iterator<Item = EnvResult<(storage::Key, T)>> + '_>
+    where
+        T: BorshDeserialize,
+    {
+        let sub_prefix = sub_prefix.to_string();
+        let iter_id = unsafe {
+            namada_vp_iter_prefix_self(
+                sub_prefix.as_ptr() as _,
+                sub_prefix.len() as _,
+            )
+        };
+        let post = self.post();
+        let mut iter = KeyValIterator(iter_id, PhantomData);
+        let iter = std::iter::from_fn(move || {
+            match post.iter_next(&mut iter) {
+                Ok(Some((key, val))) => {
+                    let key =
+                        match storage::Key::parse(key).into_storage_result() {
+                            Ok(key) => key,
+                            Err(err) => {
+                                // Propagate key encoding errors into
+                                // Iterator's Item
+                                return Some(Err(err));
+                            }
+                        };
+                    let val =
+                        match T::try_from_slice(&val).into_storage_result() {
+                            Ok(val) => val,
+                            Err(err) => {
+                                // Propagate val encoding errors into
+                                // Iterator's Item
+                                return Some(Err(err));
+                            }
+                        };
+                    Some(Ok((key, val)))
+                }
+                Ok(None) => None,
+                Err(err) => {
+                    // Propagate `iter_next` errors into Iterator's Item
+                    Some(Err(err))
+                }
+            }
+        });
+        Ok(iter)
+    }
+
+    /// Get an opaque token from which the iteration of the given prefix
+    /// iterator can be resumed with [`Ctx::iter_resume`]. Returns `None` when
+    /// no item has been returned from the iterator yet or it's exhausted.
+    pub fn iter_continuation_token<T>(
+        &self,
+        iter: &KeyValIterator<T>,
+    ) -> Option<Vec<u8>> {
+        let read_result = unsafe { namada_vp_iter_continuation_token(iter.0) };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+    }
+
+    /// Resume a prefix iteration from a token taken with
+    /// [`Ctx::iter_continuation_token`], after the last key that was returned
+    /// before the token was taken. The resumed iterator is over the same
+    /// prefix and state (prior or posterior) as the original one.
+    pub fn iter_resume(
+        &self,
+        token: &[u8],
+    ) -> EnvResult<KeyValIterator<(String, Vec<u8>)>> {
+        let iter_id = unsafe {
+            namada_vp_iter_resume(token.as_ptr() as _, token.len() as _)
+        };
+        Ok(KeyValIterator(iter_id, PhantomData))
+    }
 }
 
 /// Read access to the prior storage (state before tx execution) via
@@ -191,6 +269,17 @@ pub fn reject() -> VpResult {
     Ok(false)
 }
 
+/// Reject a transaction with the given reason. Unlike [`reject`], this aborts
+/// the execution of the VP immediately, so it can be called from anywhere in
+/// the VP's call stack. The reason is recorded by the host.
+pub fn reject_with_reason(reason: impl AsRef<str>) -> ! {
+    let reason = reason.as_ref();
+    unsafe {
+        namada_vp_reject(reason.as_ptr() as _, reason.len() as _);
+    }
+    unreachable!("The host aborts the VP execution on rejection")
+}
+
 #[derive(Debug)]
 pub struct KeyValIterator<T>(pub u64, pub PhantomData<T>);
 
@@ -251,6 +340,130 @@ impl<'view> VpEnv<'view> for Ctx {
         get_block_hash()
     }
 
+    fn get_block_time(&self) -> Result<time::DateTimeUtc, Error> {
+        let read_result = unsafe { namada_vp_get_block_time() };
+        let bytes = read_from_buffer(read_result, namada_vp_result_buffer)
+            .ok_or(Error::SimpleMessage("The block header is not available"))?;
+        time::DateTimeUtc::try_from_slice(&bytes[..]).into_storage_result()
+    }
+
+    fn has_verifier(&self, addr: &Address) -> Result<bool, Error> {
+        let addr = addr.encode();
+        let found = unsafe {
+            namada_vp_has_verifier(addr.as_ptr() as _, addr.len() as _)
+        };
+        Ok(HostEnvResult::is_success(found))
+    }
+
+    fn read_with_proof(
+        &self,
+        key: &storage::Key,
+    ) -> Result<Option<ValueWithProof>, Error> {
+        let key = key.to_string();
+        let read_result = unsafe {
+            namada_vp_read_with_proof(key.as_ptr() as _, key.len() as _)
+        };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|bytes| ValueWithProof::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
+    fn verify_merkle_proof(
+        &self,
+        root: &Hash,
+        key: &storage::Key,
+        value: &[u8],
+        proof: &[u8],
+    ) -> Result<bool, Error> {
+        let root = root.serialize_to_vec();
+        let key = key.to_string();
+        let verified = unsafe {
+            namada_vp_verify_merkle_proof(
+                root.as_ptr() as _,
+                root.len() as _,
+                key.as_ptr() as _,
+                key.len() as _,
+                value.as_ptr() as _,
+                value.len() as _,
+                proof.as_ptr() as _,
+                proof.len() as _,
+            )
+        };
+        Ok(HostEnvResult::is_success(verified))
+    }
+
+    fn get_changes(
+        &self,
+        prefix: &storage::Key,
+    ) -> Result<Vec<KeyChange>, Error> {
+        let prefix = prefix.to_string();
+        let read_result = unsafe {
+            namada_vp_get_changes(prefix.as_ptr() as _, prefix.len() as _)
+        };
+        match read_from_buffer(read_result, namada_vp_result_buffer) {
+            Some(bytes) => Vec::<KeyChange>::try_from_slice(&bytes[..])
+                .into_storage_result(),
+            None => Ok(vec![]),
+        }
+    }
+
+    fn read_amount_pre(
+        &'view self,
+        key: &storage::Key,
+    ) -> Result<Option<token::Amount>, Error> {
+        let key = key.to_string();
+        let read_result = unsafe {
+            namada_vp_read_amount_pre(key.as_ptr() as _, key.len() as _)
+        };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|bytes| token::Amount::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
+    fn read_amount_post(
+        &'view self,
+        key: &storage::Key,
+    ) -> Result<Option<token::Amount>, Error> {
+        let key = key.to_string();
+        let read_result = unsafe {
+            namada_vp_read_amount_post(key.as_ptr() as _, key.len() as _)
+        };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|bytes| token::Amount::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
+    fn read_address_pre(
+        &'view self,
+        key: &storage::Key,
+    ) -> Result<Option<Address>, Error> {
+        let key = key.to_string();
+        let read_result = unsafe {
+            namada_vp_read_address_pre(key.as_ptr() as _, key.len() as _)
+        };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|bytes| Address::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
+    fn read_address_post(
+        &'view self,
+        key: &storage::Key,
+    ) -> Result<Option<Address>, Error> {
+        let key = key.to_string();
+        let read_result = unsafe {
+            namada_vp_read_address_post(key.as_ptr() as _, key.len() as _)
+        };
+        read_from_buffer(read_result, namada_vp_result_buffer)
+            .map(|bytes| Address::try_from_slice(&bytes[..]))
+            .transpose()
+            .into_storage_result()
+    }
+
     fn get_block_epoch(&self) -> Result<Epoch, Error> {
         // Both `CtxPreStorageRead` and `CtxPostStorageRead` have the same impl
         get_block_epoch()
@@ -325,6 +538,16 @@ impl<'view> VpEnv<'view> for Ctx {
         })
     }
 
+    fn get_tx_hash(&self) -> Result<Hash, Error> {
+        let result = Vec::with_capacity(HASH_LENGTH);
+        unsafe {
+            namada_vp_get_tx_hash(result.as_ptr() as _);
+        }
+        let slice =
+            unsafe { slice::from_raw_parts(result.as_ptr(), HASH_LENGTH) };
+        Ok(Hash::try_from(slice).expect("Cannot convert the hash"))
+    }
+
     fn charge_gas(&self, used_gas: u64) -> Result<(), Error> {
         unsafe { namada_vp_charge_gas(used_gas) };
         Ok(())